@@ -0,0 +1,154 @@
+use std::collections::BTreeSet;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config,
+    game::Game,
+    mission::MissionState,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+const FILE: &str = "achievements.toml";
+
+const PROMPT: &str = "enter  back    q  quit";
+
+/// A milestone unlocked once and kept across runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Achievement {
+    FirstApple,
+    Length50,
+    Survivor,
+    Untouched,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::FirstApple,
+        Achievement::Length50,
+        Achievement::Survivor,
+        Achievement::Untouched,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstApple => "First bite",
+            Achievement::Length50 => "Long boi",
+            Achievement::Survivor => "Survivor",
+            Achievement::Untouched => "Untouched",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Achievement::FirstApple => "eat something",
+            Achievement::Length50 => "grow to length 50",
+            Achievement::Survivor => "stay alive for 5 minutes",
+            Achievement::Untouched => "complete a mission without touching a wall",
+        }
+    }
+
+    /// Whether `game` has done what it takes.
+    pub fn earned(self, game: &Game) -> bool {
+        match self {
+            Achievement::FirstApple => game.stats.apples > 0,
+            Achievement::Length50 => game.player.len() >= 50,
+            Achievement::Survivor => game.stats.time >= 300.,
+            Achievement::Untouched => {
+                let complete = game
+                    .mission
+                    .as_ref()
+                    .is_some_and(|mission| mission.state == MissionState::Complete);
+                complete && game.stats.wall_hits == 0
+            }
+        }
+    }
+}
+
+/// The achievements unlocked so far, kept in
+/// `$XDG_DATA_HOME/snake/achievements.toml`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Achievements {
+    unlocked: BTreeSet<Achievement>,
+    /// Whether anything's been unlocked since the last save.
+    #[serde(skip)]
+    pub unsaved: bool,
+}
+
+impl Achievements {
+    /// Loads the unlocked achievements, treating a missing file as none yet.
+    pub fn load() -> Result<Achievements, String> {
+        config::load_data(FILE)
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        config::save_data(FILE, self)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Unlocks `achievement`, returning false if it already was.
+    pub fn unlock(&mut self, achievement: Achievement) -> bool {
+        let new = self.unlocked.insert(achievement);
+        self.unsaved |= new;
+        new
+    }
+}
+
+/// Lists every achievement and whether it's been unlocked.
+#[derive(Debug, Clone)]
+pub struct AchievementsScreen {
+    pub achievements: Achievements,
+}
+
+impl AchievementsScreen {
+    pub fn new() -> Self {
+        Self {
+            achievements: Achievements::load().unwrap_or_default(),
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let lines: Vec<(bool, String)> = Achievement::ALL
+            .iter()
+            .map(|&achievement| {
+                let unlocked = self.achievements.has(achievement);
+                let mark = if unlocked { "[x]" } else { "[ ]" };
+                let line = format!(
+                    "{mark} {:<12} {}",
+                    achievement.name(),
+                    achievement.describe()
+                );
+                (unlocked, line)
+            })
+            .collect();
+        let block_height = lines.len() as u16 + 4;
+        let mut row = height.saturating_sub(block_height) / 2;
+        frame.print_centered(row, "ACHIEVEMENTS", colors.head);
+        row += 2;
+        let widest = lines.iter().map(|(_, line)| line.len()).max().unwrap_or(0);
+        let col = width.saturating_sub(widest as u16) / 2;
+        for (unlocked, line) in &lines {
+            let fg = if *unlocked { colors.head } else { colors.wall };
+            frame.print_fg(col, row, line, fg);
+            row += 1;
+        }
+        row += 1;
+        frame.print_centered(row, PROMPT, colors.wall);
+        frame
+    }
+}
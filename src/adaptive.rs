@@ -0,0 +1,45 @@
+use crate::events::GameEvent;
+
+/// Apples in a row without a slip before play gets a notch harder.
+const STREAK: u32 = 5;
+
+/// Notches either side of the chosen difficulty it can go.
+const MAX_NOTCHES: i32 = 4;
+
+/// Keeps track of how the player's doing from what happens in their runs,
+/// for the adaptive difficulty option: a clean streak of apples makes play a
+/// notch harder and losing a life makes it a notch easier, carrying over
+/// from one run to the next.
+#[derive(Clone, Debug, Default)]
+pub struct Adaptive {
+    /// Notches harder than the chosen difficulty, negative for easier.
+    notches: i32,
+    /// Apples eaten since the last slip.
+    streak: u32,
+}
+
+impl Adaptive {
+    pub fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::FoodEaten { .. } => {
+                self.streak += 1;
+                if self.streak >= STREAK {
+                    self.streak = 0;
+                    self.notches = (self.notches + 1).min(MAX_NOTCHES);
+                }
+            }
+            // Close calls break the streak without counting against the
+            // player.
+            GameEvent::Poisoned | GameEvent::ShieldUsed | GameEvent::Rewound => self.streak = 0,
+            GameEvent::LifeLost(_) | GameEvent::Died { .. } => {
+                self.streak = 0;
+                self.notches = (self.notches - 1).max(-MAX_NOTCHES);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn notches(&self) -> i32 {
+        self.notches
+    }
+}
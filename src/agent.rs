@@ -0,0 +1,88 @@
+use std::{
+    fs::File,
+    io::{
+        BufRead,
+        BufReader,
+    },
+    os::fd::OwnedFd,
+    process::{
+        Child,
+        ChildStdout,
+        Command,
+        Stdio,
+    },
+    sync::mpsc::SyncSender,
+};
+
+use crate::{
+    Commands,
+    input::{
+        self,
+        InputSource,
+    },
+    logging::log,
+    stream::StateStream,
+};
+
+/// A bot run as its own program. It's sent the game on stdin as the state
+/// stream's JSON, a line per move of the snake, and answers on stdout with
+/// a command a line, named as in a script: `left`, `right`, `confirm` to
+/// start a run, and so on. `none` or a blank line leaves the snake be.
+pub struct Agent {
+    child: Child,
+}
+
+impl Agent {
+    /// Starts `program` through the shell, returning it along with the
+    /// stream its observations go to and the source its actions come from.
+    pub fn spawn(program: &str) -> Result<(Agent, StateStream, AgentActions), String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // It would draw over the game.
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("{program}: {err}"))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(format!("{program}: couldn't talk to it"));
+        };
+        let observations = StateStream::new(File::from(OwnedFd::from(stdin)));
+        Ok((Agent { child }, observations, AgentActions { stdout }))
+    }
+}
+
+impl Drop for Agent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// What the bot says to do.
+pub struct AgentActions {
+    stdout: ChildStdout,
+}
+
+impl InputSource for AgentActions {
+    fn run(self: Box<Self>, sender: SyncSender<Commands>) {
+        for line in BufReader::new(self.stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() || line == "none" {
+                continue;
+            }
+            let Some(command) = input::parse_command(line) else {
+                log!(Warn, "unknown command from the agent: `{line}`");
+                continue;
+            };
+            log!(Debug, "agent: {command:?}");
+            if sender.send(command).is_err() {
+                break;
+            }
+        }
+    }
+}
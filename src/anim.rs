@@ -0,0 +1,25 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// Counts animation steps from wall-clock time, so decorative animations run
+/// at their own pace whatever the game loop is doing.
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    started: Instant,
+    step: Duration,
+}
+
+impl Ticker {
+    pub fn new(steps_per_second: f64) -> Self {
+        let started = Instant::now();
+        let step = Duration::from_secs_f64(1. / steps_per_second);
+        Self { started, step }
+    }
+
+    /// How many whole steps have passed since the ticker was created.
+    pub fn step(&self) -> u64 {
+        (self.started.elapsed().as_nanos() / self.step.as_nanos().max(1)) as u64
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufWriter,
+        Write,
+    },
+};
+
+use crate::{
+    Vec2,
+    events::GameEvent,
+    game::{
+        DeathCause,
+        Game,
+    },
+    stream,
+};
+
+/// Short spoken-style lines about the run, such as "food up-left 5 cells"
+/// or "wall ahead", written one a line for a screen reader or speech
+/// synthesiser to read out, so the game can be played by ear.
+pub struct Announcer {
+    out: BufWriter<File>,
+    /// Lines waiting for the next update.
+    pending: Vec<String>,
+    last_tick: Option<u64>,
+    /// The food last pointed out.
+    target: Option<Vec2>,
+    /// What was last said to be ahead.
+    danger: Option<DeathCause>,
+}
+
+impl Announcer {
+    /// Opens `target`, a path or an inherited file descriptor as for the
+    /// state stream.
+    pub fn open(target: &str) -> Result<Announcer, String> {
+        Ok(Announcer {
+            out: BufWriter::new(stream::open_target(target)?),
+            pending: Vec::new(),
+            last_tick: None,
+            target: None,
+            danger: None,
+        })
+    }
+
+    /// Queues up anything worth saying about `event`.
+    pub fn record(&mut self, event: &GameEvent, game: &Game) {
+        self.pending.push(event.describe());
+        if let GameEvent::FoodEaten { .. } | GameEvent::Poisoned = event {
+            self.pending.push(format!("length {}", game.player.len()));
+        }
+    }
+
+    /// Says what's been queued, along with where the nearest food is when it
+    /// changes and whatever the snake is about to run into, once a move.
+    pub fn update(&mut self, game: &Game) -> io::Result<()> {
+        if self.last_tick == Some(game.ticks) {
+            return Ok(());
+        }
+        self.last_tick = Some(game.ticks);
+        let danger = game.danger_ahead();
+        if danger != self.danger
+            && let Some(cause) = danger
+        {
+            self.pending.push(match cause {
+                DeathCause::Wall => "wall ahead".to_string(),
+                DeathCause::Tail => "body ahead".to_string(),
+                _ => "obstacle ahead".to_string(),
+            });
+        }
+        self.danger = danger;
+        let nearest = game.nearest_food();
+        let target = nearest.map(|(food, _)| food.at);
+        if target != self.target
+            && let Some((food, distance)) = nearest
+        {
+            let heading = direction(food.at - game.player.head);
+            self.pending
+                .push(format!("{} {heading} {distance} cells", food.kind.name()));
+        }
+        self.target = target;
+        for line in self.pending.drain(..) {
+            writeln!(self.out, "{line}")?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Which way `offset` points on screen, such as `up-left`.
+fn direction(offset: Vec2) -> String {
+    let vertical = match offset.y {
+        y if y < 0. => Some("up"),
+        y if y > 0. => Some("down"),
+        _ => None,
+    };
+    let horizontal = match offset.x {
+        x if x < 0. => Some("left"),
+        x if x > 0. => Some("right"),
+        _ => None,
+    };
+    match (vertical, horizontal) {
+        (Some(vertical), Some(horizontal)) => format!("{vertical}-{horizontal}"),
+        (Some(one), None) | (None, Some(one)) => one.to_string(),
+        (None, None) => "here".to_string(),
+    }
+}
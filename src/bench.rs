@@ -0,0 +1,133 @@
+use std::{
+    io,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crate::{
+    Vec2,
+    food::FoodRules,
+    game::Game,
+    render::Renderer,
+    rng::Rng,
+};
+
+/// How long each case is run for.
+const CASE_TIME: Duration = Duration::from_secs(1);
+
+/// Segments in the long-snake case.
+const LONG_SNAKE: usize = 10_000;
+
+/// Blocks in the many-entities case.
+const ENTITIES: usize = 2_000;
+
+/// Terminal size frames are drawn at.
+const COLS: u16 = 200;
+const ROWS: u16 = 60;
+
+/// Seed every case is played with, so runs compare.
+const SEED: u64 = 0x5EED;
+
+/// One measurement: what was timed and how many times a second it ran.
+pub struct Case {
+    pub name: &'static str,
+    pub rate: f64,
+    pub unit: &'static str,
+}
+
+/// Times the core loop under loads much heavier than play usually puts on
+/// it: ticks of the game with a very long snake, a crowded board and a huge
+/// arena, and frames drawn and written out. Runs are zen, so nothing ends
+/// them part way.
+pub fn run(renderer: &Renderer) -> Vec<Case> {
+    let mut long = zen_game(200, 200);
+    lay_out(&mut long, LONG_SNAKE);
+    let mut crowded = zen_game(200, 200);
+    let mut rng = Rng::new(SEED);
+    let mut placed = 0;
+    while placed < ENTITIES {
+        let cell = Vec2::new(
+            rng.below(crowded.width as u64) as f64,
+            rng.below(crowded.height as u64) as f64,
+        );
+        if cell.y != crowded.player.head.y && crowded.place_obstacle(cell).is_ok() {
+            placed += 1;
+        }
+    }
+    let large = zen_game(2_000, 2_000);
+    let (width, height) = renderer.arena_size(COLS, ROWS);
+    let mut drawn = zen_game(width, height);
+    lay_out(&mut drawn, width as usize);
+    vec![
+        ticks("long snake", long),
+        ticks("many entities", crowded),
+        ticks("large arena", large),
+        frames(renderer, drawn),
+    ]
+}
+
+/// A game of the given size to time, with the countdown skipped.
+fn zen_game(width: u16, height: u16) -> Game {
+    let mut game = Game::new(width, height, SEED, FoodRules::default());
+    game.start_zen();
+    game.countdown = None;
+    game
+}
+
+/// Gives the snake `len` more segments straight away, rather than over as
+/// many moves, winding back and forth across the rows below the head.
+fn lay_out(game: &mut Game, len: usize) {
+    let width = game.width as usize;
+    let top = game.player.head.y as usize + 1;
+    for index in 0..len {
+        let row = index / width;
+        let col = if row.is_multiple_of(2) {
+            width - 1 - index % width
+        } else {
+            index % width
+        };
+        let cell = Vec2::new(col as f64, ((top + row) % game.height as usize) as f64);
+        game.player.body.push_back(cell);
+    }
+}
+
+fn ticks(name: &'static str, mut game: Game) -> Case {
+    let rate = repeat(|| {
+        game.step();
+        game.drain_events();
+    });
+    Case {
+        name,
+        rate,
+        unit: "ticks/s",
+    }
+}
+
+fn frames(renderer: &Renderer, mut game: Game) -> Case {
+    let mut sink = io::sink();
+    let rate = repeat(|| {
+        game.step();
+        game.drain_events();
+        let frame = game.draw(renderer, COLS, ROWS);
+        // Writing to a sink can't fail.
+        let _ = renderer.present(&frame, &mut sink);
+    });
+    Case {
+        name: "draw and present",
+        rate,
+        unit: "frames/s",
+    }
+}
+
+/// Calls `each` for `CASE_TIME`, returning how many times a second it ran.
+fn repeat(mut each: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    let mut count = 0;
+    while start.elapsed() < CASE_TIME {
+        each();
+        count += 1;
+    }
+    count as f64 / start.elapsed().as_secs_f64()
+}
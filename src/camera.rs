@@ -0,0 +1,71 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::Vec2;
+
+/// How the camera keeps up with the snake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CameraMode {
+    /// Only moves once the head leaves the middle of the view.
+    DeadZone,
+    /// Keeps the head in the middle of the view.
+    Centered,
+}
+
+impl CameraMode {
+    pub fn from_name(name: &str) -> Option<CameraMode> {
+        match name {
+            "deadzone" => Some(CameraMode::DeadZone),
+            "centered" => Some(CameraMode::Centered),
+            _ => None,
+        }
+    }
+}
+
+/// The window of the arena that's on screen, for arenas too big to show
+/// whole. Positions are in arena cells.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Camera {
+    pub mode: CameraMode,
+    /// The arena cell in the top-left corner of the view.
+    pub origin: Vec2,
+}
+
+impl Camera {
+    pub fn new(mode: CameraMode) -> Self {
+        Self {
+            mode,
+            origin: Vec2::new(0., 0.),
+        }
+    }
+
+    /// Moves the view of `view` cells so it shows `target`, keeping it within
+    /// an arena of `world` cells.
+    pub fn follow(&mut self, target: Vec2, view: (u16, u16), world: (u16, u16)) {
+        if view.0 == 0 || view.1 == 0 {
+            return;
+        }
+        let (view_width, view_height) = (view.0 as f64, view.1 as f64);
+        match self.mode {
+            CameraMode::Centered => {
+                self.origin = Vec2::new(
+                    target.x - (view_width / 2.).floor(),
+                    target.y - (view_height / 2.).floor(),
+                );
+            }
+            CameraMode::DeadZone => {
+                let margin = Vec2::new((view_width / 4.).floor(), (view_height / 4.).floor());
+                let low = target - margin;
+                let high = target + margin - Vec2::new(view_width - 1., view_height - 1.);
+                self.origin = self.origin.clamp(high, low);
+            }
+        }
+        let max = Vec2::new(
+            (world.0 as f64 - view_width).max(0.),
+            (world.1 as f64 - view_height).max(0.),
+        );
+        self.origin = self.origin.clamp(Vec2::new(0., 0.), max);
+    }
+}
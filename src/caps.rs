@@ -0,0 +1,113 @@
+use std::env;
+
+/// How many colours the terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// No colour escapes at all.
+    Monochrome,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// A terminal multiplexer between the game and the terminal showing it,
+/// which has its own ideas about which escape sequences to pass on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    /// GNU screen.
+    Screen,
+}
+
+impl Multiplexer {
+    /// Works out which multiplexer we're in, if any. tmux says `screen` in
+    /// `$TERM` by default, so its own variable is checked first.
+    fn detect(term: &str, tmux: bool, screen: bool) -> Option<Multiplexer> {
+        if tmux || term.starts_with("tmux") {
+            Some(Multiplexer::Tmux)
+        } else if screen || term.starts_with("screen") {
+            Some(Multiplexer::Screen)
+        } else {
+            None
+        }
+    }
+}
+
+/// What the terminal we're running in can be trusted to do, worked out from
+/// the environment so the same binary adapts to xterm, the Linux console and
+/// dumb terminals without any flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    pub color: ColorDepth,
+    /// Box-drawing, block and arrow characters show up properly.
+    pub unicode: bool,
+    /// Braille patterns show up properly. The Linux console font has the
+    /// box-drawing characters but not these.
+    pub braille: bool,
+    /// The terminal has an alternate screen to draw the game on.
+    pub alt_screen: bool,
+    /// The multiplexer we're running inside, if any.
+    pub multiplexer: Option<Multiplexer>,
+}
+
+impl Capabilities {
+    pub fn probe() -> Capabilities {
+        let term = env::var("TERM").unwrap_or_default();
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let multiplexer = Multiplexer::detect(
+            &term,
+            env::var_os("TMUX").is_some(),
+            env::var_os("STY").is_some(),
+        );
+        Capabilities::from_env(&term, &colorterm, locale_is_utf8(), multiplexer)
+    }
+
+    fn from_env(
+        term: &str,
+        colorterm: &str,
+        utf8: bool,
+        multiplexer: Option<Multiplexer>,
+    ) -> Capabilities {
+        let dumb = term.is_empty() || term == "dumb";
+        let vt = matches!(term, "vt52" | "vt100" | "vt102" | "vt220" | "ansi");
+        let console = term == "linux";
+        let truecolor = colorterm == "truecolor" || colorterm == "24bit";
+        // `$COLORTERM` comes from outside the multiplexer, which only
+        // passes true colour on if it's been set up to: trust it in tmux
+        // with its own terminfo, and never in screen.
+        let truecolor = truecolor
+            && match multiplexer {
+                None => true,
+                Some(Multiplexer::Tmux) => term.starts_with("tmux"),
+                Some(Multiplexer::Screen) => false,
+            };
+        let color = if dumb || vt {
+            ColorDepth::Monochrome
+        } else if truecolor {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") || term.contains("direct") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        };
+        let unicode = utf8 && !dumb && !vt;
+        Capabilities {
+            color,
+            unicode,
+            braille: unicode && !console,
+            alt_screen: !dumb && !vt && !console,
+            multiplexer,
+        }
+    }
+}
+
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|value| {
+            let value = value.to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+}
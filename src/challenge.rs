@@ -0,0 +1,394 @@
+use crate::{
+    difficulty::Difficulty,
+    food::FoodRules,
+    movement::MovementRules,
+    render::{
+        Frame,
+        Renderer,
+    },
+    rules::Ruleset,
+};
+
+/// What every code starts with.
+const PREFIX: &str = "SNK";
+/// Bumped whenever the layout of a code changes, so old codes are turned
+/// away rather than misread.
+const VERSION: u8 = 2;
+/// Crockford's base32, which leaves out I, L, O and U so codes can't be
+/// misread.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Characters between dashes.
+const GROUP: usize = 4;
+
+/// Bits of the mode byte, above the two holding the difficulty.
+const TRAIL: u8 = 1 << 2;
+const PASS_THROUGH: u8 = 1 << 3;
+const TIME_ATTACK: u8 = 1 << 4;
+const MISSION: u8 = 1 << 5;
+const WRAPS: u8 = 1 << 6;
+const NO_POISON: u8 = 1 << 7;
+
+/// Bits of the rules byte, each saying a rule is off its default and its
+/// value follows.
+const LIVES: u8 = 1;
+const PORTALS: u8 = 1 << 1;
+const FOOD_COUNT: u8 = 1 << 2;
+const HUNGER: u8 = 1 << 3;
+const CLOSING: u8 = 1 << 4;
+const FOG: u8 = 1 << 5;
+const FOOD_WEIGHTS: u8 = 1 << 6;
+const MOVEMENT: u8 = 1 << 7;
+
+/// Everything needed to play a run again exactly: its seed, arena and
+/// rules.
+///
+/// A challenge is shared as a code like `SNK-7F3K-...`: the settings packed
+/// into bytes, each rule only there if it's off its default, with a
+/// checksum on the end, written out in base32. Seconds are kept to a tenth.
+#[derive(Clone, Copy, Debug)]
+pub struct Challenge {
+    pub seed: u64,
+    pub width: u16,
+    pub height: u16,
+    pub difficulty: Difficulty,
+    pub movement: MovementRules,
+    pub rules: Ruleset,
+    pub mission: bool,
+    pub lives: u32,
+    pub portals: usize,
+    pub food: FoodRules,
+    pub fog: Option<f64>,
+}
+
+impl Challenge {
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![VERSION];
+        bytes.extend(self.seed.to_le_bytes());
+        push_varint(&mut bytes, self.width as u64);
+        push_varint(&mut bytes, self.height as u64);
+        let difficulty = Difficulty::ALL
+            .iter()
+            .position(|&difficulty| difficulty == self.difficulty)
+            .unwrap_or(0) as u8;
+        let mut mode = difficulty;
+        for (on, bit) in [
+            (self.rules.trail, TRAIL),
+            (self.rules.pass_through, PASS_THROUGH),
+            (self.rules.timer, TIME_ATTACK),
+            (self.mission, MISSION),
+            (self.rules.wraps, WRAPS),
+            (!self.rules.poison, NO_POISON),
+        ] {
+            if on {
+                mode |= bit;
+            }
+        }
+        bytes.push(mode);
+
+        let defaults = FoodRules::default();
+        let weights = food_weights(&self.food);
+        let mut rules = 0;
+        let mut values = Vec::new();
+        let mut rule = |bit: u8, value: Option<u64>| {
+            if let Some(value) = value {
+                rules |= bit;
+                push_varint(&mut values, value);
+            }
+        };
+        rule(LIVES, Some(self.lives as u64).filter(|&lives| lives != 1));
+        rule(
+            PORTALS,
+            Some(self.portals as u64).filter(|&pairs| pairs != 0),
+        );
+        let count = self.food.count as u64;
+        rule(FOOD_COUNT, Some(count).filter(|&count| count != 1));
+        rule(HUNGER, self.rules.hunger.map(tenths));
+        rule(CLOSING, self.rules.closing.map(tenths));
+        rule(FOG, self.fog.map(tenths));
+        rule(
+            MOVEMENT,
+            match self.movement {
+                MovementRules::Classic => None,
+                MovementRules::Ice => Some(0),
+                MovementRules::Analog(degrees) => Some(degrees as u64 + 1),
+            },
+        );
+        if weights != food_weights(&defaults) {
+            rules |= FOOD_WEIGHTS;
+            for weight in weights {
+                push_varint(&mut values, weight as u64);
+            }
+        }
+        bytes.push(rules);
+        bytes.extend(values);
+        bytes.push(checksum(&bytes));
+
+        let text = to_base32(&bytes);
+        let groups: Vec<&str> = text
+            .as_bytes()
+            .chunks(GROUP)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+            .collect();
+        format!("{PREFIX}-{}", groups.join("-"))
+    }
+
+    /// Reads a code back, forgiving lowercase, missing dashes and the
+    /// letters Crockford's alphabet reads as digits.
+    pub fn decode(code: &str) -> Result<Challenge, String> {
+        let code = code.trim().to_ascii_uppercase();
+        let body = code
+            .strip_prefix(PREFIX)
+            .and_then(|body| body.strip_prefix('-'))
+            .ok_or_else(|| format!("a challenge code starts with {PREFIX}-"))?;
+        let bytes = from_base32(body)?;
+        let Some((&sum, bytes)) = bytes.split_last() else {
+            return Err("that code is too short".to_string());
+        };
+        if checksum(bytes) != sum {
+            return Err("that code has a typo in it".to_string());
+        }
+        let mut reader = Reader { bytes, at: 0 };
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(format!("that code is from another version ({version})"));
+        }
+        let mut seed = [0; 8];
+        for byte in &mut seed {
+            *byte = reader.byte()?;
+        }
+        let width = reader.small()?;
+        let height = reader.small()?;
+        let mode = reader.byte()?;
+        let rules = reader.byte()?;
+        let mut rule = |bit: u8| {
+            if rules & bit == 0 {
+                Ok(None)
+            } else {
+                reader.varint().map(Some)
+            }
+        };
+        let lives = rule(LIVES)?.map_or(1, |lives| lives as u32);
+        let portals = rule(PORTALS)?.unwrap_or(0) as usize;
+        let count = rule(FOOD_COUNT)?.unwrap_or(1) as usize;
+        let hunger = rule(HUNGER)?.map(seconds);
+        let closing = rule(CLOSING)?.map(seconds);
+        let fog = rule(FOG)?.map(seconds);
+        let movement = match rule(MOVEMENT)? {
+            None => MovementRules::Classic,
+            Some(0) => MovementRules::Ice,
+            Some(degrees) => MovementRules::Analog(
+                u16::try_from(degrees - 1).map_err(|_| "that code's steering is off")?,
+            ),
+        };
+        let mut food = FoodRules {
+            count,
+            ..FoodRules::default()
+        };
+        if rules & FOOD_WEIGHTS != 0 {
+            let mut weights = [0; 7];
+            for weight in &mut weights {
+                *weight = u32::try_from(reader.varint()?).map_err(|_| "that code's food is off")?;
+            }
+            set_food_weights(&mut food, weights);
+        }
+        if reader.at != bytes.len() {
+            return Err("that code is too long".to_string());
+        }
+        Ok(Challenge {
+            seed: u64::from_le_bytes(seed),
+            width,
+            height,
+            difficulty: Difficulty::ALL[(mode & 0b11) as usize],
+            movement,
+            rules: Ruleset {
+                wraps: mode & WRAPS != 0,
+                hunger,
+                poison: mode & NO_POISON == 0,
+                closing,
+                trail: mode & TRAIL != 0,
+                timer: mode & TIME_ATTACK != 0,
+                pass_through: mode & PASS_THROUGH != 0,
+            },
+            mission: mode & MISSION != 0,
+            lives,
+            portals,
+            food,
+            fog,
+        })
+    }
+}
+
+fn food_weights(food: &FoodRules) -> [u32; 7] {
+    [
+        food.normal,
+        food.bonus,
+        food.mega,
+        food.mouse,
+        food.poison,
+        food.power,
+        food.shield,
+    ]
+}
+
+fn set_food_weights(food: &mut FoodRules, weights: [u32; 7]) {
+    [
+        food.normal,
+        food.bonus,
+        food.mega,
+        food.mouse,
+        food.poison,
+        food.power,
+        food.shield,
+    ] = weights;
+}
+
+fn tenths(seconds: f64) -> u64 {
+    (seconds * 10.).round() as u64
+}
+
+fn seconds(tenths: u64) -> f64 {
+    tenths as f64 / 10.
+}
+
+/// Appends `value` seven bits at a time, low bits first, with the top bit of
+/// each byte set while more follow.
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0x5a, |sum: u8, &byte| sum.rotate_left(3) ^ byte)
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    let mut bits = 0_u32;
+    let mut held = 0;
+    for &byte in bytes {
+        bits = bits << 8 | byte as u32;
+        held += 8;
+        while held >= 5 {
+            held -= 5;
+            text.push(ALPHABET[(bits >> held & 0x1f) as usize] as char);
+        }
+    }
+    if held > 0 {
+        text.push(ALPHABET[(bits << (5 - held) & 0x1f) as usize] as char);
+    }
+    text
+}
+
+fn from_base32(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut bits = 0_u32;
+    let mut held = 0;
+    for ch in text.chars().filter(|&ch| ch != '-' && !ch.is_whitespace()) {
+        let ch = match ch {
+            'O' => '0',
+            'I' | 'L' => '1',
+            ch => ch,
+        };
+        let value = ALPHABET
+            .iter()
+            .position(|&letter| letter as char == ch)
+            .ok_or_else(|| format!("`{ch}` can't be in a challenge code"))?;
+        bits = bits << 5 | value as u32;
+        held += 5;
+        if held >= 8 {
+            held -= 8;
+            bytes.push((bits >> held) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Reads the bytes of a code in order.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.at).ok_or("that code is cut short")?;
+        self.at += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("that code has a number too big in it".to_string())
+    }
+
+    fn small(&mut self) -> Result<u16, String> {
+        u16::try_from(self.varint()?).map_err(|_| "that code's arena is too big".to_string())
+    }
+}
+
+const PROMPT: &str = "enter play  backspace erase  esc back";
+
+/// Where a challenge code is typed in, from the title screen.
+#[derive(Clone, Debug, Default)]
+pub struct ChallengeScreen {
+    text: String,
+    /// Why the last code tried didn't work.
+    error: Option<String>,
+}
+
+impl ChallengeScreen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, ch: char) {
+        if ch.is_ascii_alphanumeric() || ch == '-' {
+            self.text.push(ch.to_ascii_uppercase());
+            self.error = None;
+        }
+    }
+
+    pub fn erase(&mut self) {
+        self.text.pop();
+        self.error = None;
+    }
+
+    /// The challenge typed in, if the code is good. The `SNK-` can be left
+    /// off.
+    pub fn submit(&mut self) -> Option<Challenge> {
+        let typed = Challenge::decode(&self.text)
+            .or_else(|_| Challenge::decode(&format!("{PREFIX}-{}", self.text)));
+        match typed {
+            Ok(challenge) => Some(challenge),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let row = height.saturating_sub(7) / 2;
+        frame.print_centered(row, "ENTER A CHALLENGE CODE", colors.head);
+        frame.print_centered(row + 2, &format!("{}_", self.text), colors.bonus);
+        if let Some(error) = &self.error {
+            frame.print_centered(row + 4, error, colors.food);
+        }
+        frame.print_centered(row + 6, PROMPT, colors.wall);
+        frame
+    }
+}
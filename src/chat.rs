@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use crate::{
+    json,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Messages shown over the game at once.
+const SHOWN: usize = 4;
+
+/// Seconds a message stays up with the chat line closed.
+const LINGER: f64 = 8.;
+
+/// Messages kept for control clients to ask for.
+const KEPT: usize = 50;
+
+/// Characters a message can run to.
+const MAX_LEN: usize = 120;
+
+/// Who a message was sent by and what it said.
+#[derive(Clone, Debug)]
+struct Message {
+    from: String,
+    text: String,
+    /// Seconds since it was sent.
+    age: f64,
+}
+
+/// Messages between the players and anyone connected through the control
+/// socket, shown over the bottom of the game as it carries on, with a line
+/// to type into opened with `t`.
+#[derive(Clone, Debug, Default)]
+pub struct Chat {
+    pub open: bool,
+    text: String,
+    /// Every message kept, oldest first.
+    messages: VecDeque<Message>,
+}
+
+impl Chat {
+    pub fn push(&mut self, ch: char) {
+        if self.text.chars().count() < MAX_LEN {
+            self.text.push(ch);
+        }
+    }
+
+    pub fn erase(&mut self) {
+        self.text.pop();
+    }
+
+    /// Takes the line typed, closing the chat line, if there's anything
+    /// more than spaces in it.
+    pub fn submit(&mut self) -> Option<String> {
+        self.open = false;
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Adds a message `from` someone.
+    pub fn post(&mut self, from: &str, text: &str) {
+        if self.messages.len() >= KEPT {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            from: from.to_string(),
+            text: text.chars().take(MAX_LEN).collect(),
+            age: 0.,
+        });
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for message in &mut self.messages {
+            message.age += dt;
+        }
+    }
+
+    /// The messages kept, oldest first, as a line of JSON.
+    pub fn to_json(&self) -> String {
+        let messages: Vec<String> = self
+            .messages
+            .iter()
+            .map(|message| {
+                format!(
+                    r#"{{"from": {}, "text": {}}}"#,
+                    json::string(&message.from),
+                    json::string(&message.text)
+                )
+            })
+            .collect();
+        format!(r#"{{"chat": [{}]}}"#, messages.join(", "))
+    }
+
+    /// Draws the latest messages up from the bottom of `frame`, and the
+    /// line being typed under them while it's open. With it closed, only
+    /// messages from the last few seconds are shown.
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame) {
+        let colors = &renderer.theme.colors;
+        let recent = self
+            .messages
+            .iter()
+            .rev()
+            .take(SHOWN)
+            .take_while(|message| self.open || message.age < LINGER);
+        let mut row = frame.height();
+        if self.open {
+            row = row.saturating_sub(1);
+            frame.print_fg(0, row, &format!("say: {}_", self.text), colors.bonus);
+        }
+        for message in recent {
+            row = row.saturating_sub(1);
+            frame.print_fg(0, row, &format!("{}: ", message.from), colors.head);
+            let col = message.from.chars().count() as u16 + 2;
+            frame.print(col, row, &message.text);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs,
+    io,
+    path::PathBuf,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+    de::DeserializeOwned,
+};
+
+use crate::{
+    food::FoodRules,
+    players::Player,
+    theme::ThemeSpec,
+};
+
+/// Settings read from `$XDG_CONFIG_HOME/snake/config.toml`. Command line
+/// arguments take precedence over anything set here.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub render: Option<String>,
+    /// Forces ASCII glyphs on or off; detected from the terminal when unset.
+    pub ascii: Option<bool>,
+    pub theme: Option<String>,
+    /// Count down again after unpausing. On unless set to false.
+    pub resume_countdown: Option<bool>,
+    /// Replay the last moments in slow motion after dying. On unless set to
+    /// false.
+    pub death_replay: Option<bool>,
+    /// Shake the arena when the snake crashes. On unless set to false, for
+    /// anyone who finds it distracting.
+    pub screen_shake: Option<bool>,
+    /// Show the score and what's going on in the terminal's window title.
+    /// On unless set to false.
+    pub window_title: Option<bool>,
+    /// Send a desktop notification through the terminal on a new personal
+    /// best. On unless set to false.
+    pub notifications: Option<bool>,
+    /// Turn off screen shake, blinking, flashes, effects and gradients.
+    pub reduced_motion: Option<bool>,
+    /// Slow time down for a moment when the snake passes close by a wall or
+    /// itself at speed. Off unless set.
+    pub slow_motion: Option<bool>,
+    /// Slide the snake smoothly between cells.
+    pub smooth: Option<bool>,
+    /// Arena size in cells. Unset sides fit the terminal.
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// `deadzone` or `centered`.
+    pub camera: Option<String>,
+    /// `easy`, `normal`, `hard` or `insane`. Normal unless set.
+    pub difficulty: Option<String>,
+    /// Cells of the arena the snake moves a second on normal difficulty,
+    /// whatever the size of the terminal. Ten unless set.
+    pub speed: Option<f64>,
+    /// Milliseconds after the snake moves into a cell that a turn still
+    /// counts as made in the cell before. 60 unless set; 0 turns it off.
+    pub corner_grace: Option<u32>,
+    /// Draw block-mode cells two columns wide so the arena isn't stretched
+    /// upwards. On unless set to false.
+    pub square: Option<bool>,
+    /// How many times over to blow the arena up, for seeing it from a
+    /// distance. One unless set.
+    pub scale: Option<u16>,
+    /// Lives per run. One unless set.
+    pub lives: Option<u32>,
+    /// Segments the snake starts with, counting its head. One unless set.
+    pub length: Option<usize>,
+    /// The cell the snake's head starts on, as `[x, y]`. Unset, it starts
+    /// near a corner, or where a level puts it.
+    pub spawn: Option<[u16; 2]>,
+    /// `up`, `down`, `left` or `right`: the way the snake heads off. Right
+    /// unless set or a level says otherwise.
+    pub direction: Option<String>,
+    /// Seconds the snake can go without eating before it loses a segment.
+    /// No hunger unless set.
+    pub hunger: Option<f64>,
+    /// Pairs of portals in the arena. None unless set.
+    pub portals: Option<usize>,
+    /// Seconds between the walls closing in a cell. They stay put unless set.
+    pub closing: Option<f64>,
+    /// Cells around the head that can be seen through fog at the start of a
+    /// run. No fog unless set.
+    pub fog: Option<f64>,
+    /// Light-cycle rules: the snake leaves a permanent trail and there's no
+    /// food.
+    pub trail: Option<bool>,
+    /// Walls that wrap round rather than ending the run.
+    pub wraps: Option<bool>,
+    /// Whether poison turns up at all. On unless set to false.
+    pub poison: Option<bool>,
+    /// Give each run a mission to complete for bonus points.
+    pub mission: Option<bool>,
+    /// Score as much as possible against a two minute clock.
+    pub time_attack: Option<bool>,
+    /// Endless play with nothing that can end the run.
+    pub zen: Option<bool>,
+    /// Two players on one keyboard, sharing a score and lives.
+    pub coop: Option<bool>,
+    /// Two players on one keyboard against each other, in a match to this
+    /// many rounds.
+    pub versus: Option<u32>,
+    /// Names and colours for the first player and the second, in co-op and
+    /// versus, as `{ name = "...", color = "..." }`.
+    pub players: Option<Vec<Player>>,
+    /// `classic`, `ice` or `analog`.
+    pub movement: Option<String>,
+    /// Degrees each press turns by under analog steering.
+    pub steer: Option<u16>,
+    /// Sound effect volume from 0 to 1. Full unless set.
+    pub volume: Option<f64>,
+    /// Turns the sound effects and music off.
+    pub mute: Option<bool>,
+    /// Play sounds for food getting closer and crashes coming up.
+    pub audio_cues: Option<bool>,
+    /// Nudge the difficulty up after streaks of clean play and back down
+    /// after losing a life.
+    pub adaptive: Option<bool>,
+    /// Start with the tutorial every time if true, or never if false. Unset,
+    /// it starts the first time the game is played until it's been done.
+    pub tutorial: Option<bool>,
+    /// Practice mode: runs can be paused to place food and blocks and set
+    /// the snake's length. They don't count towards anything.
+    pub practice: Option<bool>,
+    /// Time runs to the millisecond, with splits at 10, 25 and 50 apples
+    /// compared against the best ones so far.
+    pub speedrun: Option<bool>,
+    /// Where the background music is, as `menu`, `game` and `gameover`
+    /// files, with the `music` feature. No music unless set.
+    pub music: Option<String>,
+    /// `error`, `warn`, `info`, `debug` or `trace`. Nothing is logged unless
+    /// set.
+    pub log_level: Option<String>,
+    /// How much food is out at once and how often each kind turns up.
+    pub food: FoodRules,
+    pub themes: HashMap<String, ThemeSpec>,
+}
+
+/// Where anything kept between runs, such as high scores, is saved.
+pub fn data_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(dir.join("snake"))
+}
+
+/// Loads `name` from the data directory, treating a missing file as the
+/// default.
+pub fn load_data<T: DeserializeOwned + Default>(name: &str) -> Result<T, String> {
+    let Some(path) = data_dir().map(|dir| dir.join(name)) else {
+        return Ok(T::default());
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {err}", path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(format!("{}: {err}", path.display())),
+    }
+}
+
+/// Saves `data` as `name` in the data directory, creating it if need be.
+pub fn save_data<T: Serialize>(name: &str, data: &T) -> Result<(), String> {
+    let dir = data_dir().ok_or("no data directory to save to")?;
+    let text = toml::to_string(data).map_err(|err| err.to_string())?;
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let path = dir.join(name);
+    fs::write(&path, text).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        let dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(dir.join("snake").join("config.toml"))
+    }
+
+    /// Loads the config file, treating a missing file as an empty config.
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = Config::path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {err}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(format!("{}: {err}", path.display())),
+        }
+    }
+
+    /// Sets `key` in the config file, keeping everything else set in it, so
+    /// a change made from the settings screen sticks.
+    pub fn set(key: &str, value: impl Into<toml::Value>) -> Result<(), String> {
+        let path = Config::path().ok_or("no config file to keep settings in")?;
+        let mut table = match fs::read_to_string(&path) {
+            Ok(text) => text
+                .parse::<toml::Table>()
+                .map_err(|err| format!("{}: {err}", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+            Err(err) => return Err(format!("{}: {err}", path.display())),
+        };
+        table.insert(key.to_string(), value.into());
+        let text = toml::to_string(&table).map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+        }
+        fs::write(&path, text).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Vec2,
+    game::Game,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Every command the console knows, for completing them.
+const COMMANDS: [&str; 9] = [
+    "spawn",
+    "grow",
+    "extend",
+    "shrink",
+    "tp",
+    "speed",
+    "timescale",
+    "god",
+    "noclip",
+];
+
+/// Lines of earlier commands and their answers kept on screen.
+const SCROLLBACK: usize = 4;
+
+/// A change to the run made from the console, for testing.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Cheat {
+    /// Puts an apple on a cell.
+    SpawnFood(Vec2),
+    /// Grows the snake by a number of segments, added at the tail over its
+    /// next moves.
+    Grow(u32),
+    /// Takes a number of segments off the tail, never the head.
+    Shrink(u32),
+    /// Moves the snake so its head is on a cell.
+    Teleport(Vec2),
+    /// Sets the speed to a multiple of the usual.
+    Speed(f64),
+    /// Runs the whole game faster or slower than real time.
+    TimeScale(f64),
+    /// Switches crashes doing no harm on or off.
+    God,
+    /// Switches passing through walls and the snake's own body on or off.
+    NoClip,
+}
+
+impl Cheat {
+    /// Reads a cheat from a line such as `grow 10` or `spawn food 5 5`.
+    /// `extend` does the same as `grow`.
+    pub fn parse(line: &str) -> Result<Cheat, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| format!("invalid number `{word}`"))
+        };
+        // Cells are whole, so anything else is turned away rather than put
+        // off the grid.
+        let coordinate = |word: &str| {
+            word.parse::<u16>()
+                .map(f64::from)
+                .map_err(|_| format!("invalid coordinate `{word}`"))
+        };
+        let cell = |x: &str, y: &str| Ok::<_, String>(Vec2::new(coordinate(x)?, coordinate(y)?));
+        match words.as_slice() {
+            ["spawn", "food", x, y] => Ok(Cheat::SpawnFood(cell(x, y)?)),
+            ["spawn", ..] => Err("usage: spawn food <x> <y>".to_string()),
+            ["grow" | "extend", count] => count
+                .parse()
+                .map(Cheat::Grow)
+                .map_err(|_| format!("invalid length `{count}`")),
+            ["shrink", count] => count
+                .parse()
+                .map(Cheat::Shrink)
+                .map_err(|_| format!("invalid length `{count}`")),
+            ["tp", x, y] => Ok(Cheat::Teleport(cell(x, y)?)),
+            ["speed", factor] => {
+                let factor = number(factor)?;
+                if factor <= 0. {
+                    return Err(format!("invalid speed `{factor}`"));
+                }
+                Ok(Cheat::Speed(factor))
+            }
+            ["timescale", scale] => {
+                let scale = number(scale)?;
+                if scale <= 0. {
+                    return Err(format!("invalid time scale `{scale}`"));
+                }
+                Ok(Cheat::TimeScale(scale))
+            }
+            ["god"] => Ok(Cheat::God),
+            ["noclip"] => Ok(Cheat::NoClip),
+            [] => Err("empty command".to_string()),
+            [command, ..] if COMMANDS.contains(command) => {
+                Err(format!("wrong arguments for `{command}`"))
+            }
+            [command, ..] => Err(format!("unknown command `{command}`")),
+        }
+    }
+
+    /// Makes the change to `game`, marking it as cheated.
+    pub fn apply(self, game: &mut Game) -> Result<(), String> {
+        match self {
+            Cheat::SpawnFood(cell) => game.place_food(cell)?,
+            Cheat::Grow(count) => game.player.lengthen(count),
+            Cheat::Shrink(count) => {
+                for _ in 0..count {
+                    game.player.shrink();
+                }
+            }
+            Cheat::Teleport(cell) => game.teleport(cell)?,
+            Cheat::Speed(factor) => game.set_speed(factor),
+            Cheat::TimeScale(scale) => game.set_time_scale(scale),
+            Cheat::God => game.god = !game.god,
+            Cheat::NoClip => game.noclip = !game.noclip,
+        }
+        game.cheated = true;
+        Ok(())
+    }
+}
+
+/// A line to type cheats into while a run is being played, opened with `~`
+/// with `--dev` or in a debug build.
+#[derive(Clone, Debug, Default)]
+pub struct Console {
+    /// Whether it's open, and if so whether opening it paused the run.
+    pub open: Option<bool>,
+    text: String,
+    /// Earlier commands and what came of them, oldest first.
+    scrollback: Vec<String>,
+}
+
+impl Console {
+    pub fn push(&mut self, ch: char) {
+        self.text.push(ch);
+    }
+
+    pub fn erase(&mut self) {
+        self.text.pop();
+    }
+
+    /// Completes the command name being typed as far as it's the same for
+    /// every command it could be, listing them when there's more than one.
+    pub fn complete(&mut self) {
+        if self.text.contains(' ') {
+            return;
+        }
+        let matching: Vec<&str> = COMMANDS
+            .into_iter()
+            .filter(|command| command.starts_with(&self.text))
+            .collect();
+        match matching.as_slice() {
+            [] => {}
+            [command] => self.text = format!("{command} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, other| {
+                    first
+                        .bytes()
+                        .zip(other.bytes())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.text = first[..common].to_string();
+                self.print(matching.join("  "));
+            }
+        }
+    }
+
+    /// Takes the line typed, leaving the console empty for the next.
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.text);
+        self.print(format!("> {line}"));
+        line
+    }
+
+    /// Adds a line to the scrollback.
+    pub fn print(&mut self, line: String) {
+        self.scrollback.push(line);
+        if self.scrollback.len() > SCROLLBACK {
+            self.scrollback.remove(0);
+        }
+    }
+
+    /// Draws it across the bottom of `frame`.
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame) {
+        let colors = &renderer.theme.colors;
+        let rows = self.scrollback.len() as u16 + 1;
+        let top = frame.height().saturating_sub(rows);
+        let blank = " ".repeat(frame.width() as usize);
+        for row in top..frame.height() {
+            frame.print_fg(0, row, &blank, colors.wall);
+        }
+        for (index, line) in self.scrollback.iter().enumerate() {
+            frame.print_fg(0, top + index as u16, line, colors.wall);
+        }
+        frame.print_fg(
+            0,
+            frame.height().saturating_sub(1),
+            &format!("~ {}_", self.text),
+            colors.bonus,
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use std::{
+    fs,
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        Write,
+    },
+    os::unix::{
+        fs::{
+            DirBuilderExt,
+            PermissionsExt,
+        },
+        net::{
+            UnixListener,
+            UnixStream,
+        },
+    },
+    path::PathBuf,
+    sync::mpsc::{
+        self,
+        Receiver,
+        Sender,
+    },
+    thread,
+};
+
+use crate::{
+    Vec2,
+    delta::Encoder,
+    logging::log,
+    rng::Rng,
+};
+
+/// The version of the protocol this build speaks. A client says which it
+/// speaks with `hello`, and is turned away if it's another.
+pub const PROTOCOL: u32 = 1;
+
+/// Seconds a player's seat is kept for them after their client's connection
+/// drops, for it to come back.
+pub const GRACE: f64 = 30.;
+
+/// Tells one connected client from another, for as long as the game runs.
+pub type Client = u64;
+
+/// Optional parts of the protocol, as a bitfield, that a client and the game
+/// agree on with `hello`, each sticking to those both understand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Features(u32);
+
+impl Features {
+    pub const NONE: Features = Features(0);
+    /// Games for two: the lobby and players' seats.
+    pub const MODES: Features = Features(1);
+    /// The power-ups running, in state replies.
+    pub const POWER_UPS: Features = Features(1 << 1);
+    pub const CHAT: Features = Features(1 << 2);
+    /// State replies as changes since the last acknowledged, with `ack`.
+    pub const COMPRESSION: Features = Features(1 << 3);
+    /// Every feature this build has.
+    pub const ALL: Features = Features(0b1111);
+
+    pub fn from_bits(bits: u32) -> Features {
+        Features(bits)
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn has(self, features: Features) -> bool {
+        self.0 & features.0 == features.0
+    }
+
+    /// The features in both `self` and `other`.
+    pub fn common(self, other: Features) -> Features {
+        Features(self.0 & other.0)
+    }
+
+    pub fn without(self, features: Features) -> Features {
+        Features(self.0 & !features.0)
+    }
+}
+
+/// Something asked of the running game through the control socket.
+#[derive(Clone, Debug)]
+pub enum Request {
+    /// Pauses or unpauses the run.
+    Pause,
+    /// Sets the speed to a multiple of the usual.
+    Speed(f64),
+    /// Puts an apple on a cell.
+    SpawnFood(Vec2),
+    /// Asks for the game as a line of JSON.
+    State,
+    /// Sends a message to the chat.
+    Say(String),
+    /// Asks for the chat as a line of JSON.
+    Chat,
+    /// Asks for the lobby as a line of JSON.
+    Lobby,
+    /// Switches whether a player in the lobby, counting from 0, is ready.
+    Ready(usize),
+    /// Takes a player's seat, counting from 0, or takes it back after the
+    /// connection dropped with the token it was given on joining.
+    Join(usize, Option<u64>),
+}
+
+/// A line from a client: a request for the game, or one about the
+/// connection itself, which the connection answers without the game.
+#[derive(Clone, Debug)]
+enum Message {
+    Request(Request),
+    /// Opens a connection: the protocol version the client speaks, and the
+    /// features it understands.
+    Hello(u32, Features),
+    /// Acknowledges having a numbered state reply, for the next to be sent
+    /// as changes since it.
+    Ack(u64),
+}
+
+impl Message {
+    /// Reads a message from a line such as `speed 2.0` or `spawn-food 10 5`.
+    fn parse(line: &str) -> Result<Message, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| format!("invalid number `{word}`"))
+        };
+        let request = match words.as_slice() {
+            ["pause"] => Request::Pause,
+            ["state"] => Request::State,
+            ["speed", factor] => {
+                let factor = number(factor)?;
+                if factor <= 0. {
+                    return Err(format!("invalid speed `{factor}`"));
+                }
+                Request::Speed(factor)
+            }
+            ["spawn-food", x, y] => Request::SpawnFood(Vec2::new(number(x)?, number(y)?)),
+            ["chat"] => Request::Chat,
+            ["lobby"] => Request::Lobby,
+            ["ready", player] => Request::Ready(seat(player)?),
+            ["join", player] => Request::Join(seat(player)?, None),
+            ["join", player, token] => Request::Join(
+                seat(player)?,
+                Some(
+                    u64::from_str_radix(token, 16)
+                        .map_err(|_| format!("invalid token `{token}`"))?,
+                ),
+            ),
+            ["say"] => return Err("nothing to say".to_string()),
+            ["say", text @ ..] => Request::Say(text.join(" ")),
+            ["ack", seq] => {
+                return seq
+                    .parse()
+                    .map(Message::Ack)
+                    .map_err(|_| format!("invalid snapshot `{seq}`"));
+            }
+            ["hello", version, features] => {
+                let bits = |word: &str| {
+                    word.parse::<u32>()
+                        .map_err(|_| format!("invalid number `{word}`"))
+                };
+                return Ok(Message::Hello(
+                    bits(version)?,
+                    Features::from_bits(bits(features)?),
+                ));
+            }
+            [] => return Err("empty request".to_string()),
+            [command, ..] => return Err(format!("unknown request `{command}`")),
+        };
+        Ok(Message::Request(request))
+    }
+}
+
+impl Request {
+    /// The features a client has to have agreed on to ask this.
+    pub fn needs(&self) -> Features {
+        match self {
+            Request::Lobby | Request::Ready(_) | Request::Join(..) => Features::MODES,
+            Request::Say(_) | Request::Chat => Features::CHAT,
+            Request::Pause | Request::Speed(_) | Request::SpawnFood(_) | Request::State => {
+                Features::NONE
+            }
+        }
+    }
+}
+
+/// A request waiting on the game: who sent it, the features agreed with
+/// them, and where to send the reply.
+pub struct Call {
+    pub client: Client,
+    pub features: Features,
+    pub request: Request,
+    pub reply: Sender<String>,
+}
+
+/// A player's number as requests give it, counting from 1, counting from 0.
+fn seat(word: &str) -> Result<usize, String> {
+    match word.parse::<usize>() {
+        Ok(player @ 1..=2) => Ok(player - 1),
+        _ => Err(format!("invalid player `{word}`")),
+    }
+}
+
+/// Who's holding a player's seat.
+#[derive(Clone, Copy, Debug)]
+struct Seat {
+    client: Client,
+    /// Given to the client on joining, for it to take the seat back with.
+    token: u64,
+    /// Seconds since the client's connection dropped, if it has.
+    gone: Option<f64>,
+}
+
+/// Which clients are playing as which player. A seat stays held for a
+/// client for `GRACE` seconds after its connection drops, so it can join
+/// again with the seat's token and carry on rather than losing its place.
+#[derive(Clone, Debug, Default)]
+pub struct Seats {
+    held: [Option<Seat>; 2],
+}
+
+impl Seats {
+    /// Gives `player`'s seat to `client`, unless another client holds it,
+    /// giving up any other seat it held. A seat kept for a client that's
+    /// gone is only given back for its `token`. Returns whether it's taking
+    /// a seat back rather than a new one, and the seat's token.
+    pub fn join(
+        &mut self,
+        player: usize,
+        client: Client,
+        token: Option<u64>,
+    ) -> Result<(bool, u64), String> {
+        let (back, token) = match self.held[player] {
+            Some(seat) if seat.gone.is_none() && seat.client != client => {
+                return Err(format!("player {} is taken", player + 1));
+            }
+            Some(seat) if seat.gone.is_some() && token != Some(seat.token) => {
+                return Err(format!("player {} is being kept for someone", player + 1));
+            }
+            Some(seat) => (seat.gone.is_some(), seat.token),
+            None => (false, Rng::random_seed()),
+        };
+        for seat in &mut self.held {
+            if seat.is_some_and(|seat| seat.client == client) {
+                *seat = None;
+            }
+        }
+        self.held[player] = Some(Seat {
+            client,
+            token,
+            gone: None,
+        });
+        Ok((back, token))
+    }
+
+    /// Marks `client`'s connection as dropped, returning the player it was
+    /// playing as, if any.
+    pub fn leave(&mut self, client: Client) -> Option<usize> {
+        let player = self
+            .held
+            .iter()
+            .position(|seat| seat.is_some_and(|seat| seat.client == client))?;
+        if let Some(seat) = &mut self.held[player] {
+            seat.gone = Some(0.);
+        }
+        Some(player)
+    }
+
+    /// Counts down the grace for seats whose clients have gone, freeing any
+    /// that have run out of it. Returns the players who've been dropped.
+    pub fn update(&mut self, dt: f64) -> Vec<usize> {
+        let mut dropped = Vec::new();
+        for (player, seat) in self.held.iter_mut().enumerate() {
+            let Some(Seat {
+                gone: Some(gone), ..
+            }) = seat
+            else {
+                continue;
+            };
+            *gone += dt;
+            if *gone >= GRACE {
+                *seat = None;
+                dropped.push(player);
+            }
+        }
+        dropped
+    }
+
+    /// Whether any seat is being kept for a client that's gone.
+    pub fn waiting(&self) -> bool {
+        self.held
+            .iter()
+            .any(|seat| seat.is_some_and(|seat| seat.gone.is_some()))
+    }
+}
+
+/// Binds a socket at `path` that only its owner can connect to. It's bound
+/// inside a directory no one else can get into and only moved to `path`
+/// once its own permissions are set, so there's never a moment it's open to
+/// anyone else.
+fn bind_private(path: &str) -> io::Result<UnixListener> {
+    let private = PathBuf::from(format!("{path}.{}", std::process::id()));
+    fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let socket = private.join("socket");
+    let bound = UnixListener::bind(&socket).and_then(|listener| {
+        fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&socket, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_file(&socket);
+    let _ = fs::remove_dir(&private);
+    bound
+}
+
+/// A Unix socket that tooling can send the running game requests through,
+/// one per line, each answered with a line back. There's no authentication:
+/// the socket is only reachable on this machine and only by its owner.
+pub struct ControlSocket {
+    path: PathBuf,
+    requests: Receiver<Call>,
+    notices: Receiver<String>,
+    /// Clients that have hung up.
+    departures: Receiver<Client>,
+}
+
+impl ControlSocket {
+    /// Listens on `path`, replacing a socket left behind by a game that's no
+    /// longer running.
+    pub fn bind(path: &str) -> Result<ControlSocket, String> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("{path}: another game is already listening"));
+        }
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(format!("{path}: {err}"));
+            }
+            _ => {}
+        }
+        let listener = bind_private(path).map_err(|err| format!("{path}: {err}"))?;
+        let (sender, requests) = mpsc::channel();
+        let (notify, notices) = mpsc::channel();
+        let (depart, departures) = mpsc::channel();
+        thread::spawn(move || {
+            for (id, client) in (0..).zip(listener.incoming().flatten()) {
+                let sender = sender.clone();
+                let notify = notify.clone();
+                let depart = depart.clone();
+                let _ = notify.send("control client connected".to_string());
+                thread::spawn(move || {
+                    serve(client, id, sender);
+                    let _ = depart.send(id);
+                    let _ = notify.send("control client left".to_string());
+                });
+            }
+        });
+        Ok(ControlSocket {
+            path: PathBuf::from(path),
+            requests,
+            notices,
+            departures,
+        })
+    }
+
+    /// The next request waiting to be answered, if any.
+    pub fn poll(&self) -> Option<Call> {
+        self.requests.try_recv().ok()
+    }
+
+    /// The next client to have hung up, if any.
+    pub fn departed(&self) -> Option<Client> {
+        self.departures.try_recv().ok()
+    }
+
+    /// The next thing worth telling the player about, such as a client
+    /// connecting, if any.
+    pub fn notice(&self) -> Option<String> {
+        self.notices.try_recv().ok()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Answers one client's requests until it hangs up or the game stops. A
+/// client that hasn't said hello is taken to be this build, with every
+/// feature but compression, which it has to ask for; one that has only gets
+/// the features both understand.
+fn serve(client: UnixStream, id: Client, requests: Sender<Call>) {
+    let Ok(mut out) = client.try_clone() else {
+        return;
+    };
+    let mut features = Features::ALL.without(Features::COMPRESSION);
+    let mut encoder = Encoder::default();
+    for line in BufReader::new(client).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        log!(Debug, "control request: {line}");
+        let reply = match Message::parse(&line) {
+            Ok(Message::Hello(version, _)) if version != PROTOCOL => {
+                log!(Info, "control client {id} speaks protocol {version}");
+                let _ = writeln!(
+                    out,
+                    "error: protocol version {version} isn't supported, this game speaks {PROTOCOL}"
+                );
+                return;
+            }
+            Ok(Message::Hello(_, theirs)) => {
+                features = theirs.common(Features::ALL);
+                format!("hello {PROTOCOL} {}", features.bits())
+            }
+            Ok(Message::Ack(_)) if !features.has(Features::COMPRESSION) => {
+                format!("error: `{line}` needs a feature that wasn't agreed")
+            }
+            Ok(Message::Ack(seq)) => match encoder.ack(seq) {
+                Ok(()) => "ok".to_string(),
+                Err(err) => format!("error: {err}"),
+            },
+            Ok(Message::Request(request)) if !features.has(request.needs()) => {
+                format!("error: `{line}` needs a feature that wasn't agreed")
+            }
+            Ok(Message::Request(request)) => {
+                let compress =
+                    matches!(request, Request::State) && features.has(Features::COMPRESSION);
+                let (sender, reply) = mpsc::channel();
+                let call = Call {
+                    client: id,
+                    features,
+                    request,
+                    reply: sender,
+                };
+                if requests.send(call).is_err() {
+                    return;
+                }
+                match reply.recv() {
+                    Ok(reply) if compress && reply.starts_with('{') => encoder.encode(&reply),
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(err) => format!("error: {err}"),
+        };
+        if writeln!(out, "{reply}").is_err() {
+            return;
+        }
+    }
+}
+
+/// Sends each of `requests` to the game listening on `path`, printing the
+/// replies, after saying hello to check it speaks the same protocol.
+pub fn client(path: &str, requests: impl Iterator<Item = String>) -> Result<(), String> {
+    let socket = UnixStream::connect(path).map_err(|err| format!("{path}: {err}"))?;
+    let mut out = socket.try_clone().map_err(|err| err.to_string())?;
+    let mut replies = BufReader::new(socket).lines();
+    // Replies are printed as they come, so they're asked for in full.
+    let features = Features::ALL.without(Features::COMPRESSION);
+    writeln!(out, "hello {PROTOCOL} {}", features.bits()).map_err(|err| err.to_string())?;
+    match replies.next() {
+        Some(Ok(reply)) if reply.starts_with("hello ") => {}
+        Some(Ok(reply)) => return Err(reply.trim_start_matches("error: ").to_string()),
+        Some(Err(err)) => return Err(err.to_string()),
+        None => return Err("the game hung up".to_string()),
+    }
+    for request in requests {
+        writeln!(out, "{request}").map_err(|err| err.to_string())?;
+        match replies.next() {
+            Some(reply) => println!("{}", reply.map_err(|err| err.to_string())?),
+            None => return Err("the game hung up".to_string()),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::{
+    game::{
+        DeathCause,
+        Game,
+        HUD_ROWS,
+        Snapshot,
+    },
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Arena offsets for each frame of the shake after hitting a wall.
+const SHAKE: [(i32, i32); 3] = [(1, 0), (-1, 0), (0, 1)];
+
+/// Frames per second the shake is paced at.
+const SHAKE_RATE: f64 = 30.;
+
+/// Seconds the body flashes for.
+const FLASH_TIME: f64 = 0.8;
+
+/// Flashes per second.
+const FLASH_RATE: f64 = 8.;
+
+/// Seconds the body takes to dissolve from the tail.
+const DISSOLVE_TIME: f64 = 0.8;
+
+/// How fast the final moments replay, relative to real time.
+const REPLAY_SPEED: f64 = 0.5;
+
+/// Plays out a death before the game-over screen: the body flashes, crumbles
+/// away tail first, and then the final moments can be replayed in slow
+/// motion.
+#[derive(Debug, Clone)]
+pub struct DeathScreen {
+    pub game: Game,
+    replay: bool,
+    shake: bool,
+    elapsed: f64,
+}
+
+impl DeathScreen {
+    /// `shake` jolts the arena for a few frames if the snake ran into
+    /// something.
+    pub fn new(game: Game, replay: bool, shake: bool) -> Self {
+        let shake = shake && game.stats.death == Some(DeathCause::Wall);
+        Self {
+            game,
+            replay,
+            shake,
+            elapsed: 0.,
+        }
+    }
+
+    fn replay_time(&self) -> f64 {
+        if self.replay {
+            self.game.history.len() as f64 / self.game.speed() / REPLAY_SPEED
+        } else {
+            0.
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= FLASH_TIME + DISSOLVE_TIME + self.replay_time()
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut snapshot = self.game.snapshot();
+        let mut caption = None;
+        if self.elapsed < FLASH_TIME {
+            if (self.elapsed * FLASH_RATE) as u64 % 2 == 1 && !renderer.reduced_motion {
+                snapshot.segments.clear();
+            }
+        } else if self.elapsed < FLASH_TIME + DISSOLVE_TIME {
+            let left = 1. - (self.elapsed - FLASH_TIME) / DISSOLVE_TIME;
+            let keep = (snapshot.segments.len() as f64 * left).ceil() as usize;
+            snapshot.segments.truncate(keep);
+        } else if let Some(replayed) = self.replayed_tick() {
+            snapshot = replayed.clone();
+            caption = Some("replay - enter to skip");
+        }
+        let offset = match SHAKE.get((self.elapsed * SHAKE_RATE) as usize) {
+            Some(&offset) if self.shake => offset,
+            _ => (0, 0),
+        };
+        let mut frame = self
+            .game
+            .draw_scene(renderer, width, height, &snapshot, offset);
+        if let Some(caption) = caption {
+            frame.print_centered(HUD_ROWS, caption, renderer.theme.colors.head);
+        }
+        frame
+    }
+
+    fn replayed_tick(&self) -> Option<&Snapshot> {
+        let history = &self.game.history;
+        let replaying = self.elapsed - FLASH_TIME - DISSOLVE_TIME;
+        let index = (replaying * REPLAY_SPEED * self.game.speed()) as usize;
+        history.get(index.min(history.len().saturating_sub(1)))
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    Vec2,
+    game::{
+        Game,
+        Snapshot,
+    },
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Cells shown either side of where the snake died, across and down.
+const REACH_X: u16 = 8;
+const REACH_Y: u16 = 4;
+
+/// Seconds the last tick is held before the loop starts again.
+const HOLD: f64 = 0.75;
+
+/// A small inset looping the last few seconds before a death, zoomed in on
+/// where it happened.
+#[derive(Clone, Debug)]
+pub struct DeathCam {
+    ticks: Vec<Snapshot>,
+    /// Cells that can't be moved through around the death, in inset
+    /// coordinates.
+    walls: Vec<Vec2>,
+    /// Arena cells the top left of the inset shows.
+    origin: Vec2,
+    /// Ticks played each second.
+    rate: f64,
+    elapsed: f64,
+}
+
+impl DeathCam {
+    /// The death cam for a game that's just ended, if it has anything to
+    /// show.
+    pub fn new(game: &Game) -> Option<DeathCam> {
+        game.stats.death?;
+        let ticks: Vec<Snapshot> = game.history.iter().cloned().collect();
+        let last = ticks.last()?.segments.first().copied()?;
+        let origin = Vec2::new(
+            last.x.round() - REACH_X as f64,
+            last.y.round() - REACH_Y as f64,
+        );
+        let mut walls = Vec::new();
+        for y in 0..=2 * REACH_Y {
+            for x in 0..=2 * REACH_X {
+                let cell = Vec2::new(x as f64, y as f64);
+                if !game.inside(origin + cell) {
+                    walls.push(cell);
+                }
+            }
+        }
+        Some(DeathCam {
+            ticks,
+            walls,
+            origin,
+            rate: game.speed(),
+            elapsed: 0.,
+        })
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt;
+    }
+
+    fn tick(&self) -> Option<&Snapshot> {
+        let length = self.ticks.len() as f64 / self.rate + HOLD;
+        let into = self.elapsed % length;
+        let index = (into * self.rate) as usize;
+        self.ticks
+            .get(index.min(self.ticks.len().saturating_sub(1)))
+    }
+
+    /// The inset, border included.
+    pub fn draw(&self, renderer: &Renderer) -> Frame {
+        let mut arena = renderer.arena_frame(2 * REACH_X + 1, 2 * REACH_Y + 1);
+        for &wall in &self.walls {
+            renderer.draw_wall(&mut arena, wall);
+        }
+        if let Some(tick) = self.tick() {
+            tick.shifted(Vec2::new(-self.origin.x, -self.origin.y))
+                .draw(renderer, &mut arena);
+        }
+        let mut frame = renderer.frame(arena.width() + 2, arena.height() + 2);
+        frame.blit(&arena, 1, 1);
+        frame.outline(
+            0,
+            0,
+            frame.width(),
+            frame.height(),
+            renderer.caps.unicode,
+            renderer.theme.colors.wall,
+        );
+        frame
+    }
+}
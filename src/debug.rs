@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use crate::{
+    game::Game,
+    profile,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Frames the overlay averages its numbers over, about four seconds' worth.
+const WINDOW: usize = 120;
+
+/// Columns of the frame time sparkline, each the slowest of the frames it
+/// covers.
+const SPARK_WIDTH: usize = 30;
+
+/// Sparkline bars from shortest to tallest.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_SPARKS: [char; 8] = ['_', '.', ',', '-', '~', '=', '*', '#'];
+
+/// What one frame of the loop took.
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    /// Seconds since the previous frame.
+    dt: f64,
+    /// Seconds spent updating and drawing, before waiting for the next frame.
+    work: f64,
+    /// Game ticks run so far, if a game was being played.
+    ticks: Option<u64>,
+}
+
+/// Performance and game state numbers drawn in the top-right corner. Toggled
+/// with F3.
+#[derive(Debug, Clone, Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    samples: VecDeque<Sample>,
+}
+
+impl DebugOverlay {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Notes a finished frame, whether or not the overlay is showing.
+    pub fn record(&mut self, dt: f64, work: f64, game: Option<&Game>) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        let ticks = game.map(|game| game.ticks);
+        self.samples.push_back(Sample { dt, work, ticks });
+    }
+
+    fn fps(&self) -> f64 {
+        let elapsed: f64 = self.samples.iter().map(|sample| sample.dt).sum();
+        if elapsed > 0. {
+            self.samples.len() as f64 / elapsed
+        } else {
+            0.
+        }
+    }
+
+    /// Game ticks per second over the window, counting only frames of the
+    /// current game.
+    fn tick_rate(&self) -> f64 {
+        let Some(last) = self.samples.back().and_then(|sample| sample.ticks) else {
+            return 0.;
+        };
+        let mut first = last;
+        let mut elapsed = 0.;
+        for sample in self.samples.iter().rev() {
+            match sample.ticks {
+                Some(ticks) if ticks <= first => {
+                    first = ticks;
+                    elapsed += sample.dt;
+                }
+                _ => break,
+            }
+        }
+        if elapsed > 0. {
+            (last - first) as f64 / elapsed
+        } else {
+            0.
+        }
+    }
+
+    /// Work time of the frame at percentile `p`, in milliseconds.
+    fn work_percentile(&self, p: f64) -> f64 {
+        let mut work: Vec<f64> = self.samples.iter().map(|sample| sample.work).collect();
+        work.sort_by(f64::total_cmp);
+        profile::percentile(&work, p)
+    }
+
+    /// Work time over the window as a row of bars, oldest on the left,
+    /// scaled so the slowest frame is a full bar.
+    fn sparkline(&self, unicode: bool) -> String {
+        let sparks = if unicode { SPARKS } else { ASCII_SPARKS };
+        let per_column = WINDOW.div_ceil(SPARK_WIDTH);
+        let work: Vec<f64> = self.samples.iter().map(|sample| sample.work).collect();
+        let columns: Vec<f64> = work
+            .chunks(per_column)
+            .map(|chunk| chunk.iter().copied().fold(0., f64::max))
+            .collect();
+        let slowest = columns.iter().copied().fold(f64::EPSILON, f64::max);
+        columns
+            .iter()
+            .map(|&work| {
+                let level = (work / slowest * (sparks.len() - 1) as f64).round() as usize;
+                sparks[level.min(sparks.len() - 1)]
+            })
+            .collect()
+    }
+
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame, game: Option<&Game>) {
+        if !self.visible {
+            return;
+        }
+        let mut lines = vec![
+            format!("fps    {:.1}", self.fps()),
+            format!("ticks  {:.1}/s", self.tick_rate()),
+            format!(
+                "frame  p50 {:.1}  p95 {:.1}  p99 {:.1} ms",
+                self.work_percentile(0.5),
+                self.work_percentile(0.95),
+                self.work_percentile(0.99),
+            ),
+            format!("       {}", self.sparkline(renderer.caps.unicode)),
+        ];
+        if let Some(game) = game {
+            let head = game.player.head;
+            lines.push(format!("drops  {}", game.player.dropped_turns));
+            lines.push(format!("head   {},{}", head.x, head.y));
+            lines.push(format!(
+                "snake {}  food {}  effects {}",
+                game.player.len(),
+                game.food.len(),
+                game.effects.len()
+            ));
+        }
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let col = frame.width().saturating_sub(width);
+        for (row, line) in lines.iter().enumerate() {
+            frame.print_fg(col, row as u16, line, renderer.theme.colors.wall);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crate::json;
+
+/// How long snapshots can go unacknowledged before the next is sent in
+/// full, in case the client's lost track.
+pub const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Snapshots kept waiting for an acknowledgement; older ones can no longer
+/// be acknowledged.
+const UNACKED: usize = 32;
+
+/// Snapshots as a line of JSON each, as sent to one client: each numbered,
+/// and written as only what's changed since the last one the client
+/// acknowledged having. Until it's acknowledged one, or if it stops doing
+/// so for `ACK_TIMEOUT`, they're sent in full.
+///
+/// A full snapshot is `{"seq": 4, "full": {...}}`. A delta is
+/// `{"seq": 5, "base": 4, "changed": {...}, "removed": [...]}`, with the
+/// members whose values differ from snapshot `base` and the keys no longer
+/// there. A changed array can come as `{"push": [...], "keep": n}`: the
+/// elements given, followed by the first `n` of the array as it was.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    seq: u64,
+    /// The last snapshot the client acknowledged, and its number.
+    acked: Option<(u64, String)>,
+    /// Snapshots sent since, oldest first, each with when it was sent.
+    sent: VecDeque<(u64, String, Instant)>,
+    /// When the oldest snapshot not acknowledged was sent.
+    waiting: Option<Instant>,
+}
+
+impl Encoder {
+    /// Numbers `snapshot`, a JSON object, and writes it against the last
+    /// one acknowledged.
+    pub fn encode(&mut self, snapshot: &str) -> String {
+        if self
+            .waiting
+            .is_some_and(|since| since.elapsed() >= ACK_TIMEOUT)
+        {
+            self.acked = None;
+            self.sent.clear();
+            self.waiting = None;
+        }
+        self.seq += 1;
+        let line = match &self.acked {
+            Some((base, old)) => match delta(old, snapshot) {
+                Some(delta) => format!(r#"{{"seq": {}, "base": {base}, {delta}}}"#, self.seq),
+                None => format!(r#"{{"seq": {}, "full": {snapshot}}}"#, self.seq),
+            },
+            None => format!(r#"{{"seq": {}, "full": {snapshot}}}"#, self.seq),
+        };
+        if self.sent.len() >= UNACKED {
+            self.sent.pop_front();
+        }
+        let now = Instant::now();
+        self.sent.push_back((self.seq, snapshot.to_string(), now));
+        self.waiting.get_or_insert(now);
+        line
+    }
+
+    /// Takes snapshot `seq` as the client's, to write the next against.
+    pub fn ack(&mut self, seq: u64) -> Result<(), String> {
+        let index = self
+            .sent
+            .iter()
+            .position(|&(sent, ..)| sent == seq)
+            .ok_or_else(|| format!("no snapshot {seq} waiting"))?;
+        self.acked = self
+            .sent
+            .drain(..=index)
+            .next_back()
+            .map(|(seq, snapshot, _)| (seq, snapshot));
+        self.waiting = self.sent.front().map(|&(.., sent)| sent);
+        Ok(())
+    }
+}
+
+/// The `"changed"` and `"removed"` members taking object `old` to `new`, or
+/// `None` if either isn't an object.
+fn delta(old: &str, new: &str) -> Option<String> {
+    let old = json::fields(old)?;
+    let new = json::fields(new)?;
+    let changed: Vec<String> = new
+        .iter()
+        .filter_map(
+            |&(key, value)| match old.iter().find(|&&(other, _)| other == key) {
+                Some(&(_, before)) if before == value => None,
+                Some(&(_, before)) => Some(format!("{key}: {}", shifted(before, value))),
+                None => Some(format!("{key}: {value}")),
+            },
+        )
+        .collect();
+    let removed: Vec<&str> = old
+        .iter()
+        .map(|&(key, _)| key)
+        .filter(|&key| new.iter().all(|&(other, _)| other != key))
+        .collect();
+    Some(format!(
+        r#""changed": {{{}}}, "removed": [{}]"#,
+        changed.join(", "),
+        removed.join(", ")
+    ))
+}
+
+/// Array `new` as elements pushed onto the front of array `old`, if it's
+/// that with some cut off the end, as a snake is from one move to the next.
+/// Anything else is just `new`.
+fn shifted(old: &str, new: &str) -> String {
+    let (Some(before), Some(after)) = (json::elements(old), json::elements(new)) else {
+        return new.to_string();
+    };
+    let pushed = (0..after.len()).find(|&pushed| {
+        let kept = &after[pushed..];
+        kept.len() <= before.len() && before[..kept.len()] == *kept
+    });
+    match pushed {
+        Some(pushed) if pushed < after.len() => format!(
+            r#"{{"push": [{}], "keep": {}}}"#,
+            after[..pushed].join(", "),
+            after.len() - pushed
+        ),
+        _ => new.to_string(),
+    }
+}
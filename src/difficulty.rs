@@ -0,0 +1,91 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    food::FoodKind,
+    game::Game,
+};
+
+/// A bundle of settings making a run easier or harder. Scores are only
+/// compared within a difficulty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Difficulty {
+    /// Slower, the walls wrap round and there's no poison.
+    Easy,
+    #[default]
+    Normal,
+    /// Faster, speeding up with every apple, with more poison about.
+    Hard,
+    /// Faster still, speeding up quicker, with poison everywhere.
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    /// How fast the snake starts, as a multiple of the usual.
+    fn speed(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.3,
+            Difficulty::Insane => 1.6,
+        }
+    }
+
+    /// Cells per second the snake speeds up by for each apple.
+    fn ramp(self) -> f64 {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => 0.,
+            Difficulty::Hard => 0.1,
+            Difficulty::Insane => 0.25,
+        }
+    }
+
+    /// How many times the usual amount of poison turns up.
+    fn poison(self) -> u32 {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+            Difficulty::Insane => 4,
+        }
+    }
+
+    /// Sets `game` up to be played at this difficulty.
+    pub fn apply(self, game: &mut Game) {
+        game.difficulty = self;
+        game.set_speed(self.speed());
+        game.ramp = self.ramp();
+        game.rules.wraps |= self == Difficulty::Easy;
+        game.food_rules.poison *= self.poison();
+        if !game.rules.poison {
+            game.food_rules.poison = 0;
+        }
+        if game.food_rules.poison == 0 {
+            game.food.retain(|food| food.kind != FoodKind::Poison);
+        }
+    }
+}
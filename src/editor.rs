@@ -0,0 +1,101 @@
+use crate::{
+    Commands,
+    Vec2,
+    food::FoodKind,
+    game::HUD_ROWS,
+    level::Level,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+const PROMPT: &str = "o wall  f food  g start, turn  x clear  F5 save  enter test  m menu";
+
+/// A screen for laying out a custom level: a cursor moves over the arena,
+/// putting down walls, food spawners and the snake's start, and the level
+/// can be saved or tried out straight away.
+#[derive(Clone, Debug)]
+pub struct EditorScreen {
+    pub level: Level,
+    /// Where the level is saved to.
+    pub path: String,
+    cursor: Vec2,
+    /// How saving went, once it's been tried.
+    notice: Option<String>,
+}
+
+impl EditorScreen {
+    pub fn new(level: Level, path: String) -> Self {
+        Self {
+            cursor: level.spawn,
+            level,
+            path,
+            notice: None,
+        }
+    }
+
+    /// Carries out an editing command. Anything that isn't one is left for
+    /// the screen's caller.
+    pub fn handle(&mut self, command: Commands) {
+        let step = match command {
+            Commands::RotatePlayer(angle) | Commands::RotatePartner(angle) => {
+                Vec2::new(angle.signum(), 0.)
+            }
+            Commands::Up => Vec2::new(0., -1.),
+            Commands::Down => Vec2::new(0., 1.),
+            _ => Vec2::new(0., 0.),
+        };
+        let cursor = self.cursor + step;
+        self.cursor = Vec2::new(
+            cursor.x.clamp(0., (self.level.width - 1) as f64),
+            cursor.y.clamp(0., (self.level.height - 1) as f64),
+        );
+        match command {
+            Commands::Obstacle => self.level.toggle_wall(self.cursor),
+            Commands::Food => self.level.toggle_spawner(self.cursor),
+            Commands::Spawn => self.level.set_spawn(self.cursor),
+            Commands::Delete => self.level.clear(self.cursor),
+            Commands::QuickSave => {
+                self.notice = Some(match self.level.save(&self.path) {
+                    Ok(()) => format!("saved {}", self.path),
+                    Err(err) => format!("couldn't save: {err}"),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let level = &self.level;
+        let mut arena = renderer.arena_frame(level.width, level.height);
+        for &wall in &level.walls {
+            renderer.draw_wall(&mut arena, wall);
+        }
+        for &spawner in &level.spawners {
+            renderer.draw_food(&mut arena, spawner, FoodKind::Normal);
+        }
+        renderer.draw_snake(&mut arena, level.direction.step(), &[level.spawn]);
+        renderer.highlight_cell(&mut arena, self.cursor, colors.bonus);
+        let arena = arena.scaled(renderer.scale);
+        let rows = height.saturating_sub(HUD_ROWS + 1);
+        frame.blit(
+            &arena,
+            width.saturating_sub(arena.width()) / 2,
+            HUD_ROWS + rows.saturating_sub(arena.height()) / 2,
+        );
+        let title = format!(
+            "{}  {}x{}  {}, {}",
+            self.path, level.width, level.height, self.cursor.x, self.cursor.y
+        );
+        frame.print_fg(0, 0, &title, colors.head);
+        if let Some(notice) = &self.notice {
+            let col = width.saturating_sub(notice.chars().count() as u16);
+            frame.print_fg(col, 0, notice, colors.bonus);
+        }
+        frame.print_centered(height.saturating_sub(1), PROMPT, colors.wall);
+        frame
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    Vec2,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Seconds the eaten food's cell flashes for.
+const FLASH_TIME: f64 = 0.25;
+
+/// Seconds the head stays highlighted after eating.
+const GLOW_TIME: f64 = 0.3;
+
+/// Seconds a popup takes to float away and fade.
+const POPUP_TIME: f64 = 0.8;
+
+#[derive(Debug, Clone)]
+pub enum EffectKind {
+    /// Lights up the background of a cell.
+    Flash,
+    /// Lights up the background under the snake's head, wherever it goes.
+    Glow,
+    /// Text that floats up from a cell.
+    Popup(String),
+}
+
+/// Something short-lived drawn over the arena.
+#[derive(Debug, Clone)]
+pub struct Effect {
+    pub kind: EffectKind,
+    /// Arena cell the effect started on.
+    pub at: Vec2,
+    age: f64,
+}
+
+impl Effect {
+    fn lifetime(&self) -> f64 {
+        match self.kind {
+            EffectKind::Flash => FLASH_TIME,
+            EffectKind::Glow => GLOW_TIME,
+            EffectKind::Popup(_) => POPUP_TIME,
+        }
+    }
+
+    /// How far through its life the effect is, from 0 to 1.
+    fn progress(&self) -> f64 {
+        (self.age / self.lifetime()).min(1.)
+    }
+}
+
+/// The transient effects currently playing.
+#[derive(Debug, Clone, Default)]
+pub struct Effects {
+    list: Vec<Effect>,
+}
+
+impl Effects {
+    pub fn spawn(&mut self, kind: EffectKind, at: Vec2) {
+        self.list.push(Effect { kind, at, age: 0. });
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Ages every effect and drops those that have played out.
+    pub fn update(&mut self, dt: f64) {
+        for effect in &mut self.list {
+            effect.age += dt;
+        }
+        self.list.retain(|effect| effect.age < effect.lifetime());
+    }
+
+    /// Draws the effects over an arena frame that already has the snake on
+    /// it, whose head is now at `head`. Effect positions are moved by `shift`
+    /// to match the frame.
+    pub fn draw(&self, renderer: &Renderer, arena: &mut Frame, shift: Vec2, head: Vec2) {
+        let colors = &renderer.theme.colors;
+        for effect in &self.list {
+            let fade = colors.food.lerp(colors.background, effect.progress());
+            match &effect.kind {
+                EffectKind::Flash => renderer.highlight_cell(arena, effect.at + shift, fade),
+                EffectKind::Glow => renderer.highlight_cell(arena, head, fade),
+                EffectKind::Popup(text) => {
+                    let (col, row) = renderer.screen_cell(effect.at + shift);
+                    let rise = (effect.progress() * 2.) as u16;
+                    let col = col.saturating_sub(text.chars().count() as u16 / 2);
+                    arena.print_fg(col, row.saturating_sub(rise + 1), text, fade);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt::Debug;
+
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+use crate::{
+    Vec2,
+    render::{
+        Frame,
+        Renderer,
+    },
+    spatial::SpatialHash,
+};
+
+/// Anything in a game's entity registry: what's on the board besides the
+/// snakes and the food, which the game keeps track of itself. Games update,
+/// draw and collide with every registered entity through this, so a new
+/// kind of thing on the board only needs an implementation rather than
+/// changes all through `Game`.
+///
+/// It's kept to the crate because `Clone` for boxed entities would otherwise
+/// make everything its methods take count as public.
+pub(crate) trait Entity: Debug {
+    /// Advances it by `dt` seconds of play.
+    fn update(&mut self, _dt: f64) {}
+
+    /// The arena cells it takes up.
+    fn cells(&self) -> Vec<Vec2>;
+
+    /// Whether updating it can change the cells it takes up.
+    fn moves(&self) -> bool {
+        false
+    }
+
+    /// Whether the snake crashes running into it, rather than passing over.
+    fn solid(&self) -> bool {
+        false
+    }
+
+    /// Whether it's run its course and should be taken off the board.
+    fn expired(&self) -> bool {
+        false
+    }
+
+    /// Draws it into `arena`, moved by `shift` arena cells.
+    fn draw(&self, renderer: &Renderer, arena: &mut Frame, shift: Vec2);
+
+    /// It as it's written into a saved game.
+    fn saved(&self) -> SavedEntity;
+
+    fn clone_box(&self) -> Box<dyn Entity>;
+}
+
+impl Clone for Box<dyn Entity> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// An entity in a saved game, tagged with what kind it is so it can be
+/// put back in the registry on loading.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum SavedEntity {
+    Obstacle(Obstacle),
+}
+
+impl SavedEntity {
+    fn restore(self) -> Box<dyn Entity> {
+        match self {
+            SavedEntity::Obstacle(obstacle) => Box::new(obstacle),
+        }
+    }
+}
+
+/// A game's entity registry, with the entities bucketed by cell so that
+/// collisions and free cells can be looked up without going through them
+/// all. The buckets are kept up to date as entities come and go, and
+/// refreshed each time they're updated if any of them move.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    entities: Vec<Box<dyn Entity>>,
+    cells: SpatialHash,
+}
+
+impl Registry {
+    pub(crate) fn push(&mut self, entity: Box<dyn Entity>) {
+        self.cells.insert(self.entities.len(), entity.cells());
+        self.entities.push(entity);
+    }
+
+    /// Takes off every entity `keep` says no to.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&dyn Entity) -> bool) {
+        let before = self.entities.len();
+        self.entities.retain(|entity| keep(entity.as_ref()));
+        if self.entities.len() != before {
+            self.reindex();
+        }
+    }
+
+    /// Advances every entity by `dt` seconds of play, and takes off any
+    /// that have run their course.
+    pub fn update(&mut self, dt: f64) {
+        let before = self.entities.len();
+        let mut moved = false;
+        for entity in &mut self.entities {
+            entity.update(dt);
+            moved |= entity.moves();
+        }
+        self.entities.retain(|entity| !entity.expired());
+        if moved || self.entities.len() != before {
+            self.reindex();
+        }
+    }
+
+    fn reindex(&mut self) {
+        self.cells = SpatialHash::new(self.entities.iter().map(|entity| entity.cells()));
+    }
+
+    pub(crate) fn as_slice(&self) -> &[Box<dyn Entity>] {
+        &self.entities
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &dyn Entity> {
+        self.entities.iter().map(|entity| entity.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether any entity is touching `at`, taking anything closer than
+    /// `reach` as touching.
+    pub fn occupied(&self, at: Vec2, reach: f64) -> bool {
+        self.cells.occupied(at, reach)
+    }
+
+    /// Whether anything solid is touching `at`.
+    pub fn solid_at(&self, at: Vec2, reach: f64) -> bool {
+        self.cells
+            .entities_at(at, reach)
+            .any(|index| self.entities[index].solid())
+    }
+}
+
+impl From<Vec<Box<dyn Entity>>> for Registry {
+    fn from(entities: Vec<Box<dyn Entity>>) -> Self {
+        let mut registry = Registry {
+            entities,
+            cells: SpatialHash::default(),
+        };
+        registry.reindex();
+        registry
+    }
+}
+
+impl Serialize for Registry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entities.iter().map(|entity| entity.saved()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Registry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = Vec::<SavedEntity>::deserialize(deserializer)?;
+        Ok(saved
+            .into_iter()
+            .map(SavedEntity::restore)
+            .collect::<Vec<_>>()
+            .into())
+    }
+}
+
+/// A block put down in practice mode for the snake to steer round.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Obstacle {
+    pub at: Vec2,
+}
+
+impl Entity for Obstacle {
+    fn cells(&self) -> Vec<Vec2> {
+        vec![self.at]
+    }
+
+    fn solid(&self) -> bool {
+        true
+    }
+
+    fn draw(&self, renderer: &Renderer, arena: &mut Frame, shift: Vec2) {
+        renderer.draw_wall(arena, self.at + shift);
+    }
+
+    fn saved(&self) -> SavedEntity {
+        SavedEntity::Obstacle(self.clone())
+    }
+
+    fn clone_box(&self) -> Box<dyn Entity> {
+        Box::new(self.clone())
+    }
+}
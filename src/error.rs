@@ -0,0 +1,53 @@
+use std::{
+    error::Error,
+    fmt,
+    io,
+};
+
+/// Whatever can go wrong running the game that means it has to stop.
+#[derive(Debug)]
+pub enum SnakeError {
+    /// Couldn't switch the terminal into raw mode or the alternate screen.
+    TerminalSetup(io::Error),
+    /// Couldn't find out how big the terminal is.
+    TerminalSize(io::Error),
+    /// Writing to the terminal failed.
+    Output(io::Error),
+    /// The snake's start asked for doesn't fit the arena.
+    Start(String),
+}
+
+impl fmt::Display for SnakeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnakeError::TerminalSetup(err) => write!(f, "couldn't set up the terminal: {err}"),
+            SnakeError::TerminalSize(err) => {
+                write!(f, "couldn't get the size of the terminal: {err}")
+            }
+            SnakeError::Output(err) => write!(f, "couldn't draw to the terminal: {err}"),
+            SnakeError::Start(err) => write!(f, "couldn't start the run: {err}"),
+        }
+    }
+}
+
+impl Error for SnakeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnakeError::TerminalSetup(err)
+            | SnakeError::TerminalSize(err)
+            | SnakeError::Output(err) => Some(err),
+            SnakeError::Start(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnakeError {
+    fn from(err: io::Error) -> Self {
+        SnakeError::Output(err)
+    }
+}
+
+/// The terminal's size in columns and rows.
+pub fn terminal_size() -> Result<(u16, u16), SnakeError> {
+    termion::terminal_size().map_err(SnakeError::TerminalSize)
+}
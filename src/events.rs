@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use crate::{
+    achievements::Achievement,
+    food::FoodKind,
+    game::DeathCause,
+    gameover::format_time,
+    power::PowerUp,
+    render::{
+        Frame,
+        Renderer,
+    },
+    tutorial::Lesson,
+};
+
+/// Events kept in the log. Older ones are dropped.
+const LOG_SIZE: usize = 50;
+
+/// Width of the event panel in terminal cells, including its border.
+const PANEL_WIDTH: u16 = 32;
+
+/// Something that happened during a run. The game queues these up as it goes
+/// for everything that reacts to them, such as the event log, notices and
+/// saving achievements, to pick up after each update.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    FoodEaten {
+        kind: FoodKind,
+        points: u32,
+    },
+    Poisoned,
+    PowerUpStarted(PowerUp),
+    /// A power-up ran out, or was cancelled by picking up its opposite.
+    PowerUpEnded(PowerUp),
+    ShieldPickedUp,
+    /// A shield took a crash.
+    ShieldUsed,
+    Portal,
+    LifeLost(DeathCause),
+    Died {
+        cause: DeathCause,
+    },
+    MissionComplete {
+        reward: u32,
+    },
+    Achievement(Achievement),
+    Rewound,
+    /// The snake passed close by something at speed, slowing time down.
+    NearMiss,
+    /// A player went out of a round of a versus match.
+    PlayerOut {
+        name: String,
+        cause: DeathCause,
+    },
+    /// A round of a versus match ended, won by the player named, or by
+    /// neither if both went out at once.
+    RoundOver {
+        winner: Option<String>,
+    },
+    /// The tutorial moved on to a new lesson.
+    Lesson(Lesson),
+}
+
+impl GameEvent {
+    /// A short, stable name for the kind of event, for programs reading the
+    /// state stream.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::FoodEaten { .. } => "food_eaten",
+            GameEvent::Poisoned => "poisoned",
+            GameEvent::PowerUpStarted(_) => "power_up_started",
+            GameEvent::PowerUpEnded(_) => "power_up_ended",
+            GameEvent::ShieldPickedUp => "shield_picked_up",
+            GameEvent::ShieldUsed => "shield_used",
+            GameEvent::Portal => "portal",
+            GameEvent::LifeLost(_) => "life_lost",
+            GameEvent::Died { .. } => "died",
+            GameEvent::MissionComplete { .. } => "mission_complete",
+            GameEvent::Achievement(_) => "achievement",
+            GameEvent::Rewound => "rewound",
+            GameEvent::NearMiss => "near_miss",
+            GameEvent::PlayerOut { .. } => "player_out",
+            GameEvent::RoundOver { .. } => "round_over",
+            GameEvent::Lesson(_) => "lesson",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            GameEvent::FoodEaten { kind, points } => {
+                let name = match kind {
+                    FoodKind::Bonus => "bonus apple",
+                    FoodKind::Mega => "mega apple",
+                    FoodKind::Mouse => "mouse",
+                    FoodKind::Fruit => "fruit",
+                    _ => "apple",
+                };
+                format!("ate {name} +{points}")
+            }
+            GameEvent::Poisoned => "ate poison".to_string(),
+            GameEvent::PowerUpStarted(power) => format!("picked up {}", power.name()),
+            GameEvent::PowerUpEnded(power) => format!("{} wore off", power.name()),
+            GameEvent::ShieldPickedUp => "picked up a shield".to_string(),
+            GameEvent::ShieldUsed => "shield took the hit".to_string(),
+            GameEvent::Portal => "portal used".to_string(),
+            GameEvent::LifeLost(cause) => format!("lost a life: {}", cause.describe()),
+            GameEvent::Died { cause } => format!("died: {}", cause.describe()),
+            GameEvent::MissionComplete { reward } => format!("mission complete +{reward}"),
+            GameEvent::Achievement(achievement) => format!("unlocked {}", achievement.name()),
+            GameEvent::Rewound => "rewound".to_string(),
+            GameEvent::NearMiss => "near miss".to_string(),
+            GameEvent::PlayerOut { name, cause } => format!("{name} {}", cause.describe()),
+            GameEvent::RoundOver {
+                winner: Some(winner),
+            } => format!("{winner} took the round"),
+            GameEvent::RoundOver { winner: None } => "round drawn".to_string(),
+            GameEvent::Lesson(lesson) => lesson.prompt(),
+        }
+    }
+}
+
+/// The latest events of a run, each with the run time it happened at, oldest
+/// first.
+#[derive(Clone, Debug, Default)]
+pub struct EventLog {
+    entries: VecDeque<(f64, GameEvent)>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, time: f64, event: GameEvent) {
+        if self.entries.len() >= LOG_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((time, event));
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(f64, GameEvent)> {
+        self.entries.iter()
+    }
+}
+
+/// Draws the log down the left of `frame` from `top`, newest first, with as
+/// many events as fit.
+pub fn draw_panel(renderer: &Renderer, frame: &mut Frame, top: u16, log: &EventLog) {
+    let width = PANEL_WIDTH.min(frame.width());
+    let height = frame.height().saturating_sub(top);
+    if width < 3 || height < 3 {
+        return;
+    }
+    let glyphs = &renderer.theme.glyphs;
+    let colors = &renderer.theme.colors;
+    let mut panel = renderer.frame(width, height);
+    for col in 0..width {
+        panel.put(col, 0, glyphs.wall, colors.wall);
+        panel.put(col, height - 1, glyphs.wall, colors.wall);
+    }
+    for row in 0..height {
+        panel.put(0, row, glyphs.wall, colors.wall);
+        panel.put(width - 1, row, glyphs.wall, colors.wall);
+    }
+    panel.print_fg(2, 0, " events ", colors.head);
+    let inner = (width - 2) as usize;
+    for (row, (time, event)) in (1..height - 1).zip(log.iter().rev()) {
+        let line = format!("{:>6} {}", format_time(*time), event.describe());
+        let line: String = line.chars().take(inner).collect();
+        panel.print(1, row, &line);
+    }
+    frame.blit(&panel, 0, top);
+}
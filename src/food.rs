@@ -0,0 +1,231 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Vec2,
+    power::PowerUp,
+    rng::Rng,
+};
+
+/// Points for a normal apple. The other kinds are worth multiples of it.
+pub const APPLE_SCORE: u32 = 10;
+
+/// Seconds a bonus fruit stays on the board before vanishing.
+pub const BONUS_LIFETIME: f64 = 6.;
+
+/// Seconds between special fruits turning up.
+pub const FRUIT_INTERVAL: f64 = 20.;
+
+/// Seconds a special fruit lasts.
+pub const FRUIT_LIFETIME: f64 = 8.;
+
+/// What a special fruit is worth eaten the moment it appears. It's worth less
+/// the longer it's been out.
+const FRUIT_SCORE: u32 = 5 * APPLE_SCORE;
+
+/// Seconds poison lies around before it goes away.
+pub const POISON_LIFETIME: f64 = 10.;
+
+/// Segments lost to eating poison. A snake no longer than this dies of it.
+pub const POISON_SHRINK: usize = 3;
+
+/// Points lost to eating poison.
+pub const POISON_PENALTY: u32 = 2 * APPLE_SCORE;
+
+/// Seconds a power-up waits to be picked up.
+pub const POWER_LIFETIME: f64 = 8.;
+
+/// Food this close to expiring blinks, faster as the end nears.
+const BLINK_TIME: f64 = 3.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FoodKind {
+    /// The everyday apple.
+    Normal,
+    /// Worth three apples, but only around for `BONUS_LIFETIME`.
+    Bonus,
+    /// Rare and worth ten apples.
+    Mega,
+    /// Runs from the snake when it gets close, so it's worth four apples.
+    Mouse,
+    /// Turns up every `FRUIT_INTERVAL` on top of the usual food, and is
+    /// worth more the sooner it's eaten.
+    Fruit,
+    /// Costs segments and points instead of giving them. Doesn't count
+    /// towards the food on the board.
+    Poison,
+    /// Grants a power-up. Like poison, it's on top of the usual food.
+    Power(PowerUp),
+    /// Saves the snake from one crash. Also on top of the usual food.
+    Shield,
+}
+
+impl FoodKind {
+    /// Segments the snake grows by for eating it.
+    pub fn growth(self) -> u32 {
+        match self {
+            FoodKind::Normal
+            | FoodKind::Bonus
+            | FoodKind::Mega
+            | FoodKind::Mouse
+            | FoodKind::Fruit => 1,
+            FoodKind::Poison | FoodKind::Power(_) | FoodKind::Shield => 0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FoodKind::Normal => "apple",
+            FoodKind::Bonus => "bonus",
+            FoodKind::Mega => "mega",
+            FoodKind::Mouse => "mouse",
+            FoodKind::Fruit => "fruit",
+            FoodKind::Poison => "poison",
+            FoodKind::Power(power) => power.name(),
+            FoodKind::Shield => "shield",
+        }
+    }
+
+    /// Whether it comes on top of the food rules' count rather than being
+    /// part of it.
+    pub fn is_extra(self) -> bool {
+        matches!(
+            self,
+            FoodKind::Fruit | FoodKind::Poison | FoodKind::Power(_) | FoodKind::Shield
+        )
+    }
+
+    /// Seconds it lasts on the board, if it doesn't stay until eaten.
+    pub fn lifetime(self) -> Option<f64> {
+        match self {
+            FoodKind::Bonus => Some(BONUS_LIFETIME),
+            FoodKind::Fruit => Some(FRUIT_LIFETIME),
+            FoodKind::Poison => Some(POISON_LIFETIME),
+            FoodKind::Power(_) | FoodKind::Shield => Some(POWER_LIFETIME),
+            FoodKind::Normal | FoodKind::Mega | FoodKind::Mouse => None,
+        }
+    }
+}
+
+/// Something on the board for the snake to eat.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Food {
+    pub kind: FoodKind,
+    pub at: Vec2,
+    /// Seconds on the board so far.
+    pub age: f64,
+}
+
+impl Food {
+    pub fn new(kind: FoodKind, at: Vec2) -> Self {
+        Self { kind, at, age: 0. }
+    }
+
+    /// Ages it by `dt` seconds of play.
+    pub fn update(&mut self, dt: f64) {
+        self.age += dt;
+    }
+
+    /// Points for eating it now. Poison's cost is `POISON_PENALTY` instead.
+    pub fn score(&self) -> u32 {
+        match self.kind {
+            FoodKind::Poison | FoodKind::Power(_) | FoodKind::Shield => 0,
+            FoodKind::Normal => APPLE_SCORE,
+            FoodKind::Bonus => 3 * APPLE_SCORE,
+            FoodKind::Mega => 10 * APPLE_SCORE,
+            FoodKind::Mouse => 4 * APPLE_SCORE,
+            FoodKind::Fruit => (FRUIT_SCORE as f64 * self.remaining()).ceil().max(1.) as u32,
+        }
+    }
+
+    /// The share of its lifetime it has left, from 1 down to 0. Food that
+    /// never expires always has all of it.
+    pub fn remaining(&self) -> f64 {
+        match self.kind.lifetime() {
+            Some(lifetime) => (1. - self.age / lifetime).clamp(0., 1.),
+            None => 1.,
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.kind
+            .lifetime()
+            .is_some_and(|lifetime| self.age >= lifetime)
+    }
+
+    /// Whether it shows right now. Over its last `BLINK_TIME` seconds it
+    /// blinks at a rate that climbs from 2 to about 6 blinks a second.
+    pub fn visible(&self) -> bool {
+        let Some(lifetime) = self.kind.lifetime() else {
+            return true;
+        };
+        let into = self.age - (lifetime - BLINK_TIME);
+        if into <= 0. {
+            return true;
+        }
+        let toggles = 4. * into + 1.5 * into * into;
+        (toggles as u64).is_multiple_of(2)
+    }
+}
+
+/// How much food is out at once and how likely each kind is. Read from the
+/// `[food]` table of the config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FoodRules {
+    /// Items on the board at once.
+    pub count: usize,
+    /// Relative spawn weights of each kind.
+    pub normal: u32,
+    pub bonus: u32,
+    pub mega: u32,
+    pub mouse: u32,
+    /// Poison is picked alongside the rest but comes on top of `count`, so
+    /// this sets how much of it is lying around.
+    pub poison: u32,
+    /// Power-ups are extra in the same way, each kind equally likely.
+    pub power: u32,
+    pub shield: u32,
+}
+
+impl Default for FoodRules {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            normal: 85,
+            bonus: 12,
+            mega: 3,
+            mouse: 5,
+            poison: 6,
+            power: 6,
+            shield: 2,
+        }
+    }
+}
+
+impl FoodRules {
+    /// Picks a kind by weight, falling back to normal food when every weight
+    /// is zero. Special fruit is never picked; it comes on its own timer.
+    pub fn pick(&self, rng: &mut Rng) -> FoodKind {
+        let weights = [
+            (FoodKind::Normal, self.normal),
+            (FoodKind::Bonus, self.bonus),
+            (FoodKind::Mega, self.mega),
+            (FoodKind::Mouse, self.mouse),
+            (FoodKind::Poison, self.poison),
+            (FoodKind::Power(PowerUp::random(rng)), self.power),
+            (FoodKind::Shield, self.shield),
+        ];
+        let total: u64 = weights.iter().map(|&(_, weight)| weight as u64).sum();
+        let mut roll = rng.below(total);
+        for (kind, weight) in weights {
+            if roll < weight as u64 {
+                return kind;
+            }
+            roll -= weight as u64;
+        }
+        FoodKind::Normal
+    }
+}
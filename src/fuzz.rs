@@ -0,0 +1,361 @@
+use crate::{
+    Vec2,
+    events::GameEvent,
+    food::{
+        FoodRules,
+        POISON_PENALTY,
+        POISON_SHRINK,
+    },
+    game::{
+        CORNER_GRACE,
+        Game,
+        Snake,
+    },
+    movement::MovementRules,
+    power::PowerUp,
+    replay::Input,
+    rng::Rng,
+    rules::Ruleset,
+};
+
+/// Seconds each frame advances the game by.
+const DT: f64 = 1. / 30.;
+
+/// The four cells next to any cell, as steps from it.
+const STEPS: [Vec2; 4] = [
+    Vec2 { x: 1., y: 0. },
+    Vec2 { x: -1., y: 0. },
+    Vec2 { x: 0., y: 1. },
+    Vec2 { x: 0., y: -1. },
+];
+
+/// An invariant that stopped holding: the run it broke in, the frame it
+/// broke on, and what was wrong.
+#[derive(Debug)]
+pub struct Failure {
+    pub seed: u64,
+    pub frame: usize,
+    pub what: String,
+}
+
+/// Plays a run for every seed in `seeds`, each on a randomly sized arena
+/// under a random mix of rules, with random input fed in for up to `frames`
+/// frames, checking after every frame that the game is still in a state it
+/// should be able to get into:
+///
+/// - each segment of a snake is next to the one before it, through the
+///   edges or a portal where those join cells up;
+/// - no two segments of a snake share a cell, unless it's passing through
+///   itself or was lately;
+/// - the snakes are growing to as long as everything that's happened to
+///   them says;
+/// - the score never goes down, short of rewinding or eating poison, and
+///   then by no more than poison costs;
+/// - no head ever leaves the arena while the run goes on.
+pub fn run(seeds: impl Iterator<Item = u64>, frames: usize) -> Result<u64, Failure> {
+    let mut runs = 0;
+    for seed in seeds {
+        Run::new(seed).play(frames)?;
+        runs += 1;
+    }
+    Ok(runs)
+}
+
+/// A run being fuzzed, and what's expected of it.
+struct Run {
+    seed: u64,
+    game: Game,
+    /// Picks the inputs, apart from the game's own randomness.
+    rng: Rng,
+    /// How long the snakes should be, between them.
+    length: usize,
+    score: u32,
+    ticks: u64,
+    /// For each snake, ticks left until any cells it passed through itself
+    /// on have unrolled off the tail.
+    overlapping: [usize; 2],
+    /// How long each snake was at the last check.
+    previous: [usize; 2],
+    /// Every pair of portals there's been, since the snake can still be
+    /// strung through one the walls closing in have taken away, or pass
+    /// over where one was.
+    portals: Vec<(Vec2, Vec2)>,
+}
+
+impl Run {
+    fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let width = 6 + rng.below(30) as u16;
+        let height = 6 + rng.below(16) as u16;
+        let mut game = Game::new(width, height, seed, FoodRules::default());
+        game.countdown = None;
+        // Lives to spare, so most crashes are a respawn to check rather than
+        // the end of the run.
+        game.lives = 3 + rng.below(6) as u32;
+        game.rules = Ruleset {
+            wraps: rng.below(3) == 0,
+            poison: rng.below(2) == 0,
+            closing: (rng.below(4) == 0).then(|| 1. + rng.below(5) as f64),
+            timer: rng.below(4) == 0,
+            ..Ruleset::CLASSIC
+        };
+        game.add_portals(rng.below(3) as usize);
+        if rng.below(2) == 0 {
+            game.movement = MovementRules::Ice;
+        }
+        game.corner_grace = [0., CORNER_GRACE, 0.2][rng.below(3) as usize];
+        game.slow_motion = rng.below(2) == 0;
+        match rng.below(6) {
+            0 => game.start_trail(),
+            1 => game.start_zen(),
+            _ => {}
+        }
+        match rng.below(6) {
+            0 => game.start_coop(),
+            1 => game.start_versus(1 + rng.below(3) as u32),
+            _ => {}
+        }
+        Self {
+            seed,
+            length: grown(&game),
+            score: game.stats.score,
+            ticks: game.ticks,
+            overlapping: [0; 2],
+            previous: [0; 2],
+            portals: game.portals.clone(),
+            game,
+            rng,
+        }
+    }
+
+    /// Plays up to `frames` frames, or until the run ends.
+    fn play(&mut self, frames: usize) -> Result<(), Failure> {
+        for frame in 0..frames {
+            if let Some(input) = self.input() {
+                match input {
+                    Input::Shrink if self.game.player.grown_len() > 1 => self.length -= 1,
+                    Input::Lengthen => self.length += 1,
+                    _ => {}
+                }
+                input.apply(&mut self.game);
+            }
+            let protected = self.game.invulnerable > 0. || self.game.shield;
+            self.game.update(DT);
+            let events = self.game.drain_events();
+            if self.game.is_over() {
+                return Ok(());
+            }
+            self.check(&events, protected).map_err(|what| Failure {
+                seed: self.seed,
+                frame,
+                what,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Something random for the player to do this frame, mostly nothing.
+    /// The player's snake is kept from turning into anything, and turned
+    /// away from what's in front of it where it can be, so runs last long
+    /// enough to get somewhere.
+    fn input(&mut self) -> Option<Input> {
+        let game = &self.game;
+        let sides = [90_f64.to_radians(), -90_f64.to_radians()];
+        if !game.player.turning() && game.danger_ahead().is_some() {
+            let first = self.rng.below(2) as usize;
+            let safe = [sides[first], sides[1 - first]]
+                .into_iter()
+                .find(|&angle| game.danger_turning(angle).is_none());
+            if let Some(angle) = safe {
+                return Some(Input::Steer(angle));
+            }
+        }
+        let cell = Vec2::new(
+            self.rng.below(self.game.width as u64) as f64,
+            self.rng.below(self.game.height as u64) as f64,
+        );
+        let input = match self.rng.below(100) {
+            0..=9 => Input::Steer(90_f64.to_radians()),
+            10..=19 => Input::Steer(-90_f64.to_radians()),
+            33..=37 => Input::SteerPartner(90_f64.to_radians()),
+            38..=42 => Input::SteerPartner(-90_f64.to_radians()),
+            20..=22 => Input::Shrink,
+            23..=25 => Input::Lengthen,
+            26 => Input::Pause,
+            27 => Input::Rewind,
+            // Much faster and the snake makes more than a move a frame,
+            // faster than it can be steered clear of things.
+            28 => Input::Speed(0.5 + self.rng.below(3) as f64 / 2.),
+            29..=30 => Input::PlaceFood(cell),
+            31 => Input::PlaceObstacle(cell),
+            32 => Input::ClearCell(cell),
+            _ => return None,
+        };
+        match input {
+            Input::Steer(angle)
+                if self.game.player.turning() || self.game.danger_turning(angle).is_some() =>
+            {
+                None
+            }
+            input => Some(input),
+        }
+    }
+
+    /// Checks the game against every invariant, after a frame that raised
+    /// `events`. `protected` is whether a crash could have been taken back
+    /// during it.
+    fn check(&mut self, events: &[GameEvent], protected: bool) -> Result<(), String> {
+        let game = &self.game;
+        let moved = game.ticks.saturating_sub(self.ticks) as usize;
+        self.ticks = game.ticks;
+        let mut resync = protected;
+        let mut penalties = 0;
+        for event in events {
+            match event {
+                GameEvent::FoodEaten { kind, .. } => self.length += kind.growth() as usize,
+                GameEvent::Poisoned => {
+                    self.length = self.length.saturating_sub(POISON_SHRINK);
+                    penalties += POISON_PENALTY;
+                }
+                GameEvent::LifeLost(_)
+                | GameEvent::Rewound
+                | GameEvent::ShieldUsed
+                | GameEvent::RoundOver { .. } => {
+                    resync = true;
+                }
+                _ => {}
+            }
+        }
+        if game.rules.trail {
+            self.length += moved * snakes(game).len();
+        }
+        let rewound = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::Rewound));
+        if rewound {
+            self.score = game.stats.score;
+        }
+
+        for &pair in &game.portals {
+            if !self.portals.contains(&pair) {
+                self.portals.push(pair);
+            }
+        }
+        let grown = grown(game);
+        // Rewinding can go back to when a snake was passing through itself.
+        let through = game.rules.pass_through
+            || game.powers.has(PowerUp::Ghost)
+            || game.invulnerable > 0.
+            || rewound;
+
+        for snake in snakes(game) {
+            if !game.inside(snake.head) {
+                return Err(format!(
+                    "head escaped the arena to {}, {}",
+                    snake.head.x, snake.head.y
+                ));
+            }
+        }
+        if game.stats.score < self.score.saturating_sub(penalties) {
+            return Err(format!(
+                "score went down from {} to {}",
+                self.score, game.stats.score
+            ));
+        }
+        self.score = game.stats.score;
+        if resync {
+            self.length = grown;
+        } else if grown != self.length {
+            return Err(format!(
+                "snakes are growing to {grown} long but should be {}",
+                self.length
+            ));
+        }
+        for (index, snake) in snakes(game).into_iter().enumerate() {
+            let segments: Vec<Vec2> = snake.segments().collect();
+            // Every segment added on at the tail holds it back from
+            // unrolling a tick longer.
+            let added = segments.len().saturating_sub(self.previous[index]);
+            self.previous[index] = segments.len();
+            self.overlapping[index] = if through {
+                segments.len()
+            } else if self.overlapping[index] > 0 {
+                (self.overlapping[index] + added).saturating_sub(moved)
+            } else {
+                0
+            };
+            self.check_snake(&segments, self.overlapping[index] > 0)?;
+        }
+        Ok(())
+    }
+
+    /// Checks one snake's `segments` are joined up, and that none share a
+    /// cell unless it's `overlapping` itself.
+    fn check_snake(&self, segments: &[Vec2], overlapping: bool) -> Result<(), String> {
+        let game = &self.game;
+        for (index, pair) in segments.windows(2).enumerate() {
+            let (ahead, behind) = (pair[0], pair[1]);
+            let joined = STEPS.into_iter().any(|step| {
+                let next = behind + step;
+                let twin = self
+                    .portals
+                    .iter()
+                    .find_map(|&(a, b)| (a == next).then_some(b).or((b == next).then_some(a)));
+                std::iter::once(next).chain(twin).any(|mut next| {
+                    if game.rules.wraps {
+                        next = game.wrap(next);
+                    }
+                    next == ahead
+                })
+            });
+            if !joined {
+                return Err(format!(
+                    "segments {index} and {} aren't joined: {}, {} and {}, {}",
+                    index + 1,
+                    ahead.x,
+                    ahead.y,
+                    behind.x,
+                    behind.y
+                ));
+            }
+        }
+        if !overlapping {
+            for (index, &segment) in segments.iter().enumerate() {
+                if segments[index + 1..].contains(&segment) {
+                    return Err(format!(
+                        "segment {index} shares {}, {} with another",
+                        segment.x, segment.y
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The snakes in `game`: the player's, and the partner's in co-op.
+fn snakes(game: &Game) -> Vec<&Snake> {
+    std::iter::once(&game.player)
+        .chain(game.partner.as_ref())
+        .collect()
+}
+
+/// How long the snakes in `game` are growing to, between them.
+fn grown(game: &Game) -> usize {
+    snakes(game).iter().map(|snake| snake.grown_len()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invariants_hold() {
+        if let Err(failure) = run(0..100, 3000) {
+            panic!(
+                "seed {} frame {}: {}",
+                failure.seed, failure.frame, failure.what
+            );
+        }
+    }
+}
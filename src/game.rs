@@ -0,0 +1,2597 @@
+use std::collections::{
+    LinkedList,
+    VecDeque,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Direction,
+    Vec2,
+    achievements::{
+        Achievement,
+        Achievements,
+    },
+    camera::{
+        Camera,
+        CameraMode,
+    },
+    difficulty::Difficulty,
+    effects::{
+        EffectKind,
+        Effects,
+    },
+    entity::{
+        Entity,
+        Obstacle,
+        Registry,
+    },
+    events::{
+        EventLog,
+        GameEvent,
+    },
+    food::{
+        FRUIT_INTERVAL,
+        Food,
+        FoodKind,
+        FoodRules,
+        POISON_PENALTY,
+        POISON_SHRINK,
+    },
+    level::Level,
+    minimap,
+    mission::{
+        Mission,
+        MissionEvent,
+    },
+    movement::MovementRules,
+    players::{
+        Player,
+        default_players,
+    },
+    power::{
+        PowerUp,
+        PowerUps,
+    },
+    practice,
+    render::{
+        Frame,
+        Renderer,
+    },
+    rng::Rng,
+    rules::Ruleset,
+    spatial::SpatialHash,
+    speedrun::{
+        self,
+        Speedrun,
+    },
+    theme::Color,
+    toast::ToastKind,
+    tutorial::{
+        Lesson,
+        Tutorial,
+    },
+    versus::Match,
+};
+
+/// Rows at the top of the screen kept for the score line.
+pub const HUD_ROWS: u16 = 1;
+
+/// How many cells the snake moves every second, unless the run is set to a
+/// speed of its own.
+pub const TICKS_PER_SECOND: f64 = 10.;
+
+/// Seconds after the head moves into a cell that a turn still counts as made
+/// in the cell before, so a corner taken a touch late at speed isn't missed.
+pub const CORNER_GRACE: f64 = 0.06;
+
+/// Cells per second the snake has to be going at for passing close to a
+/// crash to slow time down, with slow motion on.
+const NEAR_MISS_SPEED: f64 = 15.;
+
+/// Moves a near miss slows time down for, and how far.
+const SLOW_MOTION_TICKS: u32 = 4;
+const SLOW_MOTION_SCALE: f64 = 0.5;
+
+/// Seconds the game takes to get back up to full speed after being unpaused
+/// without a countdown, and how slow it starts off.
+const RESUME_EASE: f64 = 0.5;
+const RESUME_SCALE: f64 = 0.25;
+
+/// Cells per second the snake stops speeding up at, with a speed ramp on.
+const RAMP_LIMIT: f64 = 25.;
+
+/// How much each notch of adaptive difficulty speeds the snake up and adds
+/// to the poison.
+const ADAPT_SPEED: f64 = 1.06;
+const ADAPT_POISON: f64 = 1.25;
+
+/// Lives in the tutorial, enough that crashing only sets a lesson back.
+const TUTORIAL_LIVES: u32 = 99;
+
+/// How long the snake is grown to for the lesson on avoiding its tail.
+const TAIL_LESSON_LENGTH: usize = 12;
+
+/// Cells ahead of the snake the apple for the eating lesson goes.
+const LESSON_APPLE_DISTANCE: f64 = 4.;
+
+/// Seconds spent on each of 3, 2 and 1 before a run starts.
+const COUNTDOWN_STEP: f64 = 1.;
+
+/// Seconds "GO!" stays up once the snake is already moving.
+const COUNTDOWN_GO: f64 = 0.5;
+
+/// Seconds the snake stands still after its shield takes a crash, to give
+/// the player a chance to steer clear.
+const SHIELD_PAUSE: f64 = 0.5;
+
+/// Seconds a respawned snake can't crash for.
+const INVULNERABLE_TIME: f64 = 2.;
+
+/// Free cells a respawned snake wants straight ahead of it.
+const RESPAWN_CLEARANCE: usize = 5;
+
+/// Seconds the snake has after eating to eat again and keep a combo going.
+const COMBO_WINDOW: f64 = 3.;
+
+/// Highest combo multiplier.
+const MAX_COMBO: u32 = 8;
+
+/// Moves between each pull of a magnet.
+const MAGNET_EVERY: u64 = 3;
+
+/// Moves between each step a mouse takes.
+const MOUSE_EVERY: u64 = 2;
+
+/// How close, in cells, the head has to get before a mouse runs.
+const MOUSE_SIGHT: f64 = 6.;
+
+/// Closing walls stop once the live area is this many cells across.
+const MIN_LIVE_SIZE: u16 = 5;
+
+/// Cells the fog's clear radius grows by for each thing eaten.
+const FOG_GROWTH: f64 = 0.25;
+
+/// Seconds on the clock at the start of a time-attack run.
+pub const TIME_ATTACK: f64 = 120.;
+
+/// Seconds a bonus fruit adds to the time-attack clock.
+const BONUS_TIME: f64 = 5.;
+
+/// The time-attack clock turns urgent with this many seconds left.
+const CLOCK_WARNING: f64 = 10.;
+
+/// Times a second an invulnerable snake blinks on or off.
+const BLINK_RATE: f64 = 8.;
+
+/// Width of the HUD bars, such as the one showing how long a special fruit
+/// has left.
+const HUD_BAR: usize = 10;
+
+/// Seconds of play kept to rewind through.
+const REWIND_BUFFER: f64 = 10.;
+
+/// Seconds each rewind goes back.
+const REWIND_SECONDS: f64 = 2.;
+
+/// Rewinds each run gets.
+const REWIND_CHARGES: u32 = 3;
+
+/// Seconds of recent play kept for replaying a death.
+pub const HISTORY_SECONDS: f64 = 3.;
+
+/// Turns queued up beyond this many are dropped, so mashing keys can't build
+/// up a backlog of moves the player has long forgotten about.
+const MAX_QUEUED_TURNS: usize = 3;
+
+/// A snake living on the arena grid. Positions are whole cell coordinates.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Snake {
+    pub head: Vec2,
+    pub body: LinkedList<Vec2>,
+    pub forward: Vec2,
+    /// Turns thrown away because the queue was already full.
+    pub dropped_turns: u64,
+    /// Moves left to carry straight on for before turning is possible again.
+    pub sliding: u32,
+    /// Segments still to be added, one at the tail on each of the next moves.
+    #[serde(default)]
+    pub growing: u32,
+    /// Queued turns, each with the number of moves it has waited so far.
+    turns: VecDeque<(f64, u32)>,
+}
+
+impl Snake {
+    pub fn new(head: Vec2) -> Self {
+        let forward = Vec2::new(1., 0.);
+        let body = LinkedList::new();
+        let turns = VecDeque::new();
+        Snake {
+            head,
+            forward,
+            body,
+            dropped_turns: 0,
+            sliding: 0,
+            growing: 0,
+            turns,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.body.len() + 1
+    }
+
+    /// How long the snake will be once the segments it's still growing are
+    /// on.
+    pub fn grown_len(&self) -> usize {
+        self.len() + self.growing as usize
+    }
+
+    /// Takes a segment off, one still to grow if there is one, otherwise
+    /// the tail. The head is never taken.
+    pub fn shrink(&mut self) {
+        if self.growing > 0 {
+            self.growing -= 1;
+        } else {
+            self.body.pop_back();
+        }
+    }
+
+    /// Advances one cell, first taking the oldest queued turn if `rules` let
+    /// it. The tail follows, unless a segment is due to grow onto it.
+    pub fn r#move(&mut self, rules: MovementRules) {
+        self.grow(rules);
+        if self.growing > 0 {
+            self.growing -= 1;
+        } else {
+            self.body.pop_back();
+        }
+    }
+
+    /// Like `move`, but the tail stays where it is, so the snake ends up a
+    /// cell longer.
+    pub fn grow(&mut self, rules: MovementRules) {
+        if let Some(angle) = self.next_turn(rules) {
+            self.forward.rotate(angle);
+            self.forward = if rules.on_grid() {
+                self.forward.round()
+            } else {
+                self.forward.normalized()
+            };
+        }
+        self.body.push_front(self.head);
+        self.head += self.forward;
+    }
+
+    /// Takes the oldest queued turn if it has waited long enough and the
+    /// snake isn't sliding, and counts a move against everything else.
+    fn next_turn(&mut self, rules: MovementRules) -> Option<f64> {
+        let ready = self.sliding == 0
+            && self
+                .turns
+                .front()
+                .is_some_and(|&(_, waited)| waited >= rules.turn_delay());
+        let turn = if ready { self.turns.pop_front() } else { None };
+        self.sliding = self.sliding.saturating_sub(1);
+        for (_, waited) in &mut self.turns {
+            *waited += 1;
+        }
+        turn.map(|(angle, _)| angle)
+    }
+
+    /// Makes the snake `count` cells longer over its next moves, the tail
+    /// staying put on each while the rest moves off it.
+    pub fn lengthen(&mut self, count: u32) {
+        self.growing += count;
+    }
+
+    /// Which way the snake will be going once it's made its queued turns.
+    pub fn heading(&self) -> Vec2 {
+        let mut forward = self.forward;
+        for &(angle, _) in &self.turns {
+            forward.rotate(angle);
+        }
+        forward
+    }
+
+    /// Whether there are turns still waiting to be made.
+    pub fn turning(&self) -> bool {
+        !self.turns.is_empty()
+    }
+
+    /// Forgets any turns still waiting to be made.
+    pub fn clear_turns(&mut self) {
+        self.turns.clear();
+    }
+
+    /// Queues a turn for the next move. Turning twice before moving makes two
+    /// separate moves rather than reversing into the neck.
+    pub fn rotate(&mut self, angle: f64) {
+        if self.turns.len() < MAX_QUEUED_TURNS {
+            self.turns.push_back((angle, 0));
+        } else {
+            self.dropped_turns += 1;
+        }
+    }
+
+    /// The snake's cells, head first.
+    pub fn segments(&self) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::once(self.head).chain(self.body.iter().copied())
+    }
+
+    /// Whether the head is within `reach` of any other segment.
+    pub fn bites_itself(&self, reach: f64) -> bool {
+        self.body
+            .iter()
+            .any(|&segment| segment.distance(self.head) < reach)
+    }
+}
+
+/// How the snake is laid out when a run starts. Anything unset is left to
+/// the level, if there is one, or the defaults.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Start {
+    /// Segments long, counting the head. One unless set.
+    pub length: Option<usize>,
+    /// The cell the head starts on. Unset, the tail starts a cell in from
+    /// the corner the snake heads away from.
+    pub spawn: Option<Vec2>,
+    /// The way the snake heads off. Right unless set.
+    pub direction: Option<Direction>,
+}
+
+/// What's needed to redraw the arena as it was on one tick.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    /// Snake cells, head first.
+    pub segments: Vec<Vec2>,
+    pub heading: Vec2,
+    pub food: Vec<Food>,
+    /// How far the snake has got towards its next cell, from 0 to 1. Below 1
+    /// the head is drawn only partly into its cell.
+    pub progress: f64,
+    /// The cell the tail is sliding out of, if it moved on the last tick.
+    pub vacated: Option<Vec2>,
+    /// The second snake's cells in co-op, head first, and its heading.
+    pub partner: Vec<Vec2>,
+    pub partner_heading: Vec2,
+    /// The colour each player picked for their snake, if any.
+    pub tints: [Option<Color>; 2],
+}
+
+impl Snapshot {
+    /// The same snapshot with everything moved by `offset`.
+    pub fn shifted(&self, offset: Vec2) -> Snapshot {
+        Snapshot {
+            segments: self.segments.iter().map(|&p| p + offset).collect(),
+            food: self
+                .food
+                .iter()
+                .map(|&food| Food {
+                    at: food.at + offset,
+                    ..food
+                })
+                .collect(),
+            vacated: self.vacated.map(|p| p + offset),
+            partner: self.partner.iter().map(|&p| p + offset).collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, arena: &mut Frame) {
+        for food in &self.food {
+            if food.visible() || renderer.reduced_motion {
+                renderer.draw_food(arena, food.at, food.kind);
+            }
+        }
+        if !self.partner.is_empty() {
+            let tint = player_tint(renderer, 1, self.tints[1]);
+            renderer.draw_snake_in(arena, self.partner_heading, &self.partner, tint);
+        }
+        let tint = player_tint(renderer, 0, self.tints[0]);
+        renderer.draw_snake_in(arena, self.heading, &self.segments, tint);
+        if self.progress < 1. {
+            renderer.draw_motion(arena, &self.segments, self.vacated, self.progress, tint);
+        }
+    }
+}
+
+/// The head and body colours player `index`'s snake is drawn in, given the
+/// colour they `chose`, if any: that colour, or for the second player the
+/// theme's shield and portal colours, to tell the snakes apart. `None` is
+/// the theme's own snake colours.
+pub fn player_tint(
+    renderer: &Renderer,
+    index: usize,
+    chose: Option<Color>,
+) -> Option<(Color, Color)> {
+    let colors = &renderer.theme.colors;
+    match chose {
+        Some(color) => Some((color, color)),
+        None if index == 1 => Some((colors.shield, colors.portal)),
+        None => None,
+    }
+}
+
+/// The parts of a game that change as it's played, copied every move so it
+/// can be rewound to.
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    player: Snake,
+    partner: Option<Snake>,
+    food: Vec<Food>,
+    /// Without the registry's buckets, which are rebuilt on rewinding
+    /// rather than copied every move.
+    entities: Vec<Box<dyn Entity>>,
+    fruit_timer: f64,
+    stats: RunStats,
+    powers: PowerUps,
+    shield: bool,
+    lives: u32,
+    invulnerable: f64,
+    starving: f64,
+    portals: Vec<(Vec2, Vec2)>,
+    inset: u16,
+    since_closing: f64,
+    clock: Option<f64>,
+    mission: Option<Mission>,
+    combo: Combo,
+    ticks: u64,
+    speed: f64,
+    set_to: Option<f64>,
+    food_rules: FoodRules,
+    adapted: i32,
+    speedrun: Option<Speedrun>,
+    rng: Rng,
+    pending: f64,
+    slowed: u32,
+    near_miss: bool,
+}
+
+/// The 3-2-1-GO shown before the snake starts moving.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Countdown {
+    elapsed: f64,
+}
+
+impl Countdown {
+    /// Whether the snake still has to wait.
+    pub fn holding(&self) -> bool {
+        self.elapsed < 3. * COUNTDOWN_STEP
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= 3. * COUNTDOWN_STEP + COUNTDOWN_GO
+    }
+
+    pub fn label(&self) -> &'static str {
+        match (self.elapsed / COUNTDOWN_STEP) as u32 {
+            0 => "3",
+            1 => "2",
+            2 => "1",
+            _ => "GO!",
+        }
+    }
+}
+
+/// A score multiplier built up by eating in quick succession.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Combo {
+    pub multiplier: u32,
+    /// Seconds left to eat again before the multiplier resets.
+    left: f64,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            multiplier: 1,
+            left: 0.,
+        }
+    }
+}
+
+impl Combo {
+    /// Counts a meal, returning the multiplier it scores at.
+    pub fn hit(&mut self) -> u32 {
+        self.multiplier = if self.left > 0. {
+            (self.multiplier + 1).min(MAX_COMBO)
+        } else {
+            1
+        };
+        self.left = COMBO_WINDOW;
+        self.multiplier
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.left = (self.left - dt).max(0.);
+        if self.left == 0. {
+            self.multiplier = 1;
+        }
+    }
+
+    /// The share of the window left, from 1 down to 0.
+    pub fn remaining(&self) -> f64 {
+        self.left / COMBO_WINDOW
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeathCause {
+    Wall,
+    Tail,
+    Poison,
+    Starved,
+    Crushed,
+    /// Ran into something solid in the entity registry.
+    Obstacle,
+    /// The time-attack clock ran out.
+    OutOfTime,
+    /// Ran into the other snake, in versus.
+    Rival,
+}
+
+impl DeathCause {
+    /// Whether the snake ran into something, rather than dying some other
+    /// way.
+    pub fn is_crash(self) -> bool {
+        matches!(
+            self,
+            DeathCause::Wall | DeathCause::Tail | DeathCause::Obstacle | DeathCause::Rival
+        )
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            DeathCause::Wall => "hit the wall",
+            DeathCause::Tail => "bit its own tail",
+            DeathCause::Poison => "ate poison",
+            DeathCause::Starved => "starved",
+            DeathCause::Crushed => "was crushed by the walls",
+            DeathCause::Obstacle => "hit an obstacle",
+            DeathCause::OutOfTime => "ran out of time",
+            DeathCause::Rival => "ran into the other snake",
+        }
+    }
+}
+
+/// Everything worth reporting about a run once it's over.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RunStats {
+    pub score: u32,
+    pub apples: u32,
+    /// Seconds of play.
+    pub time: f64,
+    /// Fastest speed reached, in cells per second.
+    pub top_speed: f64,
+    /// Highest combo multiplier reached.
+    pub best_combo: u32,
+    /// Times the snake ran into a wall, whether or not it survived.
+    pub wall_hits: u32,
+    pub death: Option<DeathCause>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Game {
+    /// Arena size in cells.
+    pub width: u16,
+    pub height: u16,
+    pub player: Snake,
+    /// The second player's snake, in co-op. It shares the score and lives,
+    /// and the two pass through each other.
+    #[serde(default)]
+    pub partner: Option<Snake>,
+    /// The match, when the two snakes play against each other instead.
+    #[serde(default)]
+    pub versus: Option<Match>,
+    /// Who's playing, when there are two snakes.
+    #[serde(default = "default_players")]
+    pub players: [Player; 2],
+    pub food: Vec<Food>,
+    /// Everything else on the board.
+    #[serde(default)]
+    pub entities: Registry,
+    pub food_rules: FoodRules,
+    /// Seconds of play until the next special fruit.
+    pub fruit_timer: f64,
+    #[serde(with = "crate::rng::hex")]
+    pub seed: u64,
+    pub stats: RunStats,
+    pub paused: bool,
+    pub countdown: Option<Countdown>,
+    /// Whether unpausing counts down again before the snake moves.
+    pub resume_countdown: bool,
+    /// Whether to draw the snake part way between cells.
+    pub smooth: bool,
+    /// The last `HISTORY_SECONDS` of ticks, oldest first.
+    #[serde(skip)]
+    pub history: VecDeque<Snapshot>,
+    #[serde(skip)]
+    pub effects: Effects,
+    pub powers: PowerUps,
+    /// Whether a shield is held to take the next crash.
+    pub shield: bool,
+    /// Lives left, counting the one being played. The run ends when the last
+    /// one is lost.
+    pub lives: u32,
+    /// Seconds left in which crashes do no harm, after a respawn.
+    pub invulnerable: f64,
+    /// Crashes do no harm at all, from the console.
+    #[serde(default)]
+    pub god: bool,
+    /// The snake passes through walls, itself and anything else solid, from
+    /// the console.
+    #[serde(default)]
+    pub noclip: bool,
+    /// Whether the console's been used on the run, which keeps it from
+    /// counting.
+    #[serde(default)]
+    pub cheated: bool,
+    /// Which rules the run is played by.
+    #[serde(flatten)]
+    pub rules: Ruleset,
+    /// Seconds since the snake last ate or lost a segment to hunger.
+    starving: f64,
+    /// Linked cells: a head moving onto either end carries on from the other.
+    pub portals: Vec<(Vec2, Vec2)>,
+    /// Cells the walls have closed in from every side.
+    pub inset: u16,
+    /// Seconds since the walls last closed in.
+    since_closing: f64,
+    /// With fog on, how many cells around the head can be seen at the start.
+    pub fog: Option<f64>,
+    /// How the snake takes turns.
+    pub movement: MovementRules,
+    /// What difficulty the run is played at.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// Cells per second the snake speeds up by for each apple it eats.
+    #[serde(default)]
+    pub ramp: f64,
+    /// Cells per second the snake moves at on normal difficulty, before any
+    /// ramp or power-ups. Counted in arena cells, so it's the same on any
+    /// size of terminal.
+    #[serde(default = "usual_speed")]
+    pub base_speed: f64,
+    /// Seconds after a move that a turn is still taken as made before it.
+    /// Replays from before there was any play back without.
+    #[serde(default)]
+    pub corner_grace: f64,
+    /// Slow time down for a few moves when the snake passes close by a wall
+    /// or itself at speed.
+    #[serde(default)]
+    pub slow_motion: bool,
+    /// Moves left to make in slow motion.
+    #[serde(default)]
+    slowed: u32,
+    /// How much faster or slower than real time the game runs, before any
+    /// slow motion.
+    #[serde(default = "unscaled")]
+    time_scale: f64,
+    /// Seconds left of easing back up to speed after being unpaused.
+    #[serde(default)]
+    easing: f64,
+    /// Whether the head was next to something it could crash into after the
+    /// last move, so passing alongside it only counts once.
+    #[serde(default)]
+    near_miss: bool,
+    /// Notches harder adaptive difficulty has made the run, negative for
+    /// easier.
+    #[serde(default)]
+    pub adapted: i32,
+    /// Seconds left on the time-attack clock, in a time-attack run.
+    pub clock: Option<f64>,
+    /// The run's mission, if it has one.
+    pub mission: Option<Mission>,
+    /// Where the player's got to, if the run is the tutorial.
+    #[serde(default)]
+    pub tutorial: Option<Tutorial>,
+    /// Walls inside the arena, from a custom level.
+    #[serde(default)]
+    pub walls: Vec<Vec2>,
+    /// Cells food turns up on, from a custom level. Anywhere free if empty.
+    #[serde(default)]
+    pub spawners: Vec<Vec2>,
+    /// Practice mode: the board can be edited, and the run isn't ranked.
+    #[serde(default)]
+    pub practice: bool,
+    /// The splits so far, if the run is a speedrun.
+    #[serde(default)]
+    pub speedrun: Option<Speedrun>,
+    /// The code to play the run again with, if it can be.
+    #[serde(default)]
+    pub challenge: Option<String>,
+    /// Where the practice editor's cursor is, while the board's being
+    /// edited.
+    #[serde(skip)]
+    pub cursor: Option<Vec2>,
+    /// Loaded afresh rather than saved with the game, since they're shared
+    /// between runs.
+    #[serde(skip)]
+    pub achievements: Achievements,
+    /// Notices for the toasts since they were last drained, oldest first.
+    #[serde(skip)]
+    notices: Vec<(ToastKind, String)>,
+    /// The high score to beat, until it's been beaten.
+    #[serde(skip)]
+    pub high_score: Option<u32>,
+    /// What's happened so far, for the event panel.
+    #[serde(skip)]
+    pub log: EventLog,
+    /// Events since they were last drained, oldest first.
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    /// The save slot the run saves to and quick-loads from.
+    #[serde(skip)]
+    pub slot: usize,
+    /// Rewinds left this run.
+    pub rewinds: u32,
+    /// The state before each of the last `REWIND_BUFFER` seconds of moves,
+    /// oldest first.
+    #[serde(skip)]
+    checkpoints: VecDeque<Checkpoint>,
+    pub combo: Combo,
+    /// Which part of the arena is on screen when it doesn't all fit.
+    pub camera: Camera,
+    /// Moves made so far.
+    pub ticks: u64,
+    /// Cells per second.
+    speed: f64,
+    /// Cells per second the snake was last set to, which the ramp and
+    /// adaptive difficulty have been building on since. Saves from before
+    /// it was kept don't have it.
+    #[serde(default)]
+    set_to: Option<f64>,
+    rng: Rng,
+    /// Time banked towards the next move.
+    pending: f64,
+    /// How long the snake was growing to after the last move, if it went
+    /// straight on, nothing came of it and nothing's been changed by hand
+    /// since, so a turn just too late for it can take it back.
+    #[serde(default)]
+    quiet: Option<usize>,
+}
+
+fn usual_speed() -> f64 {
+    TICKS_PER_SECOND
+}
+
+fn unscaled() -> f64 {
+    1.
+}
+
+impl Game {
+    pub fn new(width: u16, height: u16, seed: u64, food_rules: FoodRules) -> Self {
+        let player = Snake::new(Vec2::new(1., 1.));
+        let mut game = Self {
+            width,
+            height,
+            player,
+            partner: None,
+            versus: None,
+            players: default_players(),
+            food: Vec::new(),
+            entities: Registry::default(),
+            food_rules,
+            fruit_timer: FRUIT_INTERVAL,
+            seed,
+            stats: RunStats::default(),
+            paused: false,
+            countdown: Some(Countdown::default()),
+            resume_countdown: true,
+            smooth: false,
+            history: VecDeque::new(),
+            effects: Effects::default(),
+            powers: PowerUps::default(),
+            shield: false,
+            lives: 1,
+            invulnerable: 0.,
+            god: false,
+            noclip: false,
+            cheated: false,
+            rules: Ruleset::default(),
+            starving: 0.,
+            portals: Vec::new(),
+            inset: 0,
+            since_closing: 0.,
+            fog: None,
+            movement: MovementRules::default(),
+            difficulty: Difficulty::default(),
+            ramp: 0.,
+            base_speed: TICKS_PER_SECOND,
+            corner_grace: CORNER_GRACE,
+            slow_motion: false,
+            slowed: 0,
+            time_scale: 1.,
+            easing: 0.,
+            near_miss: false,
+            adapted: 0,
+            clock: None,
+            mission: None,
+            tutorial: None,
+            speedrun: None,
+            challenge: None,
+            walls: Vec::new(),
+            spawners: Vec::new(),
+            practice: false,
+            cursor: None,
+            achievements: Achievements::default(),
+            notices: Vec::new(),
+            high_score: None,
+            log: EventLog::default(),
+            events: Vec::new(),
+            slot: 0,
+            rewinds: REWIND_CHARGES,
+            checkpoints: VecDeque::new(),
+            combo: Combo::default(),
+            camera: Camera::new(CameraMode::DeadZone),
+            ticks: 0,
+            speed: TICKS_PER_SECOND,
+            set_to: Some(TICKS_PER_SECOND),
+            rng: Rng::new(seed),
+            pending: 0.,
+            quiet: None,
+        };
+        game.restock();
+        game
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.stats.death.is_some()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.quiet = None;
+        if !self.paused && self.resume_countdown {
+            self.countdown = Some(Countdown::default());
+        } else if !self.paused {
+            self.easing = RESUME_EASE;
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.is_over() || self.paused {
+            return;
+        }
+        self.effects.update(dt);
+        if let Some(versus) = &mut self.versus
+            && versus.intermission > 0.
+        {
+            versus.intermission -= dt;
+            if versus.intermission <= 0. {
+                self.countdown = Some(Countdown::default());
+            }
+            return;
+        }
+        if let Some(countdown) = &mut self.countdown {
+            countdown.elapsed += dt;
+            if countdown.finished() {
+                self.countdown = None;
+            } else if countdown.holding() {
+                return;
+            }
+        }
+        let scaled = dt * self.time_scale();
+        self.easing = (self.easing - dt).max(0.);
+        let dt = scaled;
+        self.stats.time += dt;
+        if let Some(clock) = &mut self.clock {
+            *clock = (*clock - dt).max(0.);
+            if *clock == 0. {
+                self.stats.death = Some(DeathCause::OutOfTime);
+                self.emit(GameEvent::Died {
+                    cause: DeathCause::OutOfTime,
+                });
+                return;
+            }
+        }
+        for power in self.powers.update(dt) {
+            self.emit(GameEvent::PowerUpEnded(power));
+        }
+        self.invulnerable = (self.invulnerable - dt).max(0.);
+        self.starve(dt);
+        self.close_walls(dt);
+        if self.settle_round() {
+            return;
+        }
+        self.combo.update(dt);
+        for food in &mut self.food {
+            food.update(dt);
+        }
+        self.food.retain(|food| !food.expired());
+        self.entities.update(dt);
+        self.restock();
+        self.fruit_timer -= dt;
+        if self.fruit_timer <= 0. && !self.rules.trail && !self.tutoring() {
+            self.fruit_timer += FRUIT_INTERVAL;
+            if let Some(at) = self.food_cell() {
+                self.food.push(Food::new(FoodKind::Fruit, at));
+            }
+        }
+        self.stats.top_speed = self.stats.top_speed.max(self.speed());
+        self.pending += dt;
+        let step = 1. / self.speed();
+        while self.pending >= step && !self.is_over() {
+            self.pending -= step;
+            self.step();
+        }
+        if let Some(cause) = self.stats.death {
+            self.emit(GameEvent::Died { cause });
+        }
+    }
+
+    /// Moves the game on by one move of the snake, however long that takes
+    /// at its speed.
+    pub fn step(&mut self) {
+        let capacity = (REWIND_BUFFER * self.speed).ceil() as usize;
+        while self.checkpoints.len() >= capacity.max(1) {
+            self.checkpoints.pop_front();
+        }
+        self.checkpoints.push_back(self.checkpoint());
+        self.ticks += 1;
+        self.slowed = self.slowed.saturating_sub(1);
+        let before = self.player.clone();
+        let partner_before = self.partner.clone();
+        let (events, heading) = (self.events.len(), self.player.forward);
+        let (trail, movement) = (self.rules.trail, self.movement);
+        for partner in self.seats() {
+            let snake = self.snake_mut(partner);
+            if trail {
+                snake.grow(movement);
+            } else {
+                snake.r#move(movement);
+            }
+            let head = self.arrive(self.snake(partner).head);
+            self.snake_mut(partner).head = head;
+        }
+        if self.rules.trail {
+            self.stats.score += 1;
+        }
+        self.update_actors();
+        self.feed(false);
+        self.crash(false, before);
+        if let Some(before) = partner_before {
+            self.feed(true);
+            self.crash(true, before);
+        }
+        self.settle_round();
+        if !self.is_over() {
+            self.observe(MissionEvent::Moved {
+                time: self.stats.time,
+                length: self.player.len(),
+                combo: self.combo.multiplier,
+            });
+            self.check_achievements();
+            self.check_near_miss();
+        }
+        self.quiet =
+            (self.events.len() == events && !self.is_over() && self.player.forward == heading)
+                .then(|| self.player.grown_len());
+        self.record();
+    }
+
+    /// The `partner` flag picking out each snake in play: false for the
+    /// first player's, then true for the second's in co-op.
+    fn seats(&self) -> Vec<bool> {
+        if self.partner.is_some() {
+            vec![false, true]
+        } else {
+            vec![false]
+        }
+    }
+
+    /// The first player's snake, or with `partner` the second's, if there
+    /// is one.
+    pub fn snake(&self, partner: bool) -> &Snake {
+        match &self.partner {
+            Some(snake) if partner => snake,
+            _ => &self.player,
+        }
+    }
+
+    fn snake_mut(&mut self, partner: bool) -> &mut Snake {
+        match &mut self.partner {
+            Some(snake) if partner => snake,
+            _ => &mut self.player,
+        }
+    }
+
+    /// Where a head that's just moved onto `head` ends up: through any
+    /// portal there, and round the edges where they wrap.
+    fn arrive(&mut self, mut head: Vec2) -> Vec2 {
+        if let Some(twin) = self.portal_twin(head) {
+            head = twin;
+            self.emit(GameEvent::Portal);
+        }
+        if self.rules.wraps || self.noclip {
+            head = self.wrap(head);
+        }
+        head
+    }
+
+    /// Feeds a snake whatever food its head has just reached.
+    fn feed(&mut self, partner: bool) {
+        let head = self.snake(partner).head;
+        if let Some(index) = self
+            .food
+            .iter()
+            .position(|food| self.touching(food.at, head))
+        {
+            let food = self.food.swap_remove(index);
+            self.eat(food, partner);
+        }
+    }
+
+    /// Checks whether a snake has crashed on its last move, which from
+    /// `before` it, and ends the run or takes a life if it has, or if it's
+    /// died some other way. Anything protecting it puts it back instead.
+    fn crash(&mut self, partner: bool, before: Snake) {
+        if !self.is_over() {
+            self.stats.death = self.collision(partner);
+            if self.stats.death == Some(DeathCause::Wall) {
+                self.stats.wall_hits += 1;
+            }
+        }
+        let protected = self.god || self.invulnerable > 0. || self.shield;
+        if self.stats.death.is_some_and(DeathCause::is_crash) && protected {
+            if !self.god && self.invulnerable <= 0. {
+                self.shield = false;
+                self.emit(GameEvent::ShieldUsed);
+            }
+            self.stats.death = None;
+            let snake = self.snake_mut(partner);
+            *snake = before;
+            snake.clear_turns();
+            let head = snake.head;
+            self.pending -= SHIELD_PAUSE;
+            self.effects.spawn(EffectKind::Flash, head);
+        } else if self.is_over() {
+            self.fall(partner);
+        }
+    }
+
+    /// Deals with a snake having died: in versus it's out of the round,
+    /// otherwise it costs a life if there's one to spare, and ends the run
+    /// if not.
+    fn fall(&mut self, partner: bool) {
+        if let Some(versus) = &mut self.versus
+            && let Some(cause) = self.stats.death.take()
+        {
+            let player = usize::from(partner);
+            versus.fall(player, cause);
+            let name = self.players[player].name.clone();
+            self.emit(GameEvent::PlayerOut { name, cause });
+        } else if self.lives > 1 {
+            self.lose_life(partner);
+        }
+    }
+
+    /// Ends the round in versus if either snake is out of it, ending the
+    /// run too if that's won the match, or setting up the next round if
+    /// not. Returns whether a round ended.
+    fn settle_round(&mut self) -> bool {
+        let Some(versus) = &mut self.versus else {
+            return false;
+        };
+        let Some(cause) = versus.settle() else {
+            return false;
+        };
+        let winner = versus
+            .last
+            .flatten()
+            .map(|winner| self.players[winner].name.clone());
+        if versus.winner().is_some() {
+            self.stats.death = Some(cause);
+        } else {
+            versus.next_round();
+            let [player, partner] = versus.spawns();
+            self.player = player;
+            self.partner = Some(partner);
+            self.food.clear();
+            self.restock();
+            self.powers = PowerUps::default();
+            self.invulnerable = 0.;
+            self.starving = 0.;
+            self.inset = 0;
+            self.since_closing = 0.;
+            self.pending = 0.;
+            self.checkpoints.clear();
+            self.history.clear();
+            self.quiet = None;
+        }
+        self.emit(GameEvent::RoundOver { winner });
+        true
+    }
+
+    /// Under the hunger rule, takes a segment off the tail of every snake for
+    /// every interval gone without eating. Starving down to nothing costs a
+    /// life.
+    fn starve(&mut self, dt: f64) {
+        let Some(interval) = self.rules.hunger else {
+            return;
+        };
+        self.starving += dt;
+        while self.starving >= interval && !self.is_over() {
+            self.starving -= interval;
+            for partner in self.seats() {
+                if self.is_over() {
+                    break;
+                }
+                if self.snake(partner).grown_len() > 1 {
+                    self.snake_mut(partner).shrink();
+                } else {
+                    self.stats.death = Some(DeathCause::Starved);
+                    self.fall(partner);
+                }
+            }
+        }
+    }
+
+    /// Under closing walls, moves the walls in a cell every interval, until
+    /// the live area gets down to `MIN_LIVE_SIZE`. Food and portals left
+    /// outside are lost, and a snake caught by the walls is crushed.
+    fn close_walls(&mut self, dt: f64) {
+        let Some(interval) = self.rules.closing.filter(|_| self.walls_can_close()) else {
+            return;
+        };
+        self.since_closing += dt;
+        if self.since_closing < interval {
+            return;
+        }
+        self.since_closing -= interval;
+        self.inset += 1;
+        let (min, max) = (self.live_min(), self.live_max());
+        self.food.retain(|food| food.at.inside_rectange(min, max));
+        self.portals
+            .retain(|(a, b)| a.inside_rectange(min, max) && b.inside_rectange(min, max));
+        self.restock();
+        for partner in self.seats() {
+            let snake = self.snake(partner);
+            if self.noclip || self.is_over() || snake.segments().all(|cell| self.inside(cell)) {
+                continue;
+            }
+            self.stats.death = Some(DeathCause::Crushed);
+            self.fall(partner);
+        }
+    }
+
+    /// How far from the head can be seen through the fog, if there is any.
+    pub fn sight(&self) -> Option<f64> {
+        self.fog
+            .map(|radius| radius + FOG_GROWTH * self.stats.apples as f64)
+    }
+
+    /// Seconds until the walls next close in, if they're going to.
+    pub fn closing_in(&self) -> Option<f64> {
+        self.rules
+            .closing
+            .filter(|_| self.walls_can_close())
+            .map(|interval| interval - self.since_closing)
+    }
+
+    fn walls_can_close(&self) -> bool {
+        let smallest = self.width.min(self.height);
+        smallest.saturating_sub(2 * (self.inset + 1)) >= MIN_LIVE_SIZE
+    }
+
+    /// The top-left and bottom-right cells of the live area.
+    fn live_min(&self) -> Vec2 {
+        Vec2::new(self.inset as f64, self.inset as f64)
+    }
+
+    fn live_max(&self) -> Vec2 {
+        Vec2::new(
+            self.width as f64 - 1. - self.inset as f64,
+            self.height as f64 - 1. - self.inset as f64,
+        )
+    }
+
+    /// Lets everything on the board besides the snake take its turn.
+    fn update_actors(&mut self) {
+        if self.powers.has(PowerUp::Magnet) && self.ticks.is_multiple_of(MAGNET_EVERY) {
+            self.pull_food();
+        }
+        if self.ticks.is_multiple_of(MOUSE_EVERY) {
+            for index in 0..self.food.len() {
+                if self.food[index].kind == FoodKind::Mouse {
+                    self.flee(index);
+                }
+            }
+        }
+    }
+
+    /// Moves the mouse at `index` to the neighbouring cell furthest from the
+    /// head, if the head is close enough to scare it. It never runs into a
+    /// wall, the snake or other food, and stays put rather than getting any
+    /// closer.
+    fn flee(&mut self, index: usize) {
+        let head = self.player.head;
+        let distance = |cell: Vec2| (cell.x - head.x).abs() + (cell.y - head.y).abs();
+        let from = self.food[index].at;
+        if distance(from) > MOUSE_SIGHT {
+            return;
+        }
+        let best = [
+            Vec2::new(1., 0.),
+            Vec2::new(-1., 0.),
+            Vec2::new(0., 1.),
+            Vec2::new(0., -1.),
+        ]
+        .into_iter()
+        .map(|step| from + step)
+        .filter(|&cell| self.inside(cell) && !self.occupied(cell))
+        .filter(|&cell| distance(cell) > distance(from))
+        .max_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+        if let Some(to) = best {
+            self.food[index].at = to;
+        }
+    }
+
+    /// Moves the food nearest the head one cell towards it, along whichever
+    /// axis it's furthest off on, as long as that cell is free. Poison is
+    /// left alone.
+    fn pull_food(&mut self) {
+        let head = self.player.head;
+        let distance = |food: &Food| (food.at.x - head.x).abs() + (food.at.y - head.y).abs();
+        let nearest = self
+            .food
+            .iter()
+            .enumerate()
+            .filter(|(_, food)| food.kind != FoodKind::Poison)
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map(|(index, _)| index);
+        let Some(index) = nearest else {
+            return;
+        };
+        let from = self.food[index].at;
+        let towards = head - from;
+        let step = match Direction::of(towards) {
+            Direction::Left => Vec2::new(-1., 0.),
+            Direction::Right => Vec2::new(1., 0.),
+            Direction::Up => Vec2::new(0., -1.),
+            Direction::Down => Vec2::new(0., 1.),
+        };
+        let to = from + step;
+        if towards != Vec2::new(0., 0.) && !self.occupied(to) {
+            self.food[index].at = to;
+        }
+    }
+
+    /// The food worth eating nearest the head, counting along the axes, and
+    /// how far off it is.
+    pub fn nearest_food(&self) -> Option<(&Food, f64)> {
+        let head = self.player.head;
+        self.food
+            .iter()
+            .filter(|food| food.kind != FoodKind::Poison)
+            .map(|food| {
+                let distance = (food.at.x - head.x).abs() + (food.at.y - head.y).abs();
+                (food, distance)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// What carrying straight on would crash the snake into next move, if
+    /// anything: a wall, its own body or an obstacle.
+    pub fn danger_ahead(&self) -> Option<DeathCause> {
+        self.danger_at(self.player.head + self.player.forward)
+    }
+
+    /// What the snake would crash into on the move after turning by `angle`
+    /// from the way it's heading, as `danger_ahead` for carrying on.
+    pub fn danger_turning(&self, angle: f64) -> Option<DeathCause> {
+        let mut forward = self.player.heading();
+        forward.rotate(self.movement.turn_angle(angle));
+        if self.movement.on_grid() {
+            forward = forward.round();
+        }
+        self.danger_at(self.player.head + forward)
+    }
+
+    /// What the head would crash into next move on `cell`, if anything.
+    fn danger_at(&self, cell: Vec2) -> Option<DeathCause> {
+        if self.noclip {
+            return None;
+        }
+        let ahead = if self.rules.wraps {
+            self.wrap(cell)
+        } else {
+            cell
+        };
+        if self.rules.pass_through {
+            return (!self.in_bounds(ahead)).then_some(DeathCause::Wall);
+        }
+        let ghost = self.powers.has(PowerUp::Ghost) || self.invulnerable > 0.;
+        // The tail moves out of the way as the head moves in.
+        let body = self.player.len().saturating_sub(1);
+        if !self.inside(ahead) {
+            Some(DeathCause::Wall)
+        } else if !ghost
+            && self
+                .player
+                .segments()
+                .take(body)
+                .any(|cell| self.touching(cell, ahead))
+        {
+            Some(DeathCause::Tail)
+        } else if self.entities.solid_at(ahead, self.movement.reach()) {
+            Some(DeathCause::Obstacle)
+        } else {
+            None
+        }
+    }
+
+    /// With slow motion on, slows time down for a few moves when the head has
+    /// just come up alongside something it could have crashed into, going
+    /// fast enough for it to have been close.
+    fn check_near_miss(&mut self) {
+        let forward = self.player.forward;
+        let head = self.player.head;
+        let near = [
+            Vec2::new(-forward.y, forward.x),
+            Vec2::new(forward.y, -forward.x),
+        ]
+        .into_iter()
+        .any(|side| self.danger_at(head + side).is_some());
+        let missed = near && !self.near_miss;
+        self.near_miss = near;
+        if missed && self.slow_motion && self.speed() >= NEAR_MISS_SPEED {
+            self.slowed = SLOW_MOTION_TICKS;
+            self.emit(GameEvent::NearMiss);
+        }
+    }
+
+    /// How much faster or slower than real time the game is going: the
+    /// scale it's set to, halved for a moment after a near miss and eased
+    /// back up to after being unpaused.
+    pub fn time_scale(&self) -> f64 {
+        let slowed = if self.slowed > 0 {
+            SLOW_MOTION_SCALE
+        } else {
+            1.
+        };
+        let eased = 1. - (1. - RESUME_SCALE) * self.easing / RESUME_EASE;
+        self.time_scale * slowed * eased
+    }
+
+    /// Runs the game `scale` times as fast as real time, which has to be
+    /// more than nothing. Only moving the game on is scaled, not drawing it
+    /// or taking input.
+    pub fn set_time_scale(&mut self, scale: f64) {
+        if scale > 0. && scale.is_finite() {
+            self.time_scale = scale;
+        }
+    }
+
+    /// Takes a life instead of ending the run: the snake that died, the
+    /// partner's if `partner`, comes back half as long somewhere safe, and
+    /// neither can crash for a moment.
+    fn lose_life(&mut self, partner: bool) {
+        if let Some(cause) = self.stats.death {
+            self.emit(GameEvent::LifeLost(cause));
+        }
+        self.lives -= 1;
+        self.stats.death = None;
+        let len = (self.snake(partner).len() / 2).max(1);
+        let snake = self.respawn(len);
+        let head = snake.head;
+        *self.snake_mut(partner) = snake;
+        self.invulnerable = INVULNERABLE_TIME;
+        self.starving = 0.;
+        self.effects.spawn(EffectKind::Flash, head);
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.retry();
+            self.set_up_lesson();
+        }
+    }
+
+    /// A snake `len` cells long laid out straight, on cells without food,
+    /// facing a clear run of `RESPAWN_CLEARANCE` cells. Where nowhere has
+    /// room for that, it's shortened to a lone head on any free cell.
+    fn respawn(&mut self, len: usize) -> Snake {
+        let directions = [
+            Vec2::new(1., 0.),
+            Vec2::new(-1., 0.),
+            Vec2::new(0., 1.),
+            Vec2::new(0., -1.),
+        ];
+        let cells = self.width as u64 * self.height as u64;
+        let start = self.rng.below(cells);
+        for i in 0..cells {
+            let index = (start + i) % cells;
+            let head = Vec2::new(
+                (index % self.width as u64) as f64,
+                (index / self.width as u64) as f64,
+            );
+            for forward in directions {
+                let body = (0..len).map(|n| head - forward * n as f64);
+                let ahead = (1..=RESPAWN_CLEARANCE).map(|n| head + forward * n as f64);
+                let clear =
+                    |cell| self.inside(cell) && !self.food_at(cell) && !self.portal_at(cell);
+                let fits = body.clone().all(clear) && ahead.clone().all(|cell| self.inside(cell));
+                if fits {
+                    let mut snake = Snake::new(head);
+                    snake.forward = forward;
+                    snake.body = body.skip(1).collect();
+                    return snake;
+                }
+            }
+        }
+        let mut snake = Snake::new(Vec2::new(0., 0.));
+        if let Some(head) = self.free_cell() {
+            snake.head = head;
+        }
+        snake
+    }
+
+    /// `at` brought back into the live area from the opposite side, if it's
+    /// gone off one edge.
+    pub fn wrap(&self, at: Vec2) -> Vec2 {
+        let (min, max) = (self.live_min(), self.live_max());
+        let size = max - min + Vec2::new(1., 1.);
+        Vec2::new(
+            min.x + (at.x - min.x).rem_euclid(size.x),
+            min.y + (at.y - min.y).rem_euclid(size.y),
+        )
+    }
+
+    /// Whether `a` and `b` are close enough to count as the same place under
+    /// the movement rules.
+    fn touching(&self, a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < self.movement.reach()
+    }
+
+    /// Turns the snake for a quarter turn asked for by the player, scaled
+    /// down under analog steering.
+    pub fn steer(&mut self, angle: f64) {
+        let retake = self.take_back_move();
+        self.player.rotate(self.movement.turn_angle(angle));
+        if retake {
+            self.step();
+        }
+        self.observe(MissionEvent::Turned(angle));
+    }
+
+    /// Turns the second player's snake in co-op, as `steer` does the first's.
+    pub fn steer_partner(&mut self, angle: f64) {
+        let angle = self.movement.turn_angle(angle);
+        if let Some(partner) = &mut self.partner {
+            partner.rotate(angle);
+        }
+    }
+
+    /// Puts the snake back where it was before its last move, if the move
+    /// was made less than `corner_grace` ago, went straight on and nothing
+    /// came of it, so a turn asked for now can be made in its place.
+    /// Returns whether it did.
+    fn take_back_move(&mut self) -> bool {
+        let late = self.quiet == Some(self.player.grown_len())
+            && self.pending < self.corner_grace
+            && self.movement.turn_delay() == 0
+            && self.player.sliding == 0
+            && !self.player.turning()
+            && !self.partner.as_ref().is_some_and(Snake::turning)
+            && !self.paused
+            && self.countdown.is_none()
+            && !self.is_over();
+        if !late {
+            return false;
+        }
+        let Some(checkpoint) = self.checkpoints.pop_back() else {
+            return false;
+        };
+        // The time since the move carries over to the one made in its
+        // place, so the move after that isn't late.
+        let elapsed = self.pending - checkpoint.pending;
+        self.restore(checkpoint);
+        self.pending += elapsed;
+        self.history.pop_back();
+        self.quiet = None;
+        true
+    }
+
+    /// Unlocks any achievements the run has just earned. Unranked runs don't
+    /// earn any.
+    fn check_achievements(&mut self) {
+        if !self.ranked() {
+            return;
+        }
+        for achievement in Achievement::ALL {
+            if !self.achievements.has(achievement)
+                && achievement.earned(self)
+                && self.achievements.unlock(achievement)
+            {
+                self.emit(GameEvent::Achievement(achievement));
+            }
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            player: self.player.clone(),
+            partner: self.partner.clone(),
+            food: self.food.clone(),
+            entities: self.entities.as_slice().to_vec(),
+            fruit_timer: self.fruit_timer,
+            stats: self.stats.clone(),
+            powers: self.powers.clone(),
+            shield: self.shield,
+            lives: self.lives,
+            invulnerable: self.invulnerable,
+            starving: self.starving,
+            portals: self.portals.clone(),
+            inset: self.inset,
+            since_closing: self.since_closing,
+            clock: self.clock,
+            mission: self.mission.clone(),
+            combo: self.combo,
+            ticks: self.ticks,
+            speed: self.speed,
+            set_to: self.set_to,
+            food_rules: self.food_rules,
+            adapted: self.adapted,
+            speedrun: self.speedrun.clone(),
+            rng: self.rng.clone(),
+            pending: self.pending,
+            slowed: self.slowed,
+            near_miss: self.near_miss,
+        }
+    }
+
+    /// Spends a rewind charge to put the game back `REWIND_SECONDS`, or as
+    /// far as it can go, and counts down before carrying on. Works on a game
+    /// that's just been lost, too. Returns false with no charges or nothing
+    /// to go back to.
+    pub fn rewind(&mut self) -> bool {
+        if self.rewinds == 0 || self.checkpoints.is_empty() {
+            return false;
+        }
+        let steps = (REWIND_SECONDS * self.speed).ceil() as usize;
+        let keep = self.checkpoints.len().saturating_sub(steps);
+        self.checkpoints.truncate(keep + 1);
+        let Some(checkpoint) = self.checkpoints.pop_back() else {
+            return false;
+        };
+        self.restore(checkpoint);
+        self.rewinds -= 1;
+        self.player.clear_turns();
+        if let Some(partner) = &mut self.partner {
+            partner.clear_turns();
+        }
+        self.history.clear();
+        self.effects = Effects::default();
+        self.paused = false;
+        self.quiet = None;
+        self.countdown = Some(Countdown::default());
+        self.emit(GameEvent::Rewound);
+        true
+    }
+
+    /// Puts everything `checkpoint` kept back as it was.
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.player = checkpoint.player;
+        self.partner = checkpoint.partner;
+        self.food = checkpoint.food;
+        self.entities = checkpoint.entities.into();
+        self.fruit_timer = checkpoint.fruit_timer;
+        self.stats = checkpoint.stats;
+        self.powers = checkpoint.powers;
+        self.shield = checkpoint.shield;
+        self.lives = checkpoint.lives;
+        self.invulnerable = checkpoint.invulnerable;
+        self.starving = checkpoint.starving;
+        self.portals = checkpoint.portals;
+        self.inset = checkpoint.inset;
+        self.since_closing = checkpoint.since_closing;
+        self.clock = checkpoint.clock;
+        self.mission = checkpoint.mission;
+        self.combo = checkpoint.combo;
+        self.ticks = checkpoint.ticks;
+        self.speed = checkpoint.speed;
+        self.set_to = checkpoint.set_to;
+        self.food_rules = checkpoint.food_rules;
+        self.adapted = checkpoint.adapted;
+        self.speedrun = checkpoint.speedrun;
+        self.rng = checkpoint.rng;
+        self.pending = checkpoint.pending;
+        self.slowed = checkpoint.slowed;
+        self.near_miss = checkpoint.near_miss;
+    }
+
+    /// Takes a speedrun split if the apple just eaten reached a milestone,
+    /// and shows how it compares with the personal best.
+    fn split(&mut self) {
+        let Some(run) = &mut self.speedrun else {
+            return;
+        };
+        let Some(index) = run.update(self.stats.apples, self.stats.time) else {
+            return;
+        };
+        let mut text = format!(
+            "split {}: {}",
+            index + 1,
+            speedrun::format_split(self.stats.time)
+        );
+        if let Some(delta) = run.delta(index) {
+            text += &format!(" ({})", speedrun::format_delta(delta));
+        }
+        self.notify(text);
+    }
+
+    /// Puts `text` up in a toast for a few seconds.
+    pub fn notify(&mut self, text: String) {
+        self.notices.push((ToastKind::Notice, text));
+    }
+
+    /// Takes the notices queued since the last call, oldest first.
+    pub fn drain_notices(&mut self) -> Vec<(ToastKind, String)> {
+        std::mem::take(&mut self.notices)
+    }
+
+    /// Queues `event` up for whatever reacts to it.
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// Takes the events queued since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Gives the run a mission picked at random.
+    pub fn start_mission(&mut self) {
+        self.mission = Some(Mission::random(&mut self.rng));
+    }
+
+    /// Turns the run into the tutorial. The walls wrap round and there are
+    /// lives to spare, so mistakes only set a lesson back, and food only
+    /// turns up where a lesson puts it.
+    pub fn start_tutorial(&mut self) {
+        let tutorial = Tutorial::new();
+        self.tutorial = Some(tutorial);
+        self.rules.wraps = true;
+        self.lives = TUTORIAL_LIVES;
+        self.food.clear();
+        self.emit(GameEvent::Lesson(tutorial.lesson));
+    }
+
+    /// Whether the tutorial is running and still has lessons to go.
+    fn tutoring(&self) -> bool {
+        self.tutorial
+            .is_some_and(|tutorial| tutorial.lesson != Lesson::Done)
+    }
+
+    /// Lays the board out for the tutorial's current lesson: an apple just
+    /// ahead to eat, or a long snake with apples to weave round it for.
+    fn set_up_lesson(&mut self) {
+        let Some(tutorial) = self.tutorial else {
+            return;
+        };
+        if tutorial.lesson == Lesson::AvoidTail && self.player.len() < TAIL_LESSON_LENGTH {
+            self.player = self.respawn(TAIL_LESSON_LENGTH);
+        }
+        let offset = (self.player.heading() * LESSON_APPLE_DISTANCE).round();
+        let ahead = self.wrap(self.player.head + offset);
+        while (self.food.len() as u32) < tutorial.apples_left() {
+            let at = if self.occupied(ahead) {
+                self.free_cell()
+            } else {
+                Some(ahead)
+            };
+            let Some(at) = at else {
+                return;
+            };
+            self.food.push(Food::new(FoodKind::Normal, at));
+        }
+    }
+
+    /// Passes `event` on to the mission, paying out its reward if that
+    /// completes it, and to the tutorial.
+    fn observe(&mut self, event: MissionEvent) {
+        if let Some(tutorial) = &mut self.tutorial
+            && tutorial.observe(event)
+        {
+            let lesson = tutorial.lesson;
+            self.emit(GameEvent::Lesson(lesson));
+            self.set_up_lesson();
+        }
+        let Some(mission) = &mut self.mission else {
+            return;
+        };
+        if mission.observe(event) {
+            let reward = mission.objective.reward();
+            self.stats.score += reward;
+            self.effects.spawn(
+                EffectKind::Popup(format!("+{reward} mission")),
+                self.player.head,
+            );
+            self.emit(GameEvent::MissionComplete { reward });
+        }
+    }
+
+    /// Whether `cell` is in the live area, inside any walls that have
+    /// closed in.
+    pub fn inside(&self, cell: Vec2) -> bool {
+        self.in_bounds(cell) && !self.walls.contains(&cell)
+    }
+
+    /// Whether `cell` is within the arena's edges, whatever walls are in it.
+    fn in_bounds(&self, cell: Vec2) -> bool {
+        cell.inside_rectange(self.live_min(), self.live_max())
+    }
+
+    fn food_at(&self, cell: Vec2) -> bool {
+        self.food.iter().any(|food| self.touching(food.at, cell))
+    }
+
+    fn portal_at(&self, cell: Vec2) -> bool {
+        self.portal_twin(cell).is_some()
+    }
+
+    /// The other end of the portal on `cell`, if there is one.
+    pub fn portal_twin(&self, cell: Vec2) -> Option<Vec2> {
+        self.portals.iter().find_map(|&(a, b)| {
+            if self.touching(a, cell) {
+                Some(b)
+            } else if self.touching(b, cell) {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The cells taken up by everything on the board: the snake, each food,
+    /// each registered entity, then any partner.
+    pub fn board(&self) -> impl Iterator<Item = Vec<Vec2>> {
+        let player = std::iter::once(self.player.segments().collect());
+        let food = self.food.iter().map(|food| vec![food.at]);
+        let partner = self.partner.iter().map(|snake| snake.segments().collect());
+        player
+            .chain(food)
+            .chain(self.entities.iter().map(|entity| entity.cells()))
+            .chain(partner)
+    }
+
+    /// Every entity on the board bucketed by cell, numbered as `board` has
+    /// them.
+    pub fn spatial(&self) -> SpatialHash {
+        SpatialHash::new(self.board())
+    }
+
+    /// Whether anything on the board or a portal is on `cell`.
+    fn occupied(&self, cell: Vec2) -> bool {
+        let on_snake = std::iter::once(&self.player)
+            .chain(&self.partner)
+            .any(|snake| snake.segments().any(|at| self.touching(at, cell)));
+        on_snake
+            || self.food_at(cell)
+            || self.entities.occupied(cell, self.movement.reach())
+            || self.portal_at(cell)
+    }
+
+    /// Switches to zen rules, turning off everything else that could end the
+    /// run: hunger, closing walls and poison.
+    pub fn start_zen(&mut self) {
+        self.rules = self.rules.zen();
+        self.food_rules.poison = 0;
+        self.food.retain(|food| food.kind != FoodKind::Poison);
+    }
+
+    /// Whether the run counts towards anything kept between runs. One where
+    /// the snake passes through itself, as in zen, is hardly lost, so it
+    /// doesn't, and nor does one at a speed of its own or with more than the
+    /// usual grace for turns.
+    pub fn ranked(&self) -> bool {
+        !self.rules.pass_through
+            && self.tutorial.is_none()
+            && !self.practice
+            && !self.cheated
+            && !self.custom_level()
+            && self.versus.is_none()
+            && self.base_speed == TICKS_PER_SECOND
+            && self.corner_grace <= CORNER_GRACE
+    }
+
+    /// Whether the arena's laid out by a custom level. Levels can be made
+    /// as easy as anyone likes, so they aren't ranked.
+    fn custom_level(&self) -> bool {
+        !self.walls.is_empty() || !self.spawners.is_empty()
+    }
+
+    /// Lays the arena out as `level`, which sets its size too.
+    pub fn load_level(&mut self, level: &Level) {
+        self.width = level.width;
+        self.height = level.height;
+        self.walls = level.walls.clone();
+        self.spawners = level.spawners.clone();
+        self.player = Snake::new(level.spawn);
+        self.food.clear();
+        self.restock();
+    }
+
+    /// Lays the snake out for `start`, head first on its spawn and the rest
+    /// straight back from there, taking away any food it lands on. Fails,
+    /// leaving the snake as it was, if any of it would be off the arena, on
+    /// a wall or on a block.
+    pub fn place_snake(&mut self, start: Start) -> Result<(), String> {
+        let length = start.length.unwrap_or(1).max(1);
+        let direction = start.direction.unwrap_or(Direction::Right);
+        let step = direction.step();
+        let head = start.spawn.unwrap_or_else(|| {
+            let tail = Vec2::new(
+                if direction == Direction::Left {
+                    self.width as f64 - 2.
+                } else {
+                    1.
+                },
+                if direction == Direction::Up {
+                    self.height as f64 - 2.
+                } else {
+                    1.
+                },
+            );
+            tail + step * (length - 1) as f64
+        });
+        let mut snake = Snake::new(head);
+        snake.forward = step;
+        for index in 1..length {
+            snake.body.push_back(head - step * index as f64);
+        }
+        for cell in snake.segments() {
+            let (x, y) = (cell.x, cell.y);
+            if self.walls.contains(&cell) {
+                return Err(format!("the snake would start on a wall at {x}, {y}"));
+            }
+            if !self.inside(cell) {
+                return Err(format!(
+                    "the snake doesn't fit in the arena: {x}, {y} is outside it"
+                ));
+            }
+            if self.entities.solid_at(cell, self.movement.reach()) {
+                return Err(format!("the snake would start on a block at {x}, {y}"));
+            }
+        }
+        self.food
+            .retain(|food| !snake.segments().any(|cell| cell == food.at));
+        self.player = snake;
+        self.restock();
+        Ok(())
+    }
+
+    /// The high-score table the run belongs in, if it's ranked at all. Each
+    /// difficulty has its own, normal keeping the plain mode name.
+    pub fn score_table(&self) -> Option<String> {
+        self.ranked().then(|| match self.difficulty {
+            Difficulty::Normal => self.mode_name().to_string(),
+            difficulty => format!("{}-{}", self.mode_name(), difficulty.name()),
+        })
+    }
+
+    /// What kind of run this is, for labelling it.
+    pub fn mode_name(&self) -> &'static str {
+        if self.tutorial.is_some() {
+            "tutorial"
+        } else if self.practice {
+            "practice"
+        } else if self.rules.is_zen() {
+            "zen"
+        } else if self.clock.is_some() {
+            "time-attack"
+        } else if self.rules.trail {
+            "trail"
+        } else if self.versus.is_some() {
+            "versus"
+        } else if self.partner.is_some() {
+            "co-op"
+        } else {
+            "classic"
+        }
+    }
+
+    /// Brings in a second snake for co-op, laid out as the first turned half
+    /// way round about the middle of the arena, or anywhere safe if that
+    /// isn't clear. The board is stocked for both.
+    pub fn start_coop(&mut self) {
+        let far = Vec2::new(self.width as f64 - 1., self.height as f64 - 1.);
+        let mut partner = Snake::new(far - self.player.head);
+        partner.forward = self.player.forward * -1.;
+        partner.body = self.player.body.iter().map(|&cell| far - cell).collect();
+        let clear = partner.segments().all(|cell| {
+            self.inside(cell)
+                && !self.portal_at(cell)
+                && !self.player.segments().any(|segment| segment == cell)
+                && !self.entities.occupied(cell, self.movement.reach())
+        });
+        if !clear {
+            partner = self.respawn(self.player.len());
+        }
+        self.food
+            .retain(|food| !partner.segments().any(|cell| cell == food.at));
+        self.partner = Some(partner);
+        self.restock();
+    }
+
+    /// Sets the two snakes against each other in a match won by the first
+    /// to take `first_to` rounds, bringing in the second if it isn't there
+    /// yet. There's a life a round, and no rewinding.
+    pub fn start_versus(&mut self, first_to: u32) {
+        if self.partner.is_none() {
+            self.start_coop();
+        }
+        let spawns = [self.player.clone(), self.snake(true).clone()];
+        self.versus = Some(Match::new(first_to, spawns));
+        self.lives = 1;
+        self.rewinds = 0;
+    }
+
+    /// Switches to light-cycle rules, clearing away any food.
+    pub fn start_trail(&mut self) {
+        self.rules.trail = true;
+        self.food.clear();
+    }
+
+    /// Sets the snake's speed to `factor` times the run's base speed, before
+    /// any power-ups, keeping whatever the ramp and adaptive difficulty have
+    /// added to it on top.
+    pub fn set_speed(&mut self, factor: f64) {
+        let speed = self.base_speed * factor;
+        self.speed = match self.set_to {
+            Some(before) => self.speed * speed / before,
+            None => speed,
+        };
+        self.set_to = Some(speed);
+        self.quiet = None;
+    }
+
+    /// Makes the run `notches` harder than it started, for adaptive
+    /// difficulty, speeding the snake up and bringing more poison for each.
+    /// A run with no poison stays that way.
+    pub fn adapt(&mut self, notches: i32) {
+        let change = notches - self.adapted;
+        if change == 0 {
+            return;
+        }
+        self.adapted = notches;
+        self.speed *= ADAPT_SPEED.powi(change);
+        let poison = self.food_rules.poison as f64 * ADAPT_POISON.powi(change);
+        self.food_rules.poison = poison.round() as u32;
+    }
+
+    /// Puts an apple on `cell`, which has to be free and inside the walls.
+    pub fn place_food(&mut self, cell: Vec2) -> Result<(), String> {
+        self.check_free(cell)?;
+        self.food.push(Food::new(FoodKind::Normal, cell));
+        self.quiet = None;
+        Ok(())
+    }
+
+    /// Moves the whole snake so its head is on `cell`, which has to be
+    /// inside the walls.
+    pub fn teleport(&mut self, cell: Vec2) -> Result<(), String> {
+        if !self.inside(cell) {
+            return Err(format!("{}, {} is outside the walls", cell.x, cell.y));
+        }
+        self.quiet = None;
+        let offset = cell - self.player.head;
+        self.player.head = cell;
+        for segment in &mut self.player.body {
+            *segment += offset;
+        }
+        Ok(())
+    }
+
+    /// Puts a block on `cell` for the snake to crash into, which has to be
+    /// free and inside the walls.
+    pub fn place_obstacle(&mut self, cell: Vec2) -> Result<(), String> {
+        self.check_free(cell)?;
+        self.entities.push(Box::new(Obstacle { at: cell }));
+        self.quiet = None;
+        Ok(())
+    }
+
+    /// Takes any food and blocks off `cell`.
+    pub fn clear_cell(&mut self, cell: Vec2) -> Result<(), String> {
+        let before = self.food.len() + self.entities.len();
+        self.food.retain(|food| food.at != cell);
+        self.entities
+            .retain(|entity| !(entity.solid() && entity.cells().contains(&cell)));
+        if self.food.len() + self.entities.len() == before {
+            return Err(format!("nothing to clear at {}, {}", cell.x, cell.y));
+        }
+        self.quiet = None;
+        Ok(())
+    }
+
+    fn check_free(&self, cell: Vec2) -> Result<(), String> {
+        if !self.inside(cell) {
+            return Err(format!("{}, {} is outside the arena", cell.x, cell.y));
+        }
+        if self.occupied(cell) {
+            return Err(format!("{}, {} isn't free", cell.x, cell.y));
+        }
+        Ok(())
+    }
+
+    /// Places `pairs` pairs of portals on free cells away from the walls.
+    pub fn add_portals(&mut self, pairs: usize) {
+        let spatial = self.spatial();
+        let reach = self.movement.reach();
+        let mut free: Vec<Vec2> = (1..self.height.saturating_sub(1))
+            .flat_map(|y| {
+                (1..self.width.saturating_sub(1)).map(move |x| Vec2::new(x as f64, y as f64))
+            })
+            .filter(|&cell| !spatial.occupied(cell, reach) && !self.portal_at(cell))
+            .collect();
+        for _ in 0..pairs {
+            if free.len() < 2 {
+                return;
+            }
+            let a = free.swap_remove(self.rng.below(free.len() as u64) as usize);
+            let b = free.swap_remove(self.rng.below(free.len() as u64) as usize);
+            self.portals.push((a, b));
+        }
+    }
+
+    /// Scores and grows the snake that ate `food`, the partner's if
+    /// `partner`, shrinks it and takes points for poison, or hands over a
+    /// pickup.
+    fn eat(&mut self, food: Food, partner: bool) {
+        self.effects.spawn(EffectKind::Flash, food.at);
+        match food.kind {
+            FoodKind::Poison => {
+                self.stats.score = self.stats.score.saturating_sub(POISON_PENALTY);
+                self.emit(GameEvent::Poisoned);
+                self.effects
+                    .spawn(EffectKind::Popup(format!("-{POISON_PENALTY}")), food.at);
+                if self.snake(partner).grown_len() <= POISON_SHRINK {
+                    self.stats.death = Some(DeathCause::Poison);
+                } else {
+                    for _ in 0..POISON_SHRINK {
+                        self.snake_mut(partner).shrink();
+                    }
+                }
+            }
+            FoodKind::Power(power) => {
+                if let Some(cancelled) = self.powers.grant(power) {
+                    self.emit(GameEvent::PowerUpEnded(cancelled));
+                }
+                self.emit(GameEvent::PowerUpStarted(power));
+            }
+            FoodKind::Shield => {
+                self.shield = true;
+                self.emit(GameEvent::ShieldPickedUp);
+            }
+            FoodKind::Normal
+            | FoodKind::Bonus
+            | FoodKind::Mega
+            | FoodKind::Mouse
+            | FoodKind::Fruit => {
+                let multiplier = self.combo.hit();
+                self.stats.best_combo = self.stats.best_combo.max(multiplier);
+                let score = food.score() * self.powers.score_factor() * multiplier;
+                self.stats.apples += 1;
+                self.stats.score += score;
+                if self.high_score.is_some_and(|best| self.stats.score > best) {
+                    self.high_score = None;
+                    self.notices
+                        .push((ToastKind::Record, "new high score".to_string()));
+                }
+                self.split();
+                if self.ramp > 0. {
+                    self.speed = (self.speed + self.ramp).min(RAMP_LIMIT.max(self.speed));
+                }
+                self.starving = 0.;
+                self.effects.spawn(EffectKind::Glow, food.at);
+                self.effects
+                    .spawn(EffectKind::Popup(format!("+{score}")), food.at);
+                self.emit(GameEvent::FoodEaten {
+                    kind: food.kind,
+                    points: score,
+                });
+                let slide = self.movement.slide();
+                let snake = self.snake_mut(partner);
+                snake.lengthen(food.kind.growth());
+                snake.sliding = slide;
+                self.restock();
+                if let (Some(clock), FoodKind::Bonus) = (&mut self.clock, food.kind) {
+                    *clock += BONUS_TIME;
+                }
+                self.observe(MissionEvent::Ate);
+            }
+        }
+    }
+
+    fn record(&mut self) {
+        let capacity = (HISTORY_SECONDS * self.speed).ceil() as usize;
+        while self.history.len() >= capacity.max(1) {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.snapshot());
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            segments: self.player.segments().collect(),
+            heading: self.player.forward,
+            food: self.food.clone(),
+            progress: 1.,
+            vacated: None,
+            partner: self
+                .partner
+                .iter()
+                .flat_map(|snake| snake.segments())
+                .collect(),
+            partner_heading: self.snake(true).forward,
+            tints: [self.players[0].tint(), self.players[1].tint()],
+        }
+    }
+
+    /// The snapshot to draw right now: with smoothing on, the last tick's move
+    /// is shown only as far along as the time banked towards the next one.
+    fn interpolated(&self) -> Snapshot {
+        let mut snapshot = self.snapshot();
+        if !self.smooth || self.history.is_empty() || self.is_over() {
+            return snapshot;
+        }
+        snapshot.progress = (self.pending * self.speed()).min(1.);
+        let previous = self.history.len().checked_sub(2).map(|i| &self.history[i]);
+        snapshot.vacated = previous
+            .and_then(|previous| previous.segments.last().copied())
+            .filter(|tail| !snapshot.segments.contains(tail));
+        snapshot
+    }
+
+    /// Cells per second, including any power-up speeding or slowing the
+    /// snake.
+    pub fn speed(&self) -> f64 {
+        self.speed * self.powers.speed_factor()
+    }
+
+    /// What a snake, the partner's if `partner`, has crashed into, if
+    /// anything. Snakes in co-op pass through each other, but not in versus.
+    fn collision(&self, partner: bool) -> Option<DeathCause> {
+        if self.noclip {
+            return None;
+        }
+        let snake = self.snake(partner);
+        if self.rules.pass_through {
+            return (!self.in_bounds(snake.head)).then_some(DeathCause::Wall);
+        }
+        let ghost = self.powers.has(PowerUp::Ghost) || self.invulnerable > 0.;
+        let rival = self.versus.is_some()
+            && self
+                .snake(!partner)
+                .segments()
+                .any(|cell| self.touching(cell, snake.head));
+        if !self.inside(snake.head) {
+            Some(DeathCause::Wall)
+        } else if snake.bites_itself(self.movement.reach()) && !ghost {
+            Some(DeathCause::Tail)
+        } else if self.entities.solid_at(snake.head, self.movement.reach()) {
+            Some(DeathCause::Obstacle)
+        } else if rival && !ghost {
+            Some(DeathCause::Rival)
+        } else {
+            None
+        }
+    }
+
+    /// A random cell that neither the snake nor any food is on, if there's
+    /// one left.
+    fn free_cell(&mut self) -> Option<Vec2> {
+        let spatial = self.spatial();
+        let reach = self.movement.reach();
+        let free: Vec<Vec2> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Vec2::new(x as f64, y as f64)))
+            .filter(|&cell| {
+                self.inside(cell) && !spatial.occupied(cell, reach) && !self.portal_at(cell)
+            })
+            .collect();
+        if free.is_empty() {
+            None
+        } else {
+            Some(free[self.rng.below(free.len() as u64) as usize])
+        }
+    }
+
+    /// A free cell for new food: one of the level's spawners, if it has any,
+    /// or anywhere free.
+    fn food_cell(&mut self) -> Option<Vec2> {
+        if self.spawners.is_empty() {
+            return self.free_cell();
+        }
+        let spatial = self.spatial();
+        let reach = self.movement.reach();
+        let free: Vec<Vec2> = self
+            .spawners
+            .iter()
+            .copied()
+            .filter(|&cell| {
+                self.inside(cell) && !spatial.occupied(cell, reach) && !self.portal_at(cell)
+            })
+            .collect();
+        if free.is_empty() {
+            None
+        } else {
+            Some(free[self.rng.below(free.len() as u64) as usize])
+        }
+    }
+
+    /// Tops the board back up to the food rules' count, not counting any
+    /// extras, for each snake. With no free cell left the board stays short.
+    fn restock(&mut self) {
+        if self.rules.trail || self.tutoring() {
+            return;
+        }
+        let target = self.food_rules.count.max(1) * self.seats().len();
+        while self
+            .food
+            .iter()
+            .filter(|food| !food.kind.is_extra())
+            .count()
+            < target
+        {
+            let Some(at) = self.food_cell() else {
+                return;
+            };
+            let kind = self.food_rules.pick(&mut self.rng);
+            self.food.push(Food::new(kind, at));
+        }
+    }
+
+    fn draw_hud(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let mut col = 0;
+        if let Some(clock) = self.clock {
+            let seconds = clock.ceil() as u64;
+            let text = format!("{}:{:02}  ", seconds / 60, seconds % 60);
+            let colors = &renderer.theme.colors;
+            let fg = if clock <= CLOCK_WARNING {
+                colors.food
+            } else {
+                colors.head
+            };
+            frame.print_fg(col, 0, &text, fg);
+            col += text.chars().count() as u16;
+        }
+        if let Some(run) = &self.speedrun {
+            let colors = &renderer.theme.colors;
+            let time = match run.splits.last() {
+                Some(&last) if run.finished() => last,
+                _ => self.stats.time,
+            };
+            let text = format!("{}  ", speedrun::format_split(time));
+            frame.print_fg(col, 0, &text, colors.head);
+            col += text.chars().count() as u16;
+            let last = run.splits.len().checked_sub(1);
+            if let Some(delta) = last.and_then(|index| run.delta(index)) {
+                let fg = if delta > 0. { colors.food } else { colors.head };
+                let text = format!("{}  ", speedrun::format_delta(delta));
+                frame.print_fg(col, 0, &text, fg);
+                col += text.chars().count() as u16;
+            }
+        }
+        let status = match &self.versus {
+            Some(versus) => format!("round {}", versus.round),
+            None if self.partner.is_some() => format!("score {}", self.stats.score),
+            None => format!("score {}  length {}", self.stats.score, self.player.len()),
+        };
+        frame.print(col, 0, &status);
+        col += status.chars().count() as u16;
+        // Each player's name in their colour, with their length, or in
+        // versus the rounds they've won.
+        let seats = if self.partner.is_some() {
+            self.seats()
+        } else {
+            Vec::new()
+        };
+        for (index, partner) in seats.into_iter().enumerate() {
+            let player = &self.players[index];
+            let count = match &self.versus {
+                Some(versus) => versus.wins[index] as usize,
+                None => self.snake(partner).len(),
+            };
+            let text = format!("  {} {count}", player.name);
+            let tint = player_tint(renderer, index, player.tint());
+            let fg = tint.map_or(renderer.theme.colors.head, |(head, _)| head);
+            frame.print_fg(col, 0, &text, fg);
+            col += text.chars().count() as u16;
+        }
+        if self.rules.is_zen() {
+            frame.print_fg(col, 0, "  zen", renderer.theme.colors.wall);
+            col += 5;
+        }
+        if self.lives > 1 && self.tutorial.is_none() {
+            let text = format!("  lives {}", self.lives);
+            frame.print_fg(col, 0, &text, renderer.theme.colors.head);
+            col += text.chars().count() as u16;
+        }
+        if self.shield {
+            let text = format!("  {} shield", renderer.theme.glyphs.shield);
+            frame.print_fg(col, 0, &text, renderer.theme.colors.shield);
+            col += text.chars().count() as u16;
+        }
+        if self.combo.multiplier > 1 {
+            let bar = renderer.bar(self.combo.remaining(), HUD_BAR);
+            let text = format!("  x{} {bar}", self.combo.multiplier);
+            frame.print_fg(col, 0, &text, renderer.theme.colors.bonus);
+            col += text.chars().count() as u16;
+        }
+        if let Some(interval) = self.rules.hunger {
+            let fill = 1. - self.starving / interval;
+            let text = format!("  hunger {}", renderer.bar(fill, HUD_BAR));
+            frame.print_fg(col, 0, &text, renderer.theme.colors.food);
+            col += text.chars().count() as u16;
+        }
+        if self.rewinds > 0 {
+            let text = format!("  rewind {}", self.rewinds);
+            frame.print_fg(col, 0, &text, renderer.theme.colors.wall);
+            col += text.chars().count() as u16;
+        }
+        if let Some(left) = self.closing_in() {
+            let text = format!("  walls {}s", left.ceil());
+            frame.print_fg(col, 0, &text, renderer.theme.colors.wall);
+            col += text.chars().count() as u16;
+        }
+        for (power, left) in self.powers.iter() {
+            let icon = power.icon(renderer.caps.unicode);
+            let text = format!("  {icon} {}s", left.ceil());
+            frame.print_fg(col, 0, &text, renderer.theme.colors.power);
+            col += text.chars().count() as u16;
+        }
+        let mut right = width;
+        if self.god || self.noclip {
+            let text = "  DEBUG";
+            right = right.saturating_sub(text.len() as u16);
+            frame.print_fg(right, 0, text, renderer.theme.colors.food);
+        }
+        if let Some(mission) = &self.mission {
+            let text = format!("  {}", mission.status());
+            right = right.saturating_sub(text.chars().count() as u16);
+            frame.print_fg(right, 0, &text, renderer.theme.colors.bonus);
+        }
+        let fruit = self.food.iter().find(|food| food.kind == FoodKind::Fruit);
+        if let Some(fruit) = fruit {
+            let (glyph, fg) = renderer.food_style(FoodKind::Fruit);
+            let text = format!("{glyph} {}", renderer.bar(fruit.remaining(), HUD_BAR));
+            let col = right.saturating_sub(text.chars().count() as u16);
+            frame.print_fg(col, 0, &text, fg);
+        }
+        frame
+    }
+
+    /// Keeps the camera on the snake for a view of `width` by `height` arena
+    /// cells.
+    pub fn track(&mut self, width: u16, height: u16) {
+        let world = (self.width, self.height);
+        self.camera.follow(self.player.head, (width, height), world);
+    }
+
+    /// The arena cells that fit in `width` by `height` terminal cells.
+    fn view(&self, renderer: &Renderer, width: u16, height: u16) -> (u16, u16) {
+        let (cols, rows) = renderer.arena_size(width, height.saturating_sub(HUD_ROWS));
+        (self.width.min(cols), self.height.min(rows))
+    }
+
+    /// Draws the part of the arena the camera is on, as it looks in
+    /// `snapshot`, for a screen of `width` by `height`. The arena is drawn
+    /// with an extra terminal cell all round that's cropped off afterwards, so
+    /// anything just out of view is clamped into the border rather than onto
+    /// the visible edge.
+    fn draw_arena(
+        &self,
+        renderer: &Renderer,
+        snapshot: &Snapshot,
+        width: u16,
+        height: u16,
+        with_effects: bool,
+    ) -> Frame {
+        let (view_width, view_height) = self.view(renderer, width, height);
+        let (x, y) = renderer.mode.cell_size();
+        let shift = Vec2::new(x as f64, y as f64) - self.camera.origin;
+        let mut arena = renderer.arena_frame(view_width + 2 * x, view_height + 2 * y);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = Vec2::new(x as f64, y as f64);
+                if !self.inside(cell) {
+                    renderer.draw_wall(&mut arena, cell + shift);
+                }
+            }
+        }
+        for &(a, b) in &self.portals {
+            renderer.draw_portal(&mut arena, a + shift);
+            renderer.draw_portal(&mut arena, b + shift);
+        }
+        snapshot.shifted(shift).draw(renderer, &mut arena);
+        for entity in self.entities.iter() {
+            entity.draw(renderer, &mut arena, shift);
+        }
+        if let Some(cursor) = self.cursor {
+            renderer.highlight_cell(&mut arena, cursor + shift, renderer.theme.colors.bonus);
+        }
+        if with_effects && !renderer.reduced_motion {
+            self.effects
+                .draw(renderer, &mut arena, shift, self.player.head + shift);
+        }
+        if let Some(radius) = self.sight() {
+            let head = snapshot.segments.first().copied();
+            let head = head.unwrap_or(self.player.head);
+            renderer.fog(&mut arena, head + shift, radius);
+        }
+        let columns = renderer.cell_columns();
+        arena
+            .crop(
+                columns,
+                1,
+                arena.width().saturating_sub(2 * columns),
+                arena.height().saturating_sub(2),
+            )
+            .scaled(renderer.scale)
+    }
+
+    /// Where `arena` goes on a screen of `width` by `height` so it sits in the
+    /// middle of the space below the HUD, with the spare room either side.
+    fn letterbox(arena: &Frame, width: u16, height: u16) -> (u16, u16) {
+        let rows = height.saturating_sub(HUD_ROWS);
+        (
+            width.saturating_sub(arena.width()) / 2,
+            HUD_ROWS + rows.saturating_sub(arena.height()) / 2,
+        )
+    }
+
+    /// The HUD over the arena as it looks in `snapshot`, with the arena
+    /// nudged `offset` terminal cells from where it normally sits.
+    pub fn draw_scene(
+        &self,
+        renderer: &Renderer,
+        width: u16,
+        height: u16,
+        snapshot: &Snapshot,
+        offset: (i32, i32),
+    ) -> Frame {
+        let mut frame = self.draw_hud(renderer, width, height);
+        let arena = self.draw_arena(renderer, snapshot, width, height, false);
+        let (col, row) = Self::letterbox(&arena, width, height);
+        frame.blit_offset(&arena, col as i32 + offset.0, row as i32 + offset.1);
+        frame
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = self.draw_hud(renderer, width, height);
+        let mut snapshot = self.interpolated();
+        if self.invulnerable > 0.
+            && (self.invulnerable * BLINK_RATE) as u64 % 2 == 1
+            && !renderer.reduced_motion
+        {
+            snapshot.segments.clear();
+            snapshot.partner.clear();
+        }
+        let arena = self.draw_arena(renderer, &snapshot, width, height, true);
+        let (col, row) = Self::letterbox(&arena, width, height);
+        frame.blit(&arena, col, row);
+        if self.fog.is_some() {
+            snapshot.food.clear();
+        }
+        if self.view(renderer, width, height) != (self.width, self.height) {
+            minimap::draw(
+                renderer,
+                &mut frame,
+                HUD_ROWS,
+                self.width,
+                self.height,
+                &snapshot,
+            );
+        }
+
+        let overlay = if self.cursor.is_some() {
+            Some("EDITING")
+        } else if self.paused {
+            Some("PAUSED")
+        } else {
+            self.countdown.as_ref().map(Countdown::label)
+        };
+        if let Some(text) = overlay {
+            let row = HUD_ROWS + height.saturating_sub(HUD_ROWS) / 2;
+            frame.print_centered(row, text, renderer.theme.colors.head);
+        }
+        if let Some(versus) = &self.versus
+            && versus.intermission > 0.
+        {
+            versus.draw_scoreboard(renderer, &mut frame, &self.players);
+        }
+        if self.cursor.is_some() {
+            frame.print_centered(
+                height.saturating_sub(1),
+                practice::HINT,
+                renderer.theme.colors.bonus,
+            );
+        } else if let Some(tutorial) = &self.tutorial {
+            let prompt = tutorial.lesson.prompt();
+            frame.print_centered(
+                height.saturating_sub(1),
+                &prompt,
+                renderer.theme.colors.bonus,
+            );
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The quarter turn steered in every case.
+    const TURN: f64 = std::f64::consts::FRAC_PI_2;
+
+    /// A game with a snake a few segments long heading right across the
+    /// middle of an empty arena, ready to move.
+    fn game() -> Game {
+        let mut game = Game::new(30, 20, 0x5EED, FoodRules::default());
+        game.countdown = None;
+        game.food.clear();
+        let head = Vec2::new(10., 10.);
+        game.player = Snake::new(head);
+        for x in 1..4 {
+            game.player.body.push_back(head - Vec2::new(x as f64, 0.));
+        }
+        game
+    }
+
+    /// Where the head ends up turning just before the next move.
+    fn turned_early() -> Vec2 {
+        let mut game = game();
+        game.steer(TURN);
+        game.step();
+        game.player.head
+    }
+
+    #[test]
+    fn late_turn_within_grace_is_taken_back() {
+        let mut game = game();
+        game.step();
+        game.pending = game.corner_grace / 2.;
+        game.steer(TURN);
+        assert_eq!(game.player.head, turned_early());
+        assert_eq!(game.player.len(), 4);
+    }
+
+    #[test]
+    fn taken_back_move_keeps_to_time() {
+        let mut game = game();
+        let step = 1. / game.speed();
+        let grace = game.corner_grace;
+        game.update(step);
+        game.update(grace / 2.);
+        game.steer(TURN);
+        let turned = game.player.head;
+        assert_eq!(turned, turned_early());
+        // The next move is a step after the one taken back, not after the
+        // turn.
+        game.update(step - grace / 2. - 0.001);
+        assert_eq!(game.player.head, turned);
+        game.update(0.002);
+        assert_ne!(game.player.head, turned);
+    }
+
+    #[test]
+    fn late_turn_past_grace_is_not_taken_back() {
+        let mut game = game();
+        game.step();
+        let moved = game.player.head;
+        game.pending = game.corner_grace + 0.01;
+        game.steer(TURN);
+        assert_eq!(game.player.head, moved);
+        game.step();
+        assert_ne!(game.player.head, turned_early());
+        assert_eq!(
+            game.player.head,
+            moved + (turned_early() - Vec2::new(10., 10.))
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    deathcam::DeathCam,
+    game::{
+        Game,
+        RunStats,
+    },
+    render::{
+        Frame,
+        Renderer,
+    },
+    scores::HighScores,
+};
+
+const PROMPT: &str = "enter  restart    c  export cast    m  menu    q  quit";
+
+/// Columns between the stats and the death cam.
+const CAM_GAP: u16 = 3;
+
+/// The summary shown once the snake has died.
+#[derive(Debug, Clone)]
+pub struct GameOverScreen {
+    pub stats: RunStats,
+    pub length: usize,
+    pub seed: u64,
+    /// The code for playing the same run again, if there is one.
+    pub challenge: Option<String>,
+    /// The high-score table the run went into, if it was ranked.
+    pub table: Option<String>,
+    /// The best score in that table, this run included.
+    pub best: Option<u32>,
+    /// Where the run placed in the table, if it made it in.
+    pub place: Option<usize>,
+    /// How exporting the run went, once it's been tried.
+    pub notice: Option<String>,
+    /// Whether the run set new personal-best speedrun splits.
+    pub best_splits: bool,
+    /// Who won, if the run was a versus match.
+    pub verdict: Option<String>,
+    /// Who played, when there were two snakes.
+    pub players: Option<String>,
+    /// The last moments before dying, looping beside the stats.
+    cam: Option<DeathCam>,
+}
+
+impl GameOverScreen {
+    pub fn new(game: &Game) -> Self {
+        Self {
+            stats: game.stats.clone(),
+            length: game.player.len(),
+            seed: game.seed,
+            challenge: game.challenge.clone(),
+            table: game.score_table(),
+            best: None,
+            place: None,
+            notice: None,
+            best_splits: false,
+            verdict: game
+                .versus
+                .as_ref()
+                .and_then(|versus| versus.verdict(&game.players)),
+            players: game.partner.as_ref().map(|_| {
+                let [first, second] = &game.players;
+                format!("{} and {}", first.name, second.name)
+            }),
+            cam: DeathCam::new(game),
+        }
+    }
+
+    /// What the run set a new best for, if anything, as it'd be announced.
+    pub fn personal_best(&self) -> Option<String> {
+        if self.place == Some(0) {
+            Some(format!("new high score: {}", self.stats.score))
+        } else if self.best_splits {
+            Some("new personal best splits".to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if let Some(cam) = &mut self.cam {
+            cam.update(dt);
+        }
+    }
+
+    /// Enters the run into its high-score table, if it has one, and saves
+    /// the table. Scores that can't be loaded or saved are left out of the
+    /// summary rather than holding it up.
+    pub fn record(&mut self) {
+        let Some(table) = &self.table else {
+            return;
+        };
+        let Ok(mut scores) = HighScores::load() else {
+            return;
+        };
+        let place = scores.add(table, self.stats.score);
+        if scores.save().is_ok() {
+            self.place = place;
+            self.best = scores.table(table).first().copied();
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let stats = &self.stats;
+        let cause = stats.death.map_or("gave up", |cause| cause.describe());
+        let mut lines = vec![
+            format!("score         {}", stats.score),
+            format!("length        {}", self.length),
+            format!("time          {}", format_time(stats.time)),
+            format!("apples eaten  {}", stats.apples),
+            format!("best combo    x{}", stats.best_combo.max(1)),
+            format!("top speed     {:.1} cells/s", stats.top_speed),
+            format!("cause         {cause}"),
+            format!("seed          {}", self.seed),
+        ];
+        if let Some(players) = &self.players {
+            lines.insert(0, format!("players       {players}"));
+        }
+        if let (Some(table), Some(best)) = (&self.table, self.best) {
+            lines.push(format!("high score    {best} ({table})"));
+        }
+        if let Some(code) = &self.challenge {
+            lines.push(format!("challenge     {code}"));
+        }
+        let block_height = lines.len() as u16 + if self.notice.is_some() { 6 } else { 4 };
+        let mut row = height.saturating_sub(block_height) / 2;
+        let title = if let Some(verdict) = &self.verdict {
+            verdict.to_uppercase()
+        } else if self.place == Some(0) {
+            "GAME OVER - NEW HIGH SCORE".to_string()
+        } else {
+            "GAME OVER".to_string()
+        };
+        frame.print_centered(row, &title, colors.food);
+        row += 2;
+        let widest = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let cam = self.cam.as_ref().map(|cam| cam.draw(renderer));
+        // The inset goes to the right of the stats, where there's room.
+        let cam = cam.filter(|cam| widest + CAM_GAP + cam.width() <= width);
+        let total = widest + cam.as_ref().map_or(0, |cam| CAM_GAP + cam.width());
+        let col = width.saturating_sub(total) / 2;
+        if let Some(cam) = &cam {
+            frame.blit(cam, col + widest + CAM_GAP, row.saturating_sub(1));
+        }
+        for line in &lines {
+            frame.print(col, row, line);
+            row += 1;
+        }
+        row += 1;
+        frame.print_centered(row, PROMPT, colors.wall);
+        if let Some(notice) = &self.notice {
+            frame.print_centered(row + 2, notice, colors.bonus);
+        }
+        frame
+    }
+}
+
+/// Formats seconds as `m:ss.s`.
+pub fn format_time(seconds: f64) -> String {
+    let minutes = (seconds / 60.) as u64;
+    format!("{minutes}:{:04.1}", seconds - minutes as f64 * 60.)
+}
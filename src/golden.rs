@@ -0,0 +1,251 @@
+use std::{
+    fs,
+    path::Path,
+};
+
+use crate::{
+    caps::{
+        Capabilities,
+        ColorDepth,
+    },
+    difficulty::Difficulty,
+    food::FoodRules,
+    game::{
+        Game,
+        HUD_ROWS,
+    },
+    help,
+    lobby::LobbyScreen,
+    modes::ModeScreen,
+    players::default_players,
+    render::{
+        Capture,
+        Frame,
+        RenderMode,
+        Renderer,
+    },
+    rules::{
+        RulesScreen,
+        Ruleset,
+    },
+    theme::{
+        BUILTIN_THEMES,
+        Theme,
+    },
+    title::TitleScreen,
+    tournament::Bracket,
+    versus,
+};
+
+/// Where the golden frames are kept, from the top of the source tree.
+pub const GOLDEN_DIR: &str = "golden";
+
+/// Terminal sizes every scene is drawn at.
+const SIZES: [(u16, u16); 2] = [(80, 24), (120, 40)];
+
+/// Seed the game in the game scenes is played with.
+const SEED: u64 = 0x5EED;
+
+/// One screen to check: what it's called, whether it's drawn in every
+/// theme or just the classic one, and how to draw it.
+struct Scene {
+    name: &'static str,
+    themed: bool,
+    draw: fn(&Renderer, u16, u16) -> Frame,
+}
+
+const SCENES: [Scene; 7] = [
+    Scene {
+        name: "game",
+        themed: true,
+        draw: |renderer, cols, rows| game(renderer, cols, rows).draw(renderer, cols, rows),
+    },
+    Scene {
+        name: "help",
+        themed: false,
+        draw: |renderer, cols, rows| {
+            let game = game(renderer, cols, rows);
+            let mut frame = game.draw(renderer, cols, rows);
+            help::draw(renderer, &mut frame, Some(&game));
+            frame
+        },
+    },
+    Scene {
+        name: "title",
+        themed: false,
+        draw: |renderer, cols, rows| {
+            TitleScreen::new(Difficulty::Normal).draw(renderer, cols, rows)
+        },
+    },
+    Scene {
+        name: "modes",
+        themed: false,
+        draw: |renderer, cols, rows| ModeScreen::new().draw(renderer, cols, rows),
+    },
+    Scene {
+        name: "lobby",
+        themed: false,
+        draw: |renderer, cols, rows| {
+            LobbyScreen::new(Some(versus::FIRST_TO), Ruleset::CLASSIC, default_players())
+                .draw(renderer, cols, rows)
+        },
+    },
+    Scene {
+        name: "custom",
+        themed: false,
+        draw: |renderer, cols, rows| RulesScreen::new(Ruleset::CLASSIC).draw(renderer, cols, rows),
+    },
+    Scene {
+        name: "tournament",
+        themed: false,
+        draw: |renderer, cols, rows| {
+            let players = ["ana", "bo", "cy", "dee", "eve"].map(String::from).to_vec();
+            Bracket::new(players, SEED).draw(renderer, cols, rows)
+        },
+    },
+];
+
+/// What came of comparing one frame against its golden copy.
+pub enum Outcome {
+    Same,
+    /// It differs, first on the given line.
+    Changed(usize),
+    Missing,
+    /// The golden copy was written over with it.
+    Updated,
+}
+
+/// Draws every scene at every size, in every theme where it's themed,
+/// captures what would be written to the terminal and compares it with the
+/// copy kept in `dir`, or writes it there if `update`. Returns each frame's
+/// name with how it compared.
+pub fn run(dir: &str, update: bool) -> Result<Vec<(String, Outcome)>, String> {
+    if update {
+        fs::create_dir_all(dir).map_err(|err| format!("{dir}: {err}"))?;
+    }
+    let mut outcomes = Vec::new();
+    for scene in &SCENES {
+        let themes: &[&str] = if scene.themed {
+            &BUILTIN_THEMES
+        } else {
+            &["classic"]
+        };
+        for &theme in themes {
+            let renderer = renderer(theme)?;
+            for (cols, rows) in SIZES {
+                let name = format!("{}-{theme}-{cols}x{rows}", scene.name);
+                let captured = capture(&renderer, &(scene.draw)(&renderer, cols, rows))?;
+                let path = Path::new(dir).join(format!("{name}.txt"));
+                let shown = path.display();
+                let outcome = if update {
+                    fs::write(&path, &captured).map_err(|err| format!("{shown}: {err}"))?;
+                    Outcome::Updated
+                } else if !path.exists() {
+                    Outcome::Missing
+                } else {
+                    let golden =
+                        fs::read_to_string(&path).map_err(|err| format!("{shown}: {err}"))?;
+                    if golden == captured {
+                        Outcome::Same
+                    } else {
+                        let same = golden
+                            .lines()
+                            .zip(captured.lines())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        Outcome::Changed(same + 1)
+                    }
+                };
+                outcomes.push((name, outcome));
+            }
+        }
+    }
+    Ok(outcomes)
+}
+
+/// A renderer as if on a truecolour Unicode terminal, so frames come out
+/// the same wherever they're checked.
+fn renderer(theme: &str) -> Result<Renderer, String> {
+    let theme = Theme::builtin(theme).ok_or(format!("unknown theme `{theme}`"))?;
+    let caps = Capabilities {
+        color: ColorDepth::TrueColor,
+        unicode: true,
+        braille: true,
+        alt_screen: true,
+        multiplexer: None,
+    };
+    Ok(Renderer::new(RenderMode::Block, theme, caps, false))
+}
+
+/// A few moves into a run filling the terminal, with the snake grown a bit
+/// and turned a corner.
+fn game(renderer: &Renderer, cols: u16, rows: u16) -> Game {
+    let (width, height) = renderer.arena_size(cols, rows.saturating_sub(HUD_ROWS));
+    let mut game = Game::new(width, height, SEED, FoodRules::default());
+    game.countdown = None;
+    game.player.lengthen(5);
+    for tick in 0..12 {
+        if tick == 6 {
+            game.steer(90_f64.to_radians());
+        }
+        game.step();
+    }
+    game.drain_events();
+    game
+}
+
+/// What presenting `frame` writes to the terminal, with escapes spelt out
+/// as `\e` and a line break before each move of the cursor, so it diffs
+/// as text.
+fn capture(renderer: &Renderer, frame: &Frame) -> Result<String, String> {
+    let mut capture = Capture::default();
+    renderer
+        .present(frame, &mut capture)
+        .map_err(|err| err.to_string())?;
+    let raw = capture.frames.concat();
+    let mut text = String::new();
+    let mut chars = raw.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            text.push(ch);
+            continue;
+        }
+        let mut escape = String::from("\\e");
+        for ch in chars.by_ref() {
+            escape.push(ch);
+            if ch.is_ascii_alphabetic() {
+                break;
+            }
+        }
+        if escape.ends_with('H') && !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&escape);
+    }
+    text.push('\n');
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_match() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_DIR);
+        let outcomes = run(&dir.to_string_lossy(), false).unwrap();
+        let differ: Vec<String> = outcomes
+            .into_iter()
+            .filter_map(|(name, outcome)| match outcome {
+                Outcome::Same => None,
+                Outcome::Changed(line) => Some(format!("{name} changed from line {line}")),
+                Outcome::Missing | Outcome::Updated => Some(format!("{name} is missing")),
+            })
+            .collect();
+        assert!(
+            differ.is_empty(),
+            "golden frames differ, check them and run `snake golden --update`: {}",
+            differ.join(", ")
+        );
+    }
+}
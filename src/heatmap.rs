@@ -0,0 +1,102 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Vec2,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Columns and rows deaths are counted in. Arenas come in every size, so
+/// each one is squeezed onto the same grid.
+const COLUMNS: usize = 32;
+const ROWS: usize = 16;
+
+/// Terminal columns each grid cell is drawn across, to look roughly square.
+const CELL_WIDTH: u16 = 2;
+
+/// Shades from fewest deaths to most, and their ASCII stand-ins.
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+const ASCII_SHADES: [char; 4] = ['.', ':', '*', '#'];
+
+/// How many runs have ended in each part of the arena.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Heatmap {
+    /// Counts by row, then column. Empty until the first death.
+    cells: Vec<Vec<u32>>,
+}
+
+impl Heatmap {
+    /// Counts a death at `at` in an arena `width` by `height` cells.
+    pub fn add(&mut self, at: Vec2, width: u16, height: u16) {
+        if self.cells.len() != ROWS || self.cells.iter().any(|row| row.len() != COLUMNS) {
+            self.cells = vec![vec![0; COLUMNS]; ROWS];
+        }
+        let bucket = |at: f64, size: u16, buckets: usize| {
+            let fraction = at / size.max(1) as f64;
+            ((fraction * buckets as f64).floor().max(0.) as usize).min(buckets - 1)
+        };
+        let col = bucket(at.x, width, COLUMNS);
+        let row = bucket(at.y, height, ROWS);
+        self.cells[row][col] += 1;
+    }
+
+    fn most(&self) -> u32 {
+        self.cells.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    pub fn width(&self) -> u16 {
+        COLUMNS as u16 * CELL_WIDTH + 2
+    }
+
+    pub fn height(&self) -> u16 {
+        ROWS as u16 + 2
+    }
+
+    /// Draws the grid in a box with its top left at `col`, `row`, each cell
+    /// shaded by how many deaths it's had against the worst one.
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame, col: u16, row: u16) {
+        let colors = &renderer.theme.colors;
+        let unicode = renderer.caps.unicode;
+        let shades = if unicode { SHADES } else { ASCII_SHADES };
+        frame.outline(col, row, self.width(), self.height(), unicode, colors.wall);
+        let most = self.most().max(1) as f64;
+        for (y, counts) in self.cells.iter().enumerate() {
+            for (x, &count) in counts.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                let density = count as f64 / most;
+                let shade = shades
+                    [((density * shades.len() as f64).ceil() as usize).clamp(1, shades.len()) - 1];
+                let fg = if density > 2. / 3. {
+                    colors.food
+                } else if density > 1. / 3. {
+                    colors.bonus
+                } else {
+                    colors.wall
+                };
+                for offset in 0..CELL_WIDTH {
+                    let cell_col = col + 1 + x as u16 * CELL_WIDTH + offset;
+                    frame.put(cell_col, row + 1 + y as u16, shade, fg);
+                }
+            }
+        }
+    }
+
+    /// The key to the shading, fewest deaths on the left.
+    pub fn legend(&self, renderer: &Renderer) -> String {
+        let shades = if renderer.caps.unicode {
+            SHADES
+        } else {
+            ASCII_SHADES
+        };
+        let shades: String = shades.iter().collect();
+        format!("fewer {shades} more  (most in one place: {})", self.most())
+    }
+}
@@ -0,0 +1,168 @@
+use termion::event::Key;
+
+use crate::{
+    difficulty::Difficulty,
+    game::Game,
+    keymap,
+    movement::MovementRules,
+    power::PowerUp,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Draws the help in a box over the middle of `frame`: the keys, what each
+/// power-up does and, when there's a run on, the rules it's played by.
+pub fn draw(renderer: &Renderer, frame: &mut Frame, game: Option<&Game>) {
+    let mut lines = vec!["controls".to_string()];
+    // Keys that do the same thing share a line.
+    let mut groups: Vec<(&str, Vec<Key>)> = Vec::new();
+    for (key, command) in keymap() {
+        match groups
+            .iter_mut()
+            .find(|(what, _)| *what == command.describe())
+        {
+            Some((_, keys)) => keys.push(key),
+            None => groups.push((command.describe(), vec![key])),
+        }
+    }
+    for (what, keys) in groups {
+        lines.push(format!("  {:<14} {what}", key_list(&keys)));
+    }
+    lines.push(String::new());
+    lines.push("power-ups".to_string());
+    for power in PowerUp::ALL {
+        lines.push(format!(
+            "  {}  {:<14} {}s",
+            power.icon(renderer.caps.unicode),
+            power.name(),
+            power.duration()
+        ));
+    }
+    if let Some(game) = game {
+        lines.push(String::new());
+        lines.push(format!("rules: {}", game.mode_name()));
+        lines.extend(rules(game).into_iter().map(|rule| format!("  {rule}")));
+    }
+    lines.push(String::new());
+    lines.push("any key to close".to_string());
+
+    let colors = &renderer.theme.colors;
+    let glyphs = &renderer.theme.glyphs;
+    let inner = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let width = (inner + 4).min(frame.width());
+    let height = (lines.len() as u16 + 2).min(frame.height());
+    if width < 3 || height < 3 {
+        return;
+    }
+    let mut panel = renderer.frame(width, height);
+    for col in 0..width {
+        panel.put(col, 0, glyphs.wall, colors.wall);
+        panel.put(col, height - 1, glyphs.wall, colors.wall);
+    }
+    for row in 0..height {
+        panel.put(0, row, glyphs.wall, colors.wall);
+        panel.put(width - 1, row, glyphs.wall, colors.wall);
+    }
+    panel.print_fg(2, 0, " help ", colors.head);
+    for (row, line) in (1..height - 1).zip(&lines) {
+        let line: String = line.chars().take(width as usize - 4).collect();
+        panel.print(2, row, &line);
+    }
+    frame.blit(
+        &panel,
+        frame.width().saturating_sub(width) / 2,
+        frame.height().saturating_sub(height) / 2,
+    );
+}
+
+/// Keys written out for the help, a long run of them as its ends.
+fn key_list(keys: &[Key]) -> String {
+    match keys {
+        [first, .., last] if keys.len() > 4 => format!("{}-{}", key_name(*first), key_name(*last)),
+        _ => keys
+            .iter()
+            .map(|&key| key_name(key))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn key_name(key: Key) -> String {
+    match key {
+        Key::Char(' ') => "space".to_string(),
+        Key::Char('\n') => "enter".to_string(),
+        Key::Char('\t') => "tab".to_string(),
+        Key::Char(ch) => ch.to_string(),
+        Key::F(n) => format!("F{n}"),
+        Key::Left => "left".to_string(),
+        Key::Right => "right".to_string(),
+        Key::Up => "up".to_string(),
+        Key::Down => "down".to_string(),
+        Key::Delete => "del".to_string(),
+        Key::Ctrl(ch) => format!("ctrl-{ch}"),
+        key => format!("{key:?}").to_lowercase(),
+    }
+}
+
+/// The rules `game` is being played by that differ from a plain run.
+fn rules(game: &Game) -> Vec<String> {
+    let mut rules = Vec::new();
+    if game.difficulty != Difficulty::Normal {
+        rules.push(format!("{} difficulty", game.difficulty.name()));
+    }
+    if game.practice {
+        rules.push("practice: b to edit the board, nothing counts".to_string());
+    }
+    if game.rules.is_zen() {
+        rules.push("nothing can end the run".to_string());
+    } else if game.rules.pass_through {
+        rules.push("the snake passes through all but the edges".to_string());
+    }
+    if game.rules.wraps && !game.rules.is_zen() {
+        rules.push("the walls wrap round".to_string());
+    }
+    if let Some(clock) = game.clock {
+        rules.push(format!("score what you can in {clock:.0}s"));
+    }
+    if game.rules.trail {
+        rules.push("the snake leaves a trail and there's no food".to_string());
+    }
+    if game.lives > 1 {
+        rules.push(format!("{} lives", game.lives));
+    }
+    if let Some(hunger) = game.rules.hunger {
+        rules.push(format!("lose a segment every {hunger}s without eating"));
+    }
+    if !game.portals.is_empty() {
+        rules.push(format!("{} pairs of portals", game.portals.len()));
+    }
+    if !game.rules.poison && !game.rules.is_zen() {
+        rules.push("no poison".to_string());
+    }
+    if let Some(closing) = game.rules.closing {
+        rules.push(format!("the walls close in every {closing}s"));
+    }
+    if game.fog.is_some() {
+        rules.push("fog hides anything far from the head".to_string());
+    }
+    match game.movement {
+        MovementRules::Classic => {}
+        MovementRules::Ice => rules.push("ice: turns take a move to bite".to_string()),
+        MovementRules::Analog(degrees) => {
+            rules.push(format!("analog: each turn is {degrees} degrees"))
+        }
+    }
+    if let Some(mission) = &game.mission {
+        rules.push(format!("mission: {}", mission.objective.describe()));
+    }
+    if rules.is_empty() {
+        rules.push("eat, grow, don't crash".to_string());
+    }
+    rules
+}
@@ -0,0 +1,170 @@
+use std::{
+    fs,
+    io,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        mpsc::SyncSender,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use termion::input::TermRead;
+
+use crate::{
+    Commands,
+    logging::log,
+};
+
+/// Somewhere the game's commands come from. Each source runs on its own
+/// thread, sending commands until it runs out of them or the game stops
+/// listening.
+pub trait InputSource: Send {
+    fn run(self: Box<Self>, sender: SyncSender<Commands>);
+}
+
+/// Starts `source` sending to `sender`. The thread isn't waited for: the
+/// keyboard in particular is stuck reading a key until one comes, and the
+/// process ending takes it down anyway.
+pub fn spawn(source: Box<dyn InputSource>, sender: SyncSender<Commands>) {
+    thread::spawn(move || source.run(sender));
+}
+
+/// Whether keys are being typed as text, such as a challenge code, rather
+/// than taken as commands.
+static TYPING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_typing(typing: bool) {
+    TYPING.store(typing, Ordering::Relaxed);
+}
+
+/// Whether a run is being played, where some keys do something else.
+static PLAYING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_playing(playing: bool) {
+    PLAYING.store(playing, Ordering::Relaxed);
+}
+
+/// Keys pressed on the terminal.
+pub struct Keyboard;
+
+impl InputSource for Keyboard {
+    fn run(self: Box<Self>, sender: SyncSender<Commands>) {
+        let mut key_reader = io::stdin().keys();
+        while let Some(Ok(key)) = key_reader.next() {
+            let command = if TYPING.load(Ordering::Relaxed) {
+                Commands::typed(key)
+            } else {
+                Commands::from_key(key, PLAYING.load(Ordering::Relaxed))
+            };
+            let Some(command) = command else {
+                log!(Trace, "ignored key {key:?}");
+                continue;
+            };
+            log!(Debug, "key {key:?}: {command:?}");
+            if sender.send(command).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Commands read from a file, each sent at a set number of seconds after
+/// the game starts. Every line is a time and a command, such as `0.5 right`
+/// or `3 slot 2`; blank lines and anything after a `#` are skipped.
+pub struct Script {
+    steps: Vec<(f64, Commands)>,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Script, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        let mut steps = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let step = Self::parse(line).ok_or_else(|| {
+                format!(
+                    "{path}:{}: expected a time and a command, got `{line}`",
+                    number + 1
+                )
+            })?;
+            steps.push(step);
+        }
+        steps.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(Script { steps })
+    }
+
+    fn parse(line: &str) -> Option<(f64, Commands)> {
+        let (time, command) = line.split_once(char::is_whitespace)?;
+        let time: f64 = time.parse().ok().filter(|&time: &f64| time >= 0.)?;
+        Some((time, parse_command(command)?))
+    }
+}
+
+/// Reads a command named as in a script, such as `right` or `slot 2`.
+pub fn parse_command(text: &str) -> Option<Commands> {
+    let mut words = text.split_whitespace();
+    let command = match (words.next()?, words.next()) {
+        ("right", None) => Commands::RotatePlayer(90_f64.to_radians()),
+        ("left", None) => Commands::RotatePlayer(-90_f64.to_radians()),
+        ("extend", None) => Commands::Extend,
+        ("shrink", None) => Commands::Shrink,
+        ("confirm" | "enter", None) => Commands::Confirm,
+        ("pause", None) => Commands::Pause,
+        ("rewind", None) => Commands::Rewind,
+        ("menu", None) => Commands::Menu,
+        ("achievements", None) => Commands::Achievements,
+        ("chat", None) => Commands::Chat,
+        ("stats", None) => Commands::Stats,
+        ("save", None) => Commands::QuickSave,
+        ("load", None) => Commands::QuickLoad,
+        ("slot", Some(slot)) => Commands::Slot(slot.parse::<usize>().ok()?.checked_sub(1)?),
+        ("delete", None) => Commands::Delete,
+        ("export", None) => Commands::Export,
+        ("screenshot", None) => Commands::Screenshot,
+        ("log", None) => Commands::Log,
+        ("debug", None) => Commands::Debug,
+        ("help", None) => Commands::Help,
+        ("up", None) => Commands::Up,
+        ("down", None) => Commands::Down,
+        ("quit", None) => Commands::Quit,
+        ("yes", None) => Commands::Yes,
+        ("no", None) => Commands::No,
+        ("tutorial", None) => Commands::Tutorial,
+        ("edit", None) => Commands::Edit,
+        ("food", None) => Commands::Food,
+        ("obstacle", None) => Commands::Obstacle,
+        ("spawn", None) => Commands::Spawn,
+        ("challenge", None) => Commands::Challenge,
+        ("modes", None) => Commands::Modes,
+        ("type", Some(text)) if text.chars().count() == 1 => Commands::Type(text.chars().next()?),
+        _ => return None,
+    };
+    if words.next().is_some() {
+        return None;
+    }
+    Some(command)
+}
+
+impl InputSource for Script {
+    fn run(self: Box<Self>, sender: SyncSender<Commands>) {
+        let start = Instant::now();
+        for (time, command) in self.steps {
+            let due = Duration::from_secs_f64(time);
+            thread::sleep(due.saturating_sub(start.elapsed()));
+            log!(Debug, "script at {time}s: {command:?}");
+            if sender.send(command).is_err() {
+                break;
+            }
+        }
+    }
+}
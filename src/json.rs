@@ -0,0 +1,64 @@
+/// `text` as a JSON string literal.
+pub fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// The members of a JSON `object` as written, each key still quoted with its
+/// value's text, or `None` if it isn't an object.
+pub fn fields(object: &str) -> Option<Vec<(&str, &str)>> {
+    let inner = object.trim().strip_prefix('{')?.strip_suffix('}')?;
+    split(inner)
+        .into_iter()
+        .map(|member| {
+            let (key, value) = member.split_at(member.find(':')?);
+            Some((key.trim(), value[1..].trim()))
+        })
+        .collect()
+}
+
+/// The elements of a JSON `array` as written, or `None` if it isn't an
+/// array.
+pub fn elements(array: &str) -> Option<Vec<&str>> {
+    let inner = array.trim().strip_prefix('[')?.strip_suffix(']')?;
+    Some(split(inner).into_iter().map(str::trim).collect())
+}
+
+/// `text` cut at every comma that isn't inside a string, array or object.
+fn split(text: &str) -> Vec<&str> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut parts = Vec::new();
+    let (mut depth, mut quoted, mut escaped, mut start) = (0, false, false, 0);
+    for (index, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if quoted => {}
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
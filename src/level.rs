@@ -0,0 +1,172 @@
+use std::fs;
+
+use crate::{
+    Direction,
+    Vec2,
+};
+
+/// Cells in a level file, one character each.
+const WALL: char = '#';
+const SPAWN: char = 'S';
+/// Starts heading other ways than `S`, which heads right.
+const SPAWN_UP: char = '^';
+const SPAWN_DOWN: char = 'v';
+const SPAWN_LEFT: char = '<';
+const SPAWN_RIGHT: char = '>';
+const SPAWNER: char = 'F';
+const EMPTY: char = '.';
+
+/// A custom arena layout: where the walls are, where the snake starts and
+/// the cells food turns up on.
+///
+/// Levels are kept as plain text, a row of the arena to a line: `#` for a
+/// wall, `S` for where the snake starts, `F` for a food spawner and `.` for
+/// an empty cell. Short lines are padded out with empty cells. The start can
+/// be `^`, `v`, `<` or `>` instead, for the way the snake heads off; `S`
+/// heads right.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub width: u16,
+    pub height: u16,
+    pub walls: Vec<Vec2>,
+    pub spawn: Vec2,
+    /// The way the snake heads off from the start.
+    pub direction: Direction,
+    /// Where food turns up. Anywhere free, if there are none.
+    pub spawners: Vec<Vec2>,
+}
+
+impl Level {
+    /// An empty level `width` by `height` cells, with the snake starting
+    /// partway along the left of the middle row.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            walls: Vec::new(),
+            spawn: Vec2::new((width / 4) as f64, (height / 2) as f64),
+            direction: Direction::Right,
+            spawners: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Level, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        Level::parse(&text).map_err(|err| format!("{path}: {err}"))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|err| format!("{path}: {err}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Level, String> {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 || width > u16::MAX as usize || lines.len() > u16::MAX as usize {
+            return Err("a level needs between one and 65535 rows and columns".to_string());
+        }
+        let mut level = Level::new(width as u16, lines.len() as u16);
+        let mut spawn = None;
+        for (y, line) in lines.iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                let cell = Vec2::new(x as f64, y as f64);
+                match ch {
+                    WALL => level.walls.push(cell),
+                    SPAWNER => level.spawners.push(cell),
+                    SPAWN | SPAWN_UP | SPAWN_DOWN | SPAWN_LEFT | SPAWN_RIGHT if spawn.is_some() => {
+                        return Err(format!("line {}: a second start", y + 1));
+                    }
+                    SPAWN | SPAWN_RIGHT => spawn = Some(cell),
+                    SPAWN_UP | SPAWN_DOWN | SPAWN_LEFT => {
+                        spawn = Some(cell);
+                        level.direction = match ch {
+                            SPAWN_UP => Direction::Up,
+                            SPAWN_DOWN => Direction::Down,
+                            _ => Direction::Left,
+                        };
+                    }
+                    EMPTY | ' ' => {}
+                    _ => return Err(format!("line {}: unknown cell `{ch}`", y + 1)),
+                }
+            }
+        }
+        level.spawn = spawn.ok_or("no start (S) for the snake")?;
+        Ok(level)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                text.push(self.at(Vec2::new(x as f64, y as f64)));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The character for what's on `cell`.
+    fn at(&self, cell: Vec2) -> char {
+        if cell == self.spawn {
+            match self.direction {
+                Direction::Up => SPAWN_UP,
+                Direction::Down => SPAWN_DOWN,
+                Direction::Left => SPAWN_LEFT,
+                Direction::Right => SPAWN,
+            }
+        } else if self.walls.contains(&cell) {
+            WALL
+        } else if self.spawners.contains(&cell) {
+            SPAWNER
+        } else {
+            EMPTY
+        }
+    }
+
+    /// Puts a wall on `cell`, or takes away the one there. The start can't
+    /// be walled over.
+    pub fn toggle_wall(&mut self, cell: Vec2) {
+        if cell == self.spawn {
+            return;
+        }
+        self.spawners.retain(|&other| other != cell);
+        toggle(&mut self.walls, cell);
+    }
+
+    pub fn toggle_spawner(&mut self, cell: Vec2) {
+        if cell == self.spawn {
+            return;
+        }
+        self.walls.retain(|&other| other != cell);
+        toggle(&mut self.spawners, cell);
+    }
+
+    /// Moves the snake's start to `cell`, clearing whatever was there, or
+    /// turns it a quarter clockwise if it's already there.
+    pub fn set_spawn(&mut self, cell: Vec2) {
+        if cell == self.spawn {
+            self.direction = self.direction.clockwise();
+            return;
+        }
+        self.clear(cell);
+        self.spawn = cell;
+    }
+
+    pub fn clear(&mut self, cell: Vec2) {
+        self.walls.retain(|&other| other != cell);
+        self.spawners.retain(|&other| other != cell);
+    }
+}
+
+fn toggle(cells: &mut Vec<Vec2>, cell: Vec2) {
+    match cells.iter().position(|&other| other == cell) {
+        Some(index) => {
+            cells.swap_remove(index);
+        }
+        None => cells.push(cell),
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config,
+    gameover::format_time,
+    heatmap::Heatmap,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+const FILE: &str = "stats.toml";
+
+const PROMPT: &str = "left/right  heatmap    enter  back    q  quit";
+const HEATMAP_PROMPT: &str = "left/right  totals    enter  back    q  quit";
+
+/// Width of the bars charting how runs ended.
+const CHART_WIDTH: usize = 20;
+
+/// Totals over every run ever finished, kept in
+/// `$XDG_DATA_HOME/snake/stats.toml`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games: u32,
+    pub apples: u64,
+    /// Seconds of play.
+    pub playtime: f64,
+    pub longest: usize,
+    pub total_score: u64,
+    /// How many runs ended each way, by the cause's description.
+    pub deaths: BTreeMap<String, u32>,
+    /// Where in the arena runs have ended.
+    pub heatmap: Heatmap,
+    /// Whether the tutorial's been played through, so it isn't started
+    /// again on its own.
+    pub tutorial_done: bool,
+}
+
+impl LifetimeStats {
+    /// Loads the totals so far, treating a missing file as no runs yet.
+    pub fn load() -> Result<LifetimeStats, String> {
+        config::load_data(FILE)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        config::save_data(FILE, self)
+    }
+
+    /// Adds a finished run to the totals.
+    pub fn add(&mut self, score: u32, apples: u32, time: f64, length: usize, cause: &str) {
+        self.games += 1;
+        self.apples += apples as u64;
+        self.playtime += time;
+        self.longest = self.longest.max(length);
+        self.total_score += score as u64;
+        *self.deaths.entry(cause.to_string()).or_default() += 1;
+    }
+
+    pub fn average_score(&self) -> f64 {
+        if self.games == 0 {
+            0.
+        } else {
+            self.total_score as f64 / self.games as f64
+        }
+    }
+}
+
+/// Shows the lifetime totals, with a chart of how runs have ended, or a
+/// heatmap of where.
+#[derive(Debug, Clone)]
+pub struct StatsScreen {
+    pub stats: LifetimeStats,
+    /// Whether the heatmap is showing rather than the totals.
+    heatmap: bool,
+}
+
+impl StatsScreen {
+    pub fn new() -> Self {
+        Self {
+            stats: LifetimeStats::load().unwrap_or_default(),
+            heatmap: false,
+        }
+    }
+
+    /// Switches between the totals and the heatmap.
+    pub fn flip(&mut self) {
+        self.heatmap = !self.heatmap;
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        if self.heatmap {
+            return self.draw_heatmap(renderer, width, height);
+        }
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let stats = &self.stats;
+        let lines = [
+            format!("games played   {}", stats.games),
+            format!("apples eaten   {}", stats.apples),
+            format!("playtime       {}", format_time(stats.playtime)),
+            format!("longest snake  {}", stats.longest),
+            format!("average score  {:.0}", stats.average_score()),
+        ];
+        let most = stats.deaths.values().copied().max().unwrap_or(0).max(1);
+        let label_width = stats.deaths.keys().map(String::len).max().unwrap_or(0);
+        let chart: Vec<String> = stats
+            .deaths
+            .iter()
+            .map(|(cause, &count)| {
+                let bar = renderer.bar(count as f64 / most as f64, CHART_WIDTH);
+                format!("{cause:<label_width$}  {bar} {count}")
+            })
+            .collect();
+        let block_height = lines.len() as u16 + chart.len() as u16 + 6;
+        let mut row = height.saturating_sub(block_height) / 2;
+        frame.print_centered(row, "STATS", colors.head);
+        row += 2;
+        let widest = lines
+            .iter()
+            .chain(&chart)
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let col = width.saturating_sub(widest as u16) / 2;
+        for line in &lines {
+            frame.print(col, row, line);
+            row += 1;
+        }
+        row += 1;
+        for line in &chart {
+            frame.print_fg(col, row, line, colors.food);
+            row += 1;
+        }
+        row += 1;
+        frame.print_centered(row, PROMPT, colors.wall);
+        frame
+    }
+
+    fn draw_heatmap(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let heatmap = &self.stats.heatmap;
+        let mut row = height.saturating_sub(heatmap.height() + 6) / 2;
+        frame.print_centered(row, "WHERE RUNS END", colors.head);
+        row += 2;
+        heatmap.draw(
+            renderer,
+            &mut frame,
+            width.saturating_sub(heatmap.width()) / 2,
+            row,
+        );
+        row += heatmap.height() + 1;
+        frame.print_centered(row, &heatmap.legend(renderer), colors.wall);
+        row += 2;
+        frame.print_centered(row, HEATMAP_PROMPT, colors.wall);
+        frame
+    }
+}
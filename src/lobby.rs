@@ -0,0 +1,240 @@
+use crate::{
+    Options,
+    game::player_tint,
+    json,
+    menu::Menu,
+    players::Player,
+    render::{
+        Frame,
+        Renderer,
+    },
+    rules::{
+        Rule,
+        Ruleset,
+    },
+    versus,
+};
+
+/// Arena sizes the host can pick between, with what they're called. `None`
+/// is whatever size the game would otherwise have.
+const ARENAS: [(&str, Option<(u16, u16)>); 4] = [
+    ("default", None),
+    ("small", Some((24, 12))),
+    ("medium", Some((40, 20))),
+    ("large", Some((60, 30))),
+];
+
+/// Most rounds a versus match can be played first to.
+const MOST_ROUNDS: u32 = 9;
+
+/// Something the host can change in the lobby.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    /// Co-op or versus.
+    Mode,
+    /// Rounds to win a versus match.
+    Rounds,
+    Arena,
+    Rule(Rule),
+}
+
+/// Where both players meet before a game for two. The first player hosts,
+/// setting the mode, rules and arena with the arrows; each then says
+/// they're ready with their own keys, and the game starts once both are.
+/// Changing anything takes back whoever was ready.
+#[derive(Clone, Debug)]
+pub struct LobbyScreen {
+    /// Rounds a versus match is played first to, or `None` for co-op.
+    pub versus: Option<u32>,
+    pub rules: Ruleset,
+    /// Which of `ARENAS` is picked.
+    arena: usize,
+    pub players: [Player; 2],
+    pub ready: [bool; 2],
+    pub menu: Menu<Setting>,
+}
+
+impl LobbyScreen {
+    pub fn new(versus: Option<u32>, rules: Ruleset, players: [Player; 2]) -> Self {
+        let mut items = vec![
+            (Setting::Mode, "mode"),
+            (Setting::Rounds, "rounds"),
+            (Setting::Arena, "arena"),
+        ];
+        items.extend(Rule::ALL.map(|rule| (Setting::Rule(rule), rule.name())));
+        Self {
+            versus,
+            rules,
+            arena: 0,
+            players,
+            ready: [false; 2],
+            menu: Menu::new(items),
+        }
+    }
+
+    /// Changes the selected setting a step `forward` or back, taking back
+    /// both players being ready.
+    pub fn change(&mut self, forward: bool) {
+        let Some(setting) = self.menu.chosen() else {
+            return;
+        };
+        match setting {
+            Setting::Mode => {
+                self.versus = match self.versus {
+                    Some(_) => None,
+                    None => Some(versus::FIRST_TO),
+                }
+            }
+            Setting::Rounds => {
+                let Some(first_to) = &mut self.versus else {
+                    return;
+                };
+                *first_to = if forward {
+                    (*first_to % MOST_ROUNDS) + 1
+                } else {
+                    (*first_to + MOST_ROUNDS - 2) % MOST_ROUNDS + 1
+                };
+            }
+            Setting::Arena => {
+                let len = ARENAS.len();
+                self.arena = if forward {
+                    (self.arena + 1) % len
+                } else {
+                    (self.arena + len - 1) % len
+                };
+            }
+            Setting::Rule(rule) => self.rules.toggle(rule),
+        }
+        self.ready = [false; 2];
+    }
+
+    /// Switches whether `player`, counting from 0, is ready.
+    pub fn toggle_ready(&mut self, player: usize) {
+        if let Some(ready) = self.ready.get_mut(player) {
+            *ready = !*ready;
+        }
+    }
+
+    /// Whether both players are ready to start.
+    pub fn all_ready(&self) -> bool {
+        self.ready.iter().all(|&ready| ready)
+    }
+
+    /// `options` with everything set in the lobby, to start the game with.
+    pub fn options(&self, options: &Options) -> Options {
+        let (width, height) = match ARENAS[self.arena].1 {
+            Some((width, height)) => (Some(width), Some(height)),
+            None => (options.width, options.height),
+        };
+        Options {
+            rules: self.rules,
+            coop: self.versus.is_none(),
+            versus: self.versus,
+            width,
+            height,
+            players: self.players.clone(),
+            ..options.clone()
+        }
+    }
+
+    fn mode_name(&self) -> &'static str {
+        match self.versus {
+            Some(_) => "versus",
+            None => "co-op",
+        }
+    }
+
+    /// The lobby as a line of JSON, for control clients.
+    pub fn to_json(&self) -> String {
+        let rules: Vec<String> = Rule::ALL
+            .into_iter()
+            .filter(|&rule| self.rules.has(rule))
+            .map(|rule| json::string(rule.name()))
+            .collect();
+        let players: Vec<String> = self
+            .players
+            .iter()
+            .zip(self.ready)
+            .map(|(player, ready)| {
+                format!(
+                    r#"{{"name": {}, "ready": {ready}}}"#,
+                    json::string(&player.name)
+                )
+            })
+            .collect();
+        let first_to = self
+            .versus
+            .map_or("null".to_string(), |first_to| first_to.to_string());
+        format!(
+            r#"{{"lobby": {{"mode": {}, "first_to": {first_to}, "arena": {}, "rules": [{}], "players": [{}]}}}}"#,
+            json::string(self.mode_name()),
+            json::string(ARENAS[self.arena].0),
+            rules.join(", "),
+            players.join(", "),
+        )
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let rows = self.menu.height() + 9;
+        let mut row = height.saturating_sub(rows) / 2;
+        frame.print_centered(row, "LOBBY", colors.head);
+        row += 2;
+        let name_width = Rule::ALL
+            .iter()
+            .map(|rule| rule.name().len())
+            .max()
+            .unwrap_or(0);
+        let mut settings = vec![
+            (Setting::Mode, "mode", self.mode_name().to_string()),
+            (
+                Setting::Rounds,
+                "rounds",
+                self.versus
+                    .map_or("-".to_string(), |first_to| format!("first to {first_to}")),
+            ),
+            (Setting::Arena, "arena", ARENAS[self.arena].0.to_string()),
+        ];
+        for rule in Rule::ALL {
+            let state = if self.rules.has(rule) { "on" } else { "off" };
+            settings.push((Setting::Rule(rule), rule.name(), state.to_string()));
+        }
+        for (setting, name, value) in settings {
+            let selected = self.menu.chosen() == Some(setting);
+            let (marker, fg) = if selected {
+                ('>', colors.head)
+            } else {
+                (' ', colors.wall)
+            };
+            let line = format!("{marker} {name:<name_width$}  {value:<11}");
+            frame.print_centered(row, &line, fg);
+            row += 1;
+        }
+        row += 1;
+        for (index, player) in self.players.iter().enumerate() {
+            let (state, fg) = if self.ready[index] {
+                ("ready", colors.bonus)
+            } else {
+                ("waiting", colors.wall)
+            };
+            let name =
+                player_tint(renderer, index, player.tint()).map_or(colors.head, |tint| tint.0);
+            let col = width.saturating_sub(name_width as u16 + 15) / 2 + 2;
+            frame.print_fg(col, row, &player.name, name);
+            frame.print_fg(col + name_width as u16 + 2, row, state, fg);
+            row += 1;
+        }
+        frame.print_centered(
+            row + 1,
+            "up/down  choose   left/right  change   m  back",
+            colors.wall,
+        );
+        frame.print_centered(
+            row + 2,
+            "ready: enter for the 1st player, a/d for the 2nd",
+            colors.wall,
+        );
+        frame
+    }
+}
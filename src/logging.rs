@@ -0,0 +1,112 @@
+use std::{
+    fmt,
+    fs::{
+        self,
+        File,
+        OpenOptions,
+    },
+    io::Write,
+    sync::{
+        Mutex,
+        OnceLock,
+    },
+    time::Instant,
+};
+
+use crate::config;
+
+/// The log, under the data directory. Never the terminal, which is taken up
+/// by the game.
+const FILE: &str = "snake.log";
+
+/// How much goes in the log, from only errors up to everything.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+struct Logger {
+    level: Level,
+    started: Instant,
+    file: Mutex<File>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Starts logging everything at `level` and above, appending to the log
+/// file. Until this is called nothing is logged.
+pub fn init(level: Level) -> Result<(), String> {
+    let dir = config::data_dir().ok_or("no data directory to log to")?;
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let path = dir.join(FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    let logger = Logger {
+        level,
+        started: Instant::now(),
+        file: Mutex::new(file),
+    };
+    // Only ever set once, from main.
+    let _ = LOGGER.set(logger);
+    Ok(())
+}
+
+pub fn enabled(level: Level) -> bool {
+    LOGGER.get().is_some_and(|logger| level <= logger.level)
+}
+
+/// Appends a line to the log, stamped with the seconds since logging
+/// started. Use `log!` rather than calling this directly, so the message
+/// isn't formatted when it won't be kept.
+pub fn write(level: Level, message: fmt::Arguments) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    let time = logger.started.elapsed().as_secs_f64();
+    if let Ok(mut file) = logger.file.lock() {
+        // A log that can't be written to is no reason to stop the game.
+        let _ = writeln!(file, "{time:10.3} {:5} {message}", level.label());
+    }
+}
+
+/// Logs a formatted message at the level named, such as
+/// `log!(Info, "started run {seed}")`.
+macro_rules! log {
+    ($level:ident, $($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::$level) {
+            $crate::logging::write($crate::logging::Level::$level, format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use log;
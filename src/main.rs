@@ -1,108 +1,2616 @@
 #![allow(dead_code)]
+mod achievements;
+mod adaptive;
+mod agent;
+mod anim;
+mod announce;
+mod bench;
+mod camera;
+mod caps;
+mod challenge;
+mod chat;
+mod config;
+mod console;
+mod control;
+mod death;
+mod deathcam;
+mod debug;
+mod delta;
+mod difficulty;
+mod editor;
+mod effects;
+mod entity;
+mod error;
+mod events;
+mod food;
+mod fuzz;
+mod game;
+mod gameover;
+mod golden;
+mod heatmap;
+mod help;
+mod input;
+mod json;
+mod level;
+mod lifetime;
+mod lobby;
+mod logging;
+mod menu;
+mod minimap;
+mod mission;
+mod modes;
+mod movement;
+#[cfg(feature = "music")]
+mod music;
+mod osc;
+mod players;
+mod power;
+mod practice;
+mod profile;
+mod render;
+mod replay;
+mod rng;
+mod rules;
+mod save;
+mod scores;
+mod screenshot;
+mod settings;
+mod sound;
+mod spatial;
+mod speedrun;
+mod stream;
+mod terminal;
+mod theme;
+mod title;
+mod toast;
+mod tournament;
+mod tutorial;
+mod versus;
+#[cfg(feature = "ws")]
+mod ws;
+
 use std::{
-    collections::LinkedList,
+    env,
+    fs::File,
     io::{
         self,
-        Stdout,
         Write,
     },
     ops,
+    path::Path,
+    process,
     sync::mpsc::{
         self,
         Receiver,
-        SyncSender,
     },
     thread,
     time::{
         Duration,
         Instant,
+        SystemTime,
+        UNIX_EPOCH,
     },
 };
 
-use termion::{
-    event::Key,
-    input::TermRead,
-    raw::IntoRawMode,
-    screen::IntoAlternateScreen,
-    terminal_size,
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use termion::event::Key;
+
+use crate::{
+    achievements::{
+        Achievements,
+        AchievementsScreen,
+    },
+    adaptive::Adaptive,
+    agent::Agent,
+    announce::Announcer,
+    camera::{
+        Camera,
+        CameraMode,
+    },
+    caps::Capabilities,
+    challenge::{
+        Challenge,
+        ChallengeScreen,
+    },
+    chat::Chat,
+    config::Config,
+    console::{
+        Cheat,
+        Console,
+    },
+    control::{
+        ControlSocket,
+        Features,
+        Request,
+        Seats,
+    },
+    death::DeathScreen,
+    debug::DebugOverlay,
+    difficulty::Difficulty,
+    editor::EditorScreen,
+    error::{
+        SnakeError,
+        terminal_size,
+    },
+    events::GameEvent,
+    food::FoodRules,
+    game::{
+        CORNER_GRACE,
+        Game,
+        HUD_ROWS,
+        Start,
+        TIME_ATTACK,
+    },
+    gameover::GameOverScreen,
+    input::{
+        Keyboard,
+        Script,
+    },
+    lifetime::{
+        LifetimeStats,
+        StatsScreen,
+    },
+    lobby::LobbyScreen,
+    logging::{
+        Level,
+        log,
+    },
+    menu::Menu,
+    modes::ModeScreen,
+    movement::{
+        DEFAULT_STEER,
+        MovementRules,
+    },
+    players::{
+        Player,
+        default_players,
+    },
+    profile::{
+        FrameTime,
+        Profile,
+    },
+    render::{
+        Frame,
+        RenderMode,
+        Renderer,
+    },
+    replay::{
+        Input,
+        Replay,
+    },
+    rng::Rng,
+    rules::{
+        RulesScreen,
+        Ruleset,
+    },
+    save::{
+        LoadScreen,
+        SLOTS,
+    },
+    scores::HighScores,
+    settings::SettingsScreen,
+    sound::{
+        Cues,
+        Sound,
+        Sounds,
+    },
+    speedrun::Speedrun,
+    stream::StateStream,
+    terminal::Terminal,
+    theme::{
+        BUILTIN_THEMES,
+        Theme,
+    },
+    title::TitleScreen,
+    toast::{
+        ToastKind,
+        Toasts,
+    },
+    tournament::{
+        Bracket,
+        TournamentScreen,
+    },
+    tutorial::Lesson,
+};
+#[cfg(feature = "music")]
+use crate::{
+    game::TICKS_PER_SECOND,
+    music::{
+        Music,
+        Track,
+    },
 };
 
 fn main() {
-    thread::scope(|scope| {
-        let (sender, reciever) = mpsc::sync_channel(0);
-        scope.spawn(|| game_loop(reciever));
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "render") {
+        if let Err(err) = render_frame(&args[1..]) {
+            eprintln!("snake: {err}");
+            eprintln!("usage: snake render <replay> --frame <n> [options]");
+            process::exit(2);
+        }
+        return;
+    }
+    if args.first().is_some_and(|arg| arg == "export-cast") {
+        if let Err(err) = export_cast(&args[1..]) {
+            eprintln!("snake: {err}");
+            eprintln!(
+                "usage: snake export-cast <replay> <out.cast> [--playback <speed>] [options]"
+            );
+            process::exit(2);
+        }
+        return;
+    }
+    if args.first().is_some_and(|arg| arg == "bench") {
+        if let Err(err) = bench(&args[1..]) {
+            eprintln!("snake: {err}");
+            eprintln!("usage: snake bench [options]");
+            process::exit(2);
+        }
+        return;
+    }
+    if args.first().is_some_and(|arg| arg == "fuzz") {
+        match fuzz(&args[1..]) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(err) => {
+                eprintln!("snake: {err}");
+                eprintln!("usage: snake fuzz [--seeds <n>] [--seed <n>] [--frames <n>]");
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if args.first().is_some_and(|arg| arg == "golden") {
+        match golden(&args[1..]) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(err) => {
+                eprintln!("snake: {err}");
+                eprintln!("usage: snake golden [--update] [--dir <path>]");
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if args.first().is_some_and(|arg| arg == "control") {
+        let result = match &args[1..] {
+            [] => Err("control needs a socket".to_string()),
+            [socket] => control::client(socket, io::stdin().lines().map_while(Result::ok)),
+            [socket, request @ ..] => control::client(socket, std::iter::once(request.join(" "))),
+        };
+        if let Err(err) = result {
+            eprintln!("snake: {err}");
+            eprintln!("usage: snake control <socket> [request]");
+            process::exit(2);
+        }
+        return;
+    }
+    let options = match Config::load()
+        .and_then(|config| Options::from_args(args.into_iter(), &config))
+    {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("snake: {err}");
+            eprintln!(
+                "usage: snake [--render block|braille] [--theme <name>] [--ascii] [--smooth] [--reduced-motion] [--slow-motion] [--seed <n>]\n       [--stretch] [--scale <1-4>] [--food <n>] [--lives <n>] [--speed <cells/s>] [--corner-grace <ms>]\n       [--length <n>] [--spawn <x>,<y>] [--direction up|down|left|right] [--hunger <secs>] [--portals <pairs>]\n       [--closing <secs>] [--fog <radius>] [--trail] [--wrap] [--no-poison] [--zen] [--coop] [--versus <rounds>] [--player <name>[:<color>]] [--time-attack] [--mission]\n       [--movement classic|ice|analog] [--steer <degrees>]\n       [--log-level error|warn|info|debug|trace] [--script <file>]\n       [--volume <0-1>] [--mute] [--music <dir>] [--audio-cues] [--emit-state <path|fd>] [--announce <path|fd>] [--control <socket>] [--ws-port <port>] [--agent-cmd <program>]\n       [--width <cells>] [--height <cells>] [--camera deadzone|centered]\n       [--difficulty easy|normal|hard|insane] [--adaptive] [--tutorial] [--practice]\n       [--level <file>] [--edit <file>] [--speedrun] [--challenge <code>] [--dev]
+       [--profile <csv>]"
+            );
+            eprintln!("built-in themes: {}", BUILTIN_THEMES.join(", "));
+            process::exit(2);
+        }
+    };
+    if let Some(level) = options.log_level
+        && let Err(err) = logging::init(level)
+    {
+        eprintln!("snake: couldn't open the log: {err}");
+        process::exit(1);
+    }
+    log!(Info, "starting snake {}", env!("CARGO_PKG_VERSION"));
+    // Opened before the terminal's taken over, since a FIFO waits here for
+    // a reader.
+    let mut streams = match options
+        .emit_state
+        .as_deref()
+        .map(StateStream::open)
+        .transpose()
+    {
+        Ok(stream) => Vec::from_iter(stream),
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    };
+    #[cfg(feature = "ws")]
+    match options.ws_port.map(ws::Spectators::listen).transpose() {
+        Ok(spectators) => streams.extend(spectators.map(StateStream::new)),
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    }
+    #[cfg(not(feature = "ws"))]
+    if options.ws_port.is_some() {
+        eprintln!("snake: --ws-port needs snake built with the `ws` feature");
+        process::exit(2);
+    }
+    #[cfg(not(feature = "music"))]
+    if options.music.is_some() {
+        eprintln!("snake: --music needs snake built with the `music` feature");
+        process::exit(2);
+    }
+    let announcer = match options.announce.as_deref().map(Announcer::open).transpose() {
+        Ok(announcer) => announcer,
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    };
+    let control = match options
+        .control
+        .as_deref()
+        .map(ControlSocket::bind)
+        .transpose()
+    {
+        Ok(control) => control,
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    };
+    let script = match options.script.as_deref().map(Script::load).transpose() {
+        Ok(script) => script,
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    };
+    let (sender, reciever) = mpsc::sync_channel(0);
+    let agent = match options.agent_cmd.as_deref().map(Agent::spawn).transpose() {
+        Ok(Some((agent, observations, actions))) => {
+            streams.push(observations);
+            input::spawn(Box::new(actions), sender.clone());
+            Some(agent)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            eprintln!("snake: {err}");
+            process::exit(2);
+        }
+    };
+    // The keyboard stays on alongside a script, so it can still be quit.
+    if let Some(script) = script {
+        input::spawn(Box::new(script), sender.clone());
+    }
+    input::spawn(Box::new(Keyboard), sender);
+    let result = game_loop(reciever, options, streams, announcer, control);
+    drop(agent);
+    if let Err(err) = result {
+        eprintln!("snake: {err}");
+        process::exit(1);
+    }
+}
 
-        scope.spawn(|| handle_input(sender));
-    });
+/// Renders a saved replay to an asciinema cast, drawn with the render
+/// options given after the two paths, played back at `--playback` times
+/// the speed it was played at.
+fn export_cast(args: &[String]) -> Result<(), String> {
+    let [replay, out, rest @ ..] = args else {
+        return Err("export-cast needs a replay and a file to write".to_string());
+    };
+    let mut playback = 1.;
+    let mut rest = rest.iter().cloned();
+    let mut options = Vec::new();
+    while let Some(arg) = rest.next() {
+        if arg == "--playback" {
+            let value = rest.next().ok_or("--playback needs a value")?;
+            playback = value
+                .parse()
+                .ok()
+                .filter(|&speed: &f64| speed > 0. && speed.is_finite())
+                .ok_or_else(|| format!("invalid playback speed `{value}`"))?;
+        } else {
+            options.push(arg);
+        }
+    }
+    let config = Config::load()?;
+    let options = Options::from_args(options.into_iter(), &config)?;
+    let replay = Replay::load(replay)?;
+    let mut file = File::create(out).map_err(|err| format!("{out}: {err}"))?;
+    replay.export_cast(&make_renderer(&options), playback, &mut file)
+}
+
+/// Saves one frame of a replay as plain and coloured text, drawn with the
+/// render options given alongside `--frame`.
+fn render_frame(args: &[String]) -> Result<(), String> {
+    let [replay, rest @ ..] = args else {
+        return Err("render needs a replay".to_string());
+    };
+    let mut index = None;
+    let mut rest = rest.iter().cloned();
+    let mut options = Vec::new();
+    while let Some(arg) = rest.next() {
+        if arg == "--frame" {
+            let value = rest.next().ok_or("--frame needs a value")?;
+            index = Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid frame `{value}`"))?,
+            );
+        } else {
+            options.push(arg);
+        }
+    }
+    let index = index.ok_or("render needs --frame")?;
+    let config = Config::load()?;
+    let options = Options::from_args(options.into_iter(), &config)?;
+    let replay = Replay::load(replay)?;
+    let renderer = make_renderer(&options);
+    let frame = replay.frame(&renderer, index)?;
+    let base = format!("snake-{}-frame-{index}", replay.start.seed);
+    let path = screenshot::save(&frame, renderer.caps.color, &base)?;
+    println!("saved {path} and {base}.ans");
+    Ok(())
+}
+
+/// Times the core loop under heavy loads and prints how fast it went, drawn
+/// with the render options given.
+fn bench(args: &[String]) -> Result<(), String> {
+    let config = Config::load()?;
+    let options = Options::from_args(args.iter().cloned(), &config)?;
+    let renderer = make_renderer(&options);
+    for case in bench::run(&renderer) {
+        println!("{:<18} {:>12.0} {}", case.name, case.rate, case.unit);
+    }
+    Ok(())
 }
 
-fn handle_input(sender: SyncSender<Commands>) {
-    let mut key_reader = io::stdin().keys();
-    while let Some(Ok(key)) = key_reader.next() {
-        let Some(command) = Commands::from_key(key) else {
-            continue;
+/// Plays runs with random input, checking the game's invariants hold
+/// throughout. Returns whether they did, having said which broke if not.
+fn fuzz(args: &[String]) -> Result<bool, String> {
+    let mut seeds = 0..1000;
+    let mut frames = 2000;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            let value = args.next().ok_or(format!("{arg} needs a value"))?;
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("invalid number `{value}`"))
         };
-        if sender.send(command).is_err() || matches!(command, Commands::Quit) {
-            break;
+        match arg.as_str() {
+            "--seeds" => seeds = 0..value()?,
+            "--seed" => {
+                let seed = value()?;
+                seeds = seed..seed + 1;
+            }
+            "--frames" => frames = value()? as usize,
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    match fuzz::run(seeds, frames) {
+        Ok(runs) => {
+            println!("{runs} runs of up to {frames} frames, no invariants broken");
+            Ok(true)
+        }
+        Err(failure) => {
+            println!(
+                "seed {} frame {}: {}",
+                failure.seed, failure.frame, failure.what
+            );
+            Ok(false)
         }
     }
 }
 
-fn game_loop(reciever: Receiver<Commands>) {
-    let mut stdout = io::stdout()
-        .into_raw_mode()
-        .unwrap()
-        .into_alternate_screen()
-        .unwrap();
-    let mut game = Game::new();
+/// Checks the HUD, menus and arena are drawn as they were when the golden
+/// frames were last written, or writes them afresh with `--update`. Returns
+/// whether every frame matched.
+fn golden(args: &[String]) -> Result<bool, String> {
+    let mut dir = golden::GOLDEN_DIR.to_string();
+    let mut update = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--update" => update = true,
+            "--dir" => dir = args.next().ok_or("--dir needs a value")?.clone(),
+            _ => return Err(format!("unknown option `{arg}`")),
+        }
+    }
+    let outcomes = golden::run(&dir, update)?;
+    let mut matched = true;
+    for (name, outcome) in &outcomes {
+        match outcome {
+            golden::Outcome::Same => {}
+            golden::Outcome::Changed(line) => {
+                println!("changed  {name} (from line {line})");
+                matched = false;
+            }
+            golden::Outcome::Missing => {
+                println!("missing  {name}");
+                matched = false;
+            }
+            golden::Outcome::Updated => println!("wrote    {name}"),
+        }
+    }
+    if matched && !update {
+        println!("{} frames match", outcomes.len());
+    }
+    Ok(matched)
+}
+
+/// A name for a screenshot taken now, without the extension.
+fn screenshot_name() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    format!("snake-{secs}")
+}
+
+/// The renderer for `options` on the terminal we're running in.
+fn make_renderer(options: &Options) -> Renderer {
+    let mut caps = Capabilities::probe();
+    if let Some(ascii) = options.ascii {
+        caps.unicode = !ascii;
+        caps.braille &= !ascii;
+    }
+    let mut renderer = Renderer::new(
+        options.render_mode,
+        options.theme.clone(),
+        caps,
+        options.square,
+    );
+    renderer.reduced_motion = options.reduced_motion;
+    renderer.scale = options.scale;
+    renderer
+}
+
+#[derive(Clone, Debug)]
+struct Options {
+    render_mode: RenderMode,
+    theme: Theme,
+    /// Forces ASCII glyphs on or off instead of trusting the terminal probe.
+    ascii: Option<bool>,
+    /// Seed for every run, instead of a fresh one each time.
+    seed: Option<u64>,
+    resume_countdown: bool,
+    /// Replay the last moments in slow motion after dying.
+    death_replay: bool,
+    /// Shake the arena when the snake crashes, unless the motion's reduced.
+    screen_shake: bool,
+    /// Keep the window title up to date with the game.
+    window_title: bool,
+    /// Send desktop notifications on new personal bests.
+    notifications: bool,
+    /// Keep the screen as still as possible.
+    reduced_motion: bool,
+    /// Slide the snake between cells, drawing at a higher frame rate.
+    smooth: bool,
+    /// Slow time down for a moment after a near miss at speed.
+    slow_motion: bool,
+    /// Arena size in cells, where it shouldn't just fit the terminal.
+    width: Option<u16>,
+    height: Option<u16>,
+    camera: CameraMode,
+    difficulty: Difficulty,
+    /// Nudge the difficulty to suit how the player's doing.
+    adaptive: bool,
+    /// Whether to start with the tutorial, or only until it's been done if
+    /// unset.
+    tutorial: Option<bool>,
+    /// Let the board be edited, for practising.
+    practice: bool,
+    /// Time runs with splits against personal bests.
+    speedrun: bool,
+    /// Developer tools: the cheat console. Always on in debug builds.
+    dev: bool,
+    /// Where to write how long every frame took, once the game's quit.
+    profile: Option<String>,
+    /// A custom level to play on.
+    level: Option<level::Level>,
+    /// A level file to open in the editor, rather than starting at the title.
+    edit: Option<String>,
+    /// Keep block-mode cells square rather than one terminal cell each.
+    square: bool,
+    /// How many times over the arena is blown up.
+    scale: u16,
+    food: FoodRules,
+    lives: u32,
+    /// Cells a second the snake moves on normal difficulty, if not the
+    /// usual.
+    speed: Option<f64>,
+    /// Seconds after a move that a turn is still taken as made before it.
+    corner_grace: f64,
+    /// How the snake is laid out at the start of a run.
+    start: Start,
+    /// Which of the rules that can be mixed and matched are on.
+    rules: Ruleset,
+    /// Pairs of portals to put in the arena.
+    portals: usize,
+    /// Radius seen through the fog at the start, if there's fog.
+    fog: Option<f64>,
+    /// A second snake on the same keyboard, sharing the score and lives.
+    coop: bool,
+    /// A second snake on the same keyboard playing against the first, in a
+    /// match to this many rounds.
+    versus: Option<u32>,
+    /// Who's playing, when there are two snakes.
+    players: [Player; 2],
+    /// Give the run a mission.
+    mission: bool,
+    movement: MovementRules,
+    /// Sound effect volume from 0 to 1.
+    volume: f64,
+    mute: bool,
+    /// Where the background music is, if there is any.
+    music: Option<String>,
+    /// Play sounds describing the board.
+    audio_cues: bool,
+    /// How much to write to the log file, if anything.
+    log_level: Option<Level>,
+    /// A file of timed commands to play.
+    script: Option<String>,
+    /// Where to stream the game state to, a path or file descriptor.
+    emit_state: Option<String>,
+    /// Where to write announcements for a screen reader to.
+    announce: Option<String>,
+    /// Where to listen for requests from other programs.
+    control: Option<String>,
+    /// The port to stream the game to browsers from, with the `ws`
+    /// feature.
+    ws_port: Option<u16>,
+    /// A bot to play the game, run through the shell.
+    agent_cmd: Option<String>,
+}
+
+impl Options {
+    fn from_args(
+        mut args: impl Iterator<Item = String>,
+        config: &Config,
+    ) -> Result<Options, String> {
+        let mut render = config.render.clone();
+        let mut theme = config
+            .theme
+            .clone()
+            .unwrap_or_else(|| "classic".to_string());
+        let mut ascii = config.ascii;
+        let mut smooth = config.smooth.unwrap_or(false);
+        let mut reduced_motion = config.reduced_motion.unwrap_or(false);
+        let mut slow_motion = config.slow_motion.unwrap_or(false);
+        let mut square = config.square.unwrap_or(true);
+        let mut scale = config.scale.unwrap_or(1);
+        let mut seed = None;
+        let mut food = config.food;
+        let mut lives = config.lives.unwrap_or(1);
+        let mut speed = config.speed;
+        let mut corner_grace = config
+            .corner_grace
+            .map_or(CORNER_GRACE, |ms| ms as f64 / 1000.);
+        let mut length = config.length;
+        let mut spawn = config.spawn.map(|[x, y]| Vec2::new(x as f64, y as f64));
+        let mut direction = config.direction.clone();
+        let mut hunger = config.hunger;
+        let mut portals = config.portals.unwrap_or(0);
+        let mut closing = config.closing;
+        let mut fog = config.fog;
+        let mut trail = config.trail.unwrap_or(false);
+        let mut wraps = config.wraps.unwrap_or(false);
+        let mut poison = config.poison.unwrap_or(true);
+        let mut zen = config.zen.unwrap_or(false);
+        let mut coop = config.coop.unwrap_or(false);
+        let mut versus = config.versus;
+        let mut players = default_players();
+        for (index, player) in config.players.iter().flatten().take(2).enumerate() {
+            player.check()?;
+            players[index] = player.clone();
+        }
+        let mut named = 0;
+        let mut time_attack = config.time_attack.unwrap_or(false);
+        let mut mission = config.mission.unwrap_or(false);
+        let mut steer = config.steer.unwrap_or(DEFAULT_STEER);
+        let mut width = config.width;
+        let mut height = config.height;
+        let mut camera = config
+            .camera
+            .clone()
+            .unwrap_or_else(|| "deadzone".to_string());
+        let mut difficulty = config
+            .difficulty
+            .clone()
+            .unwrap_or_else(|| "normal".to_string());
+        let mut adaptive = config.adaptive.unwrap_or(false);
+        let mut tutorial = config.tutorial;
+        let mut practice = config.practice.unwrap_or(false);
+        let mut speedrun = config.speedrun.unwrap_or(false);
+        let mut dev = cfg!(debug_assertions);
+        let mut profile = None;
+        let mut challenge = None;
+        let mut level = None;
+        let mut edit = None;
+        let mut movement = config
+            .movement
+            .clone()
+            .unwrap_or_else(|| "classic".to_string());
+        let mut volume = config.volume.unwrap_or(1.);
+        let mut mute = config.mute.unwrap_or(false);
+        let mut music = config.music.clone();
+        let mut audio_cues = config.audio_cues.unwrap_or(false);
+        let mut log_level = config.log_level.clone();
+        let mut script = None;
+        let mut emit_state = None;
+        let mut control = None;
+        let mut ws_port = None;
+        let mut announce = None;
+        let mut agent_cmd = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--render" => render = Some(args.next().ok_or("--render needs a value")?),
+                "--theme" => theme = args.next().ok_or("--theme needs a value")?,
+                "--ascii" => ascii = Some(true),
+                "--smooth" => smooth = true,
+                "--reduced-motion" => reduced_motion = true,
+                "--slow-motion" => slow_motion = true,
+                "--trail" => trail = true,
+                "--wrap" => wraps = true,
+                "--no-poison" => poison = false,
+                "--zen" => zen = true,
+                "--coop" => coop = true,
+                "--time-attack" => time_attack = true,
+                "--mission" => mission = true,
+                "--stretch" => square = false,
+                "--scale" => {
+                    let value = args.next().ok_or("--scale needs a value")?;
+                    scale = value
+                        .parse()
+                        .ok()
+                        .filter(|&scale| (1..=4).contains(&scale))
+                        .ok_or_else(|| format!("invalid scale `{value}`"))?;
+                }
+                "--mute" => mute = true,
+                "--audio-cues" => audio_cues = true,
+                "--adaptive" => adaptive = true,
+                "--tutorial" => tutorial = Some(true),
+                "--practice" => practice = true,
+                "--speedrun" => speedrun = true,
+                "--dev" => dev = true,
+                "--profile" => profile = Some(args.next().ok_or("--profile needs a value")?),
+                "--challenge" => {
+                    let code = args.next().ok_or("--challenge needs a value")?;
+                    challenge = Some(Challenge::decode(&code)?);
+                }
+                "--level" => {
+                    let path = args.next().ok_or("--level needs a value")?;
+                    level = Some(level::Level::load(&path)?);
+                }
+                "--edit" => {
+                    let path = args.next().ok_or("--edit needs a value")?;
+                    if Path::new(&path).exists() {
+                        level = Some(level::Level::load(&path)?);
+                    }
+                    edit = Some(path);
+                }
+                "--music" => music = Some(args.next().ok_or("--music needs a value")?),
+                "--volume" => {
+                    let value = args.next().ok_or("--volume needs a value")?;
+                    volume = value
+                        .parse()
+                        .ok()
+                        .filter(|volume| (0. ..=1.).contains(volume))
+                        .ok_or_else(|| format!("invalid volume `{value}`"))?;
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid seed `{value}`"))?,
+                    );
+                }
+                "--width" | "--height" => {
+                    let value = args.next().ok_or(format!("{arg} needs a value"))?;
+                    let size = value
+                        .parse()
+                        .ok()
+                        .filter(|&size| size > 0)
+                        .ok_or_else(|| format!("invalid arena size `{value}`"))?;
+                    if arg == "--width" {
+                        width = Some(size);
+                    } else {
+                        height = Some(size);
+                    }
+                }
+                "--food" => {
+                    let value = args.next().ok_or("--food needs a value")?;
+                    food.count = value
+                        .parse()
+                        .ok()
+                        .filter(|&count| count > 0)
+                        .ok_or_else(|| format!("invalid food count `{value}`"))?;
+                }
+                "--player" => {
+                    let value = args.next().ok_or("--player needs a value")?;
+                    if named == players.len() {
+                        return Err("--player can only be given twice".to_string());
+                    }
+                    players[named] = Player::parse(&value)?;
+                    named += 1;
+                }
+                "--versus" => {
+                    let value = args.next().ok_or("--versus needs a value")?;
+                    versus = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&rounds| rounds > 0)
+                            .ok_or_else(|| format!("invalid number of rounds `{value}`"))?,
+                    );
+                }
+                "--lives" => {
+                    let value = args.next().ok_or("--lives needs a value")?;
+                    lives = value
+                        .parse()
+                        .ok()
+                        .filter(|&lives| lives > 0)
+                        .ok_or_else(|| format!("invalid number of lives `{value}`"))?;
+                }
+                "--speed" => {
+                    let value = args.next().ok_or("--speed needs a value")?;
+                    speed = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&speed: &f64| speed > 0. && speed.is_finite())
+                            .ok_or_else(|| format!("invalid speed `{value}`"))?,
+                    );
+                }
+                "--corner-grace" => {
+                    let value = args.next().ok_or("--corner-grace needs a value")?;
+                    let ms: u32 = value
+                        .parse()
+                        .map_err(|_| format!("invalid corner grace `{value}`"))?;
+                    corner_grace = ms as f64 / 1000.;
+                }
+                "--length" => {
+                    let value = args.next().ok_or("--length needs a value")?;
+                    length = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&length| length > 0)
+                            .ok_or_else(|| format!("invalid length `{value}`"))?,
+                    );
+                }
+                "--spawn" => {
+                    let value = args.next().ok_or("--spawn needs a value")?;
+                    let cell = value
+                        .split_once(',')
+                        .and_then(|(x, y)| {
+                            Some((x.trim().parse::<u16>().ok()?, y.trim().parse::<u16>().ok()?))
+                        })
+                        .ok_or_else(|| format!("invalid cell `{value}`, expected <x>,<y>"))?;
+                    spawn = Some(Vec2::new(cell.0 as f64, cell.1 as f64));
+                }
+                "--direction" => direction = Some(args.next().ok_or("--direction needs a value")?),
+                "--hunger" => {
+                    let value = args.next().ok_or("--hunger needs a value")?;
+                    hunger = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&secs: &f64| secs > 0.)
+                            .ok_or_else(|| format!("invalid hunger interval `{value}`"))?,
+                    );
+                }
+                "--portals" => {
+                    let value = args.next().ok_or("--portals needs a value")?;
+                    portals = value
+                        .parse()
+                        .map_err(|_| format!("invalid number of portals `{value}`"))?;
+                }
+                "--closing" => {
+                    let value = args.next().ok_or("--closing needs a value")?;
+                    closing = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&secs: &f64| secs > 0.)
+                            .ok_or_else(|| format!("invalid closing interval `{value}`"))?,
+                    );
+                }
+                "--fog" => {
+                    let value = args.next().ok_or("--fog needs a value")?;
+                    fog = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&radius: &f64| radius > 0.)
+                            .ok_or_else(|| format!("invalid fog radius `{value}`"))?,
+                    );
+                }
+                "--steer" => {
+                    let value = args.next().ok_or("--steer needs a value")?;
+                    steer = value
+                        .parse()
+                        .ok()
+                        .filter(|&degrees| degrees > 0 && degrees < 180)
+                        .ok_or_else(|| format!("invalid steering angle `{value}`"))?;
+                }
+                "--camera" => camera = args.next().ok_or("--camera needs a value")?,
+                "--movement" => movement = args.next().ok_or("--movement needs a value")?,
+                "--difficulty" => difficulty = args.next().ok_or("--difficulty needs a value")?,
+                "--emit-state" => {
+                    emit_state = Some(args.next().ok_or("--emit-state needs a value")?)
+                }
+                "--agent-cmd" => agent_cmd = Some(args.next().ok_or("--agent-cmd needs a value")?),
+                "--announce" => announce = Some(args.next().ok_or("--announce needs a value")?),
+                "--control" => control = Some(args.next().ok_or("--control needs a value")?),
+                "--ws-port" => {
+                    let value = args.next().ok_or("--ws-port needs a value")?;
+                    ws_port = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid port `{value}`"))?,
+                    );
+                }
+                "--script" => script = Some(args.next().ok_or("--script needs a value")?),
+                "--log-level" => log_level = Some(args.next().ok_or("--log-level needs a value")?),
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        let mut movement = MovementRules::from_name(&movement)
+            .ok_or_else(|| format!("unknown movement rules `{movement}`"))?;
+        if let MovementRules::Analog(degrees) = &mut movement {
+            *degrees = steer;
+        }
+        // Analog steering moves in steps too fine for block cells to show,
+        // so it's drawn in braille unless asked otherwise.
+        let render = render.unwrap_or_else(|| match movement {
+            MovementRules::Analog(_) => "braille".to_string(),
+            _ => "block".to_string(),
+        });
+        let render_mode = RenderMode::from_name(&render)
+            .ok_or_else(|| format!("unknown render mode `{render}`"))?;
+        let theme = Theme::find(&theme, &config.themes)?;
+        let camera = CameraMode::from_name(&camera)
+            .ok_or_else(|| format!("unknown camera mode `{camera}`"))?;
+        let difficulty = Difficulty::from_name(&difficulty)
+            .ok_or_else(|| format!("unknown difficulty `{difficulty}`"))?;
+        if length == Some(0) {
+            return Err("invalid length `0`".to_string());
+        }
+        if let Some(speed) = speed.filter(|&speed| speed <= 0. || !speed.is_finite()) {
+            return Err(format!("invalid speed `{speed}`"));
+        }
+        let direction = direction
+            .map(|name| {
+                Direction::from_name(&name).ok_or_else(|| format!("unknown direction `{name}`"))
+            })
+            .transpose()?;
+        let log_level = log_level
+            .map(|name| {
+                Level::from_name(&name).ok_or_else(|| format!("unknown log level `{name}`"))
+            })
+            .transpose()?;
+        let rules = Ruleset {
+            wraps,
+            hunger,
+            poison,
+            closing,
+            trail,
+            timer: time_attack,
+            pass_through: false,
+        };
+        // Zen takes away anything else that could end the run.
+        let rules = if zen { rules.zen() } else { rules };
+        let options = Options {
+            render_mode,
+            theme,
+            ascii,
+            seed,
+            resume_countdown: config.resume_countdown.unwrap_or(true),
+            death_replay: config.death_replay.unwrap_or(true),
+            screen_shake: config.screen_shake.unwrap_or(true),
+            window_title: config.window_title.unwrap_or(true),
+            notifications: config.notifications.unwrap_or(true),
+            reduced_motion,
+            smooth,
+            slow_motion,
+            width,
+            height,
+            camera,
+            difficulty,
+            adaptive,
+            tutorial,
+            practice,
+            speedrun,
+            dev,
+            profile,
+            level,
+            edit,
+            square,
+            scale,
+            food,
+            lives,
+            speed,
+            corner_grace,
+            start: Start {
+                length,
+                spawn,
+                direction,
+            },
+            rules,
+            portals,
+            fog,
+            coop,
+            versus,
+            players,
+            mission,
+            movement,
+            volume,
+            mute,
+            music,
+            audio_cues,
+            log_level,
+            script,
+            emit_state,
+            announce,
+            control,
+            ws_port,
+            agent_cmd,
+        };
+        Ok(match challenge {
+            Some(challenge) => options.with_challenge(&challenge),
+            None => options,
+        })
+    }
+
+    /// The challenge a run started with these options plays, given what
+    /// was picked for it when it started.
+    fn challenge(&self, seed: u64, width: u16, height: u16, difficulty: Difficulty) -> Challenge {
+        Challenge {
+            seed,
+            width,
+            height,
+            difficulty,
+            movement: self.movement,
+            rules: self.rules,
+            mission: self.mission,
+            lives: self.lives,
+            portals: self.portals,
+            food: self.food,
+            fog: self.fog,
+        }
+    }
+
+    /// These options, set to play `challenge` and nothing else.
+    fn with_challenge(&self, challenge: &Challenge) -> Options {
+        Options {
+            seed: Some(challenge.seed),
+            width: Some(challenge.width),
+            height: Some(challenge.height),
+            difficulty: challenge.difficulty,
+            movement: challenge.movement,
+            rules: challenge.rules,
+            mission: challenge.mission,
+            lives: challenge.lives,
+            portals: challenge.portals,
+            food: challenge.food,
+            fog: challenge.fog,
+            practice: false,
+            coop: false,
+            versus: None,
+            level: None,
+            speed: None,
+            start: Start::default(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Takes over the terminal and runs the game on it until it's quit, putting
+/// the terminal back as it was afterwards, even if the game fails.
+fn game_loop(
+    reciever: Receiver<Commands>,
+    mut options: Options,
+    streams: Vec<StateStream>,
+    announcer: Option<Announcer>,
+    control: Option<ControlSocket>,
+) -> Result<(), SnakeError> {
+    let mut renderer = make_renderer(&options);
+    let caps = renderer.caps;
+    let mut stdout = Terminal::new(caps.alt_screen).map_err(SnakeError::TerminalSetup)?;
+    if options.window_title {
+        // Best effort: the title is only a nicety.
+        let _ = write!(stdout, "{}", osc::push_title(caps.multiplexer));
+    }
+    let result = run(
+        &reciever,
+        &mut options,
+        &mut renderer,
+        &mut stdout,
+        streams,
+        announcer,
+        control.as_ref(),
+    );
+    let restored = if caps.alt_screen {
+        Ok(())
+    } else {
+        write!(
+            stdout,
+            "{}{}",
+            termion::clear::All,
+            termion::cursor::Goto(1, 1)
+        )
+    };
+    let restored = restored.and_then(|()| write!(stdout, "{}", termion::cursor::Show));
+    let restored = restored.and_then(|()| {
+        let title = if options.window_title {
+            osc::pop_title(caps.multiplexer)
+        } else {
+            ""
+        };
+        write!(stdout, "{title}")
+    });
+    let profile =
+        result.and_then(|profile| restored.map(|()| profile).map_err(SnakeError::Output))?;
+    // Off the alternate screen first, so the summary stays up.
+    drop(stdout);
+    if let Some(path) = &options.profile {
+        match profile.save(path) {
+            Ok(()) => eprint!("{}", profile.summary()),
+            Err(err) => eprintln!("snake: couldn't save the profile: {err}"),
+        }
+    }
+    Ok(())
+}
+
+/// Reads commands and draws frames until the player quits, returning how
+/// long each frame took if it was asked to profile them.
+fn run(
+    reciever: &Receiver<Commands>,
+    options: &mut Options,
+    renderer: &mut Renderer,
+    stdout: &mut Terminal,
+    mut streams: Vec<StateStream>,
+    mut announcer: Option<Announcer>,
+    control: Option<&ControlSocket>,
+) -> Result<Profile, SnakeError> {
+    // Picked on the title screen, for every run until it's changed.
+    let mut difficulty = options.difficulty;
+    // The tutorial starts on its own the first time the game's played by
+    // hand.
+    let first_time = options.edit.is_none()
+        && options.script.is_none()
+        && options.agent_cmd.is_none()
+        && LifetimeStats::load().is_ok_and(|stats| stats.games == 0 && !stats.tutorial_done);
+    // The editor the run being played was started from, to go back to.
+    let mut testing: Option<EditorScreen> = None;
+    // The options for the challenge code or mode being played, to restart
+    // it with.
+    let mut challenge: Option<Options> = None;
+    // The tournament a match is being played in, to go back to.
+    let mut tournament: Option<Bracket> = None;
+    let mut screen = if let Some(path) = &options.edit {
+        let level = match &options.level {
+            Some(level) => level.clone(),
+            None => {
+                let (cols, rows) = terminal_size()?;
+                let (width, height) = renderer.arena_size(cols, rows.saturating_sub(HUD_ROWS + 1));
+                level::Level::new(width, height)
+            }
+        };
+        Screen::Editor(EditorScreen::new(level, path.clone()))
+    } else if options.tutorial.unwrap_or(first_time) {
+        Screen::Playing(new_tutorial(renderer, options)?)
+    } else {
+        Screen::Title(TitleScreen::new(difficulty))
+    };
+    // The run being played, kept to save once it's over.
+    let mut replay: Option<Replay> = None;
     let mut clock = Clock::new();
-    game.draw(&mut stdout);
+    let mut debug = DebugOverlay::default();
+    let mut profile = Profile::default();
+    let mut show_log = false;
+    let mut console = Console::default();
+    let mut chat = Chat::default();
+    // Which control clients are playing as which player, and whether the
+    // run was paused to wait for one to come back.
+    let mut seats = Seats::default();
+    let mut held = false;
+    // Whether the help is up, and if so whether it paused the run.
+    let mut help: Option<bool> = None;
+    // Whether quitting a run is waiting on a yes, and if so whether asking
+    // paused it.
+    let mut quitting: Option<bool> = None;
+    let mut pause_menu = Menu::new(vec![
+        (PauseChoice::Resume, "resume"),
+        (PauseChoice::Restart, "restart"),
+        (PauseChoice::Settings, "settings"),
+        (PauseChoice::QuitToMenu, "quit to menu"),
+    ]);
+    let mut sounds = Sounds::new(options.volume, options.mute);
+    let mut toasts = Toasts::default();
+    // The window title last set, so it's only sent when it changes.
+    let mut window_title = String::new();
+    let mut cues = options.audio_cues.then(Cues::default);
+    let mut adaptive = options.adaptive.then(Adaptive::default);
+    #[cfg(feature = "music")]
+    let mut music = match options.music.as_deref().filter(|_| !options.mute) {
+        Some(dir) => Music::load(Path::new(dir)).unwrap_or_else(|err| {
+            log!(Warn, "no music: {err}");
+            Music::default()
+        }),
+        None => Music::default(),
+    };
+    let mut shown = screen.name();
+    renderer.present(&screen.draw(renderer)?, stdout)?;
     let mut dt = 0.;
     loop {
-        match reciever.try_recv() {
-            Ok(cmd) => match cmd {
-                Commands::RotatePlayer(dir) => {
-                    game.player.rotate(dir);
-                }
-                Commands::Extend => game.player.extend(),
-                Commands::Shrink => game.player.shrink(),
-                Commands::Quit => break,
-            },
-            Err(mpsc::TryRecvError::Empty) => {}
+        let work = Instant::now();
+        let command = match reciever.try_recv() {
+            Ok(cmd) => Some(cmd),
+            Err(mpsc::TryRecvError::Empty) => None,
             Err(mpsc::TryRecvError::Disconnected) => break,
         };
-        game.update(dt);
-        game.draw(&mut stdout);
-        dt = clock.tick(30.);
+        // Yes, or quit again, goes through with it; anything else takes it
+        // back, along with the pause.
+        let command = match (command, quitting) {
+            (Some(Commands::Yes | Commands::Quit), Some(_)) => Some(Commands::Quit),
+            (Some(_), Some(paused)) => {
+                quitting = None;
+                paused.then_some(Commands::Pause)
+            }
+            (command, _) => command,
+        };
+        // Any key closes the help and does nothing else, other than undo
+        // the pause it brought on.
+        let command = match (command, help) {
+            (Some(_), Some(paused)) => {
+                help = None;
+                paused.then_some(Commands::Pause)
+            }
+            (Some(Commands::Help), None) => {
+                let pause =
+                    matches!(&screen, Screen::Playing(game) if !game.paused && !game.is_over());
+                help = Some(pause);
+                pause.then_some(Commands::Pause)
+            }
+            (command, _) => command,
+        };
+        // The console takes every key while it's open, and pauses the run
+        // while it's up.
+        let command = match (command, console.open, &mut screen) {
+            (Some(Commands::Console), None, Screen::Playing(game))
+                if options.dev && !game.is_over() =>
+            {
+                let pause = !game.paused;
+                console.open = Some(pause);
+                pause.then_some(Commands::Pause)
+            }
+            (Some(Commands::Menu | Commands::Console | Commands::Type('~')), Some(paused), _) => {
+                console.open = None;
+                paused.then_some(Commands::Pause)
+            }
+            (Some(Commands::Type(ch)), Some(_), _) => {
+                console.push(ch);
+                None
+            }
+            (Some(Commands::Delete), Some(_), _) => {
+                console.erase();
+                None
+            }
+            (Some(Commands::Complete), Some(_), _) => {
+                console.complete();
+                None
+            }
+            (Some(Commands::Confirm), Some(_), Screen::Playing(game)) => {
+                let line = console.submit();
+                let applied = Cheat::parse(&line).and_then(|cheat| {
+                    cheat.apply(game)?;
+                    Ok(cheat)
+                });
+                match applied {
+                    Ok(cheat) => {
+                        log!(Info, "console: {line}");
+                        if let Some(replay) = &mut replay {
+                            replay.push(0., Some(Input::Cheat(cheat)));
+                        }
+                        console.print("ok".to_string());
+                    }
+                    Err(err) => console.print(err),
+                }
+                None
+            }
+            (Some(Commands::Quit | Commands::Suspend), Some(_), _) => command,
+            (_, Some(_), _) => None,
+            (command, None, _) => command,
+        };
+        // So does the chat line, but the run carries on underneath.
+        let command = match (command, chat.open, &screen) {
+            (Some(Commands::Chat), false, Screen::Playing(game)) if !game.is_over() => {
+                chat.open = true;
+                None
+            }
+            (Some(Commands::Menu), true, _) => {
+                chat.open = false;
+                None
+            }
+            (Some(Commands::Type(ch)), true, _) => {
+                chat.push(ch);
+                None
+            }
+            (Some(Commands::Delete), true, _) => {
+                chat.erase();
+                None
+            }
+            (Some(Commands::Confirm), true, Screen::Playing(game)) => {
+                if let Some(text) = chat.submit() {
+                    let from = &game.players[0].name;
+                    log!(Info, "chat: {from}: {text}");
+                    chat.post(from, &text);
+                }
+                None
+            }
+            (Some(Commands::Quit | Commands::Suspend), true, _) => command,
+            (_, true, _) => None,
+            (command, false, _) => command,
+        };
+        match command {
+            Some(Commands::Debug) => debug.toggle(),
+            Some(Commands::Log) => show_log = !show_log,
+            _ => {}
+        }
+        while let Some(notice) = control.and_then(ControlSocket::notice) {
+            log!(Info, "{notice}");
+            toasts.push(ToastKind::Network, notice);
+        }
+        while let Some(client) = control.and_then(ControlSocket::departed) {
+            if let Some(player) = seats.leave(client) {
+                let notice = format!("{} lost connection", player_name(&screen, options, player));
+                log!(Info, "{notice}");
+                toasts.push(ToastKind::Network, notice);
+            }
+        }
+        while let Some(call) = control.and_then(ControlSocket::poll) {
+            let answer = match (call.request, &mut screen) {
+                (Request::Join(player, token), screen) => {
+                    seats.join(player, call.client, token).map(|(back, token)| {
+                        joined(
+                            player,
+                            back,
+                            token,
+                            call.features,
+                            screen,
+                            options,
+                            &mut toasts,
+                        )
+                    })
+                }
+                (Request::State, screen) => Ok(state(screen, call.features)),
+                (Request::Say(text), _) => {
+                    chat.post("control", &text);
+                    Ok("ok".to_string())
+                }
+                (Request::Chat, _) => Ok(chat.to_json()),
+                (Request::Lobby, Screen::Lobby(lobby)) => Ok(lobby.to_json()),
+                (Request::Ready(player), Screen::Lobby(lobby)) => {
+                    lobby.toggle_ready(player);
+                    Ok("ok".to_string())
+                }
+                (Request::Lobby | Request::Ready(_), _) => Err("no lobby open".to_string()),
+                (Request::Pause, Screen::Playing(game)) => {
+                    game.toggle_pause();
+                    Ok(record(&mut replay, Input::Pause))
+                }
+                (Request::Speed(factor), Screen::Playing(game)) => {
+                    game.set_speed(factor);
+                    Ok(record(&mut replay, Input::Speed(factor)))
+                }
+                (Request::SpawnFood(cell), Screen::Playing(game)) => game
+                    .place_food(cell)
+                    .map(|()| record(&mut replay, Input::PlaceFood(cell))),
+                (Request::Pause | Request::Speed(_) | Request::SpawnFood(_), _) => {
+                    Err("no run in progress".to_string())
+                }
+            };
+            let _ = call
+                .reply
+                .send(answer.unwrap_or_else(|err| format!("error: {err}")));
+        }
+        for player in seats.update(dt) {
+            let notice = format!("{} dropped", player_name(&screen, options, player));
+            log!(Info, "{notice}");
+            toasts.push(ToastKind::Network, notice);
+        }
+        // A versus match waits for a player whose connection has dropped.
+        match &mut screen {
+            Screen::Playing(game) if game.versus.is_some() && !game.is_over() => {
+                let waiting = seats.waiting();
+                let pause = waiting && !held && !game.paused;
+                let resume = !waiting && held && game.paused;
+                if pause || resume {
+                    game.toggle_pause();
+                    if let Some(replay) = &mut replay {
+                        replay.push(0., Some(Input::Pause));
+                    }
+                }
+                held = pause || (held && waiting);
+            }
+            _ => held = false,
+        }
+        let was_over = matches!(screen, Screen::GameOver(_));
+        match &mut screen {
+            Screen::Title(title) => match command {
+                Some(Commands::Up) => {
+                    title.difficulty.up();
+                    difficulty = title.difficulty.chosen().unwrap_or_default();
+                }
+                Some(Commands::Down) => {
+                    title.difficulty.down();
+                    difficulty = title.difficulty.chosen().unwrap_or_default();
+                }
+                Some(Commands::Confirm) => {
+                    replay = None;
+                    challenge = None;
+                    screen = Screen::Playing(new_game(renderer, options, difficulty)?);
+                }
+                Some(Commands::Tutorial) => {
+                    replay = None;
+                    challenge = None;
+                    screen = Screen::Playing(new_tutorial(renderer, options)?);
+                }
+                Some(Commands::Challenge) => screen = Screen::Challenge(ChallengeScreen::new()),
+                Some(Commands::Modes) => screen = Screen::Modes(ModeScreen::new()),
+                Some(Commands::Tournament) => screen = Screen::Tournament(TournamentScreen::new()),
+                Some(Commands::Achievements) => {
+                    screen = Screen::Achievements(AchievementsScreen::new())
+                }
+                Some(Commands::Stats) => screen = Screen::Stats(StatsScreen::new()),
+                Some(Commands::QuickLoad) => screen = Screen::Load(LoadScreen::new()),
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Load(load) => match command {
+                Some(Commands::Slot(slot)) => load.select(slot),
+                Some(Commands::Delete) => load.delete_selected(),
+                Some(Commands::Confirm) => {
+                    if let Some(game) = load.chosen() {
+                        replay = None;
+                        screen = Screen::Playing(game);
+                    }
+                }
+                Some(Commands::Menu | Commands::QuickLoad) => {
+                    screen = Screen::Title(TitleScreen::new(difficulty))
+                }
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Stats(stats) => match command {
+                Some(Commands::RotatePlayer(_) | Commands::RotatePartner(_)) => stats.flip(),
+                Some(Commands::Confirm | Commands::Menu | Commands::Stats) => {
+                    screen = Screen::Title(TitleScreen::new(difficulty))
+                }
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Achievements(_) => match command {
+                Some(Commands::Confirm | Commands::Menu | Commands::Achievements) => {
+                    screen = Screen::Title(TitleScreen::new(difficulty))
+                }
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Challenge(entry) => match command {
+                Some(Commands::Type(ch)) => entry.push(ch),
+                Some(Commands::Delete) => entry.erase(),
+                Some(Commands::Confirm) => {
+                    if let Some(code) = entry.submit() {
+                        let played = options.with_challenge(&code);
+                        replay = None;
+                        screen = Screen::Playing(new_game(renderer, &played, played.difficulty)?);
+                        challenge = Some(played);
+                    }
+                }
+                Some(Commands::Menu) => screen = Screen::Title(TitleScreen::new(difficulty)),
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Modes(modes) => match command {
+                Some(Commands::Up) => modes.modes.up(),
+                Some(Commands::Down) => modes.modes.down(),
+                Some(Commands::Confirm) => match modes.modes.chosen() {
+                    Some(Some(mode)) => {
+                        let played = mode.options(options);
+                        // Games for two meet in the lobby first.
+                        if played.coop || played.versus.is_some() {
+                            screen = Screen::Lobby(LobbyScreen::new(
+                                played.versus,
+                                played.rules,
+                                played.players.clone(),
+                            ));
+                        } else {
+                            replay = None;
+                            screen = Screen::Playing(new_game(renderer, &played, difficulty)?);
+                            challenge = Some(played);
+                        }
+                    }
+                    Some(None) => screen = Screen::Rules(RulesScreen::new(options.rules)),
+                    None => {}
+                },
+                Some(Commands::Menu | Commands::Modes) => {
+                    screen = Screen::Title(TitleScreen::new(difficulty))
+                }
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Rules(custom) => match command {
+                Some(Commands::Up) => custom.menu.up(),
+                Some(Commands::Down) => custom.menu.down(),
+                Some(Commands::RotatePlayer(_) | Commands::RotatePartner(_)) => custom.toggle(),
+                Some(Commands::Confirm) => match custom.menu.chosen() {
+                    Some(None) => {
+                        let played = Options {
+                            rules: custom.rules,
+                            ..options.clone()
+                        };
+                        replay = None;
+                        screen = Screen::Playing(new_game(renderer, &played, difficulty)?);
+                        challenge = Some(played);
+                    }
+                    _ => custom.toggle(),
+                },
+                Some(Commands::Menu) => screen = Screen::Modes(ModeScreen::new()),
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Lobby(lobby) => {
+                match command {
+                    Some(Commands::Up) => lobby.menu.up(),
+                    Some(Commands::Down) => lobby.menu.down(),
+                    Some(Commands::RotatePlayer(angle)) => lobby.change(angle > 0.),
+                    Some(Commands::Confirm) => lobby.toggle_ready(0),
+                    Some(Commands::RotatePartner(_)) => lobby.toggle_ready(1),
+                    Some(Commands::Menu) => screen = Screen::Modes(ModeScreen::new()),
+                    Some(Commands::Quit) => break,
+                    _ => {}
+                }
+                if let Screen::Lobby(lobby) = &screen
+                    && lobby.all_ready()
+                {
+                    let played = lobby.options(options);
+                    replay = None;
+                    screen = Screen::Playing(new_game(renderer, &played, difficulty)?);
+                    challenge = Some(played);
+                }
+            }
+            Screen::Tournament(entry) if entry.entering() => match command {
+                Some(Commands::Type(ch)) => entry.push(ch),
+                Some(Commands::Delete) => entry.erase(),
+                Some(Commands::Confirm) => entry.submit(Rng::random_seed()),
+                Some(Commands::Menu) => screen = Screen::Title(TitleScreen::new(difficulty)),
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Tournament(between) => match command {
+                Some(Commands::Confirm) => {
+                    match between.bracket.as_ref().and_then(Bracket::next_match) {
+                        Some(players) => {
+                            let played = Options {
+                                versus: Some(versus::FIRST_TO),
+                                coop: false,
+                                players,
+                                ..options.clone()
+                            };
+                            replay = None;
+                            tournament = between.bracket.take();
+                            screen = Screen::Playing(new_game(renderer, &played, difficulty)?);
+                            challenge = Some(played);
+                        }
+                        None => screen = Screen::Title(TitleScreen::new(difficulty)),
+                    }
+                }
+                Some(Commands::Menu) => screen = Screen::Title(TitleScreen::new(difficulty)),
+                Some(Commands::Quit) => break,
+                _ => {}
+            },
+            Screen::Settings(settings) => match command {
+                Some(Commands::Up) => settings.menu.up(),
+                Some(Commands::Down) => settings.menu.down(),
+                // Enter changes it forward, as right does.
+                Some(Commands::RotatePlayer(_) | Commands::Confirm) => {
+                    settings.change(
+                        !matches!(command, Some(Commands::RotatePlayer(angle)) if angle < 0.),
+                    );
+                    *options = settings.options(options);
+                    *renderer = make_renderer(options);
+                    sounds = Sounds::new(options.volume, options.mute);
+                }
+                Some(Commands::Menu) => screen = Screen::Playing(settings.game.clone()),
+                // Back to the paused run, to be asked there.
+                Some(Commands::Quit) => {
+                    quitting = Some(false);
+                    screen = Screen::Playing(settings.game.clone());
+                }
+                _ => {}
+            },
+            Screen::Editor(editor) => match command {
+                Some(Commands::Confirm) => {
+                    let mut game = new_game(renderer, options, difficulty)?;
+                    game.load_level(&editor.level);
+                    testing = Some(editor.clone());
+                    challenge = None;
+                    replay = None;
+                    screen = Screen::Playing(game);
+                }
+                Some(Commands::Menu) => screen = Screen::Title(TitleScreen::new(difficulty)),
+                Some(Commands::Quit) => break,
+                Some(command) => editor.handle(command),
+                None => {}
+            },
+            Screen::Playing(game) => {
+                let (cols, rows) = terminal_size()?;
+                let (width, height) = renderer.arena_size(cols, rows.saturating_sub(HUD_ROWS));
+                game.track(width, height);
+                let recording = replay.get_or_insert_with(|| Replay::new(game, cols, rows));
+                let mut input = None;
+                // Where to go instead, once this frame's done with the run.
+                let mut leave = None;
+                match command {
+                    // Everything but quitting goes to the editor while it's up.
+                    Some(command)
+                        if game.cursor.is_some() && !matches!(command, Commands::Quit) =>
+                    {
+                        input = practice::edit(game, command);
+                    }
+                    Some(Commands::Edit) if game.practice => input = practice::start(game),
+                    Some(Commands::Menu) if testing.is_some() => {
+                        leave = testing.take().map(Screen::Editor);
+                    }
+                    Some(Commands::Up) if game.paused => pause_menu.up(),
+                    Some(Commands::Down) if game.paused => pause_menu.down(),
+                    Some(Commands::Confirm) if game.paused => match pause_menu.chosen() {
+                        Some(PauseChoice::Resume) | None => {
+                            game.toggle_pause();
+                            input = Some(Input::Pause);
+                        }
+                        Some(PauseChoice::Restart) => {
+                            let played = challenge.as_ref().unwrap_or(options);
+                            let restart = new_game(renderer, played, game.difficulty)?;
+                            leave = Some(Screen::Playing(restart));
+                        }
+                        Some(PauseChoice::Settings) => {
+                            leave =
+                                Some(Screen::Settings(SettingsScreen::new(game.clone(), options)));
+                        }
+                        Some(PauseChoice::QuitToMenu) => {
+                            // Kept to come back to, as when quitting.
+                            if let Err(err) = save::save(game, game.slot) {
+                                log!(Warn, "couldn't save the run on leaving: {err}");
+                            }
+                            leave = Some(match tournament.take() {
+                                Some(bracket) => {
+                                    Screen::Tournament(TournamentScreen::showing(bracket))
+                                }
+                                None => Screen::Title(TitleScreen::new(difficulty)),
+                            });
+                        }
+                    },
+                    Some(Commands::RotatePlayer(dir)) => {
+                        game.steer(dir);
+                        input = Some(Input::Steer(dir));
+                    }
+                    Some(Commands::RotatePartner(dir)) if game.partner.is_some() => {
+                        game.steer_partner(dir);
+                        input = Some(Input::SteerPartner(dir));
+                    }
+                    Some(Commands::RotatePartner(dir)) => {
+                        game.steer(dir);
+                        input = Some(Input::Steer(dir));
+                    }
+                    Some(Commands::Pause) => {
+                        game.toggle_pause();
+                        pause_menu.reset();
+                        input = Some(Input::Pause);
+                    }
+                    // Left paused for the player to come back to.
+                    Some(Commands::Suspend) if !game.paused => {
+                        game.toggle_pause();
+                        pause_menu.reset();
+                        input = Some(Input::Pause);
+                    }
+                    Some(Commands::Rewind) => {
+                        if game.rewind() {
+                            input = Some(Input::Rewind);
+                        } else {
+                            game.notify("no rewinds left".to_string());
+                        }
+                    }
+                    Some(Commands::QuickSave) => match save::save(game, game.slot) {
+                        Ok(()) => game.notify(format!("saved to slot {}", game.slot + 1)),
+                        Err(err) => game.notify(format!("couldn't save: {err}")),
+                    },
+                    Some(Commands::QuickLoad) => match save::load(game.slot) {
+                        Ok(Some(saved)) => {
+                            *game = saved;
+                            *recording = Replay::new(game, cols, rows);
+                        }
+                        Ok(None) => game.notify(format!("slot {} is empty", game.slot + 1)),
+                        Err(err) => game.notify(format!("couldn't load: {err}")),
+                    },
+                    Some(Commands::Slot(slot)) if slot < SLOTS => {
+                        game.slot = slot;
+                        game.notify(format!("saving to slot {}", slot + 1));
+                    }
+                    Some(Commands::Quit) if quitting.is_some() => {
+                        // Quitting mid-run keeps the run to come back to.
+                        if let Err(err) = save::save(game, game.slot) {
+                            log!(Warn, "couldn't save the run on quitting: {err}");
+                        }
+                        break;
+                    }
+                    Some(Commands::Quit) => {
+                        quitting = Some(!game.paused);
+                        if !game.paused {
+                            game.toggle_pause();
+                            input = Some(Input::Pause);
+                        }
+                    }
+                    Some(
+                        Commands::Extend
+                        | Commands::Shrink
+                        | Commands::Confirm
+                        | Commands::Menu
+                        | Commands::Achievements
+                        | Commands::Chat
+                        | Commands::Stats
+                        | Commands::Slot(_)
+                        | Commands::Delete
+                        | Commands::Export
+                        | Commands::Screenshot
+                        | Commands::Log
+                        | Commands::Debug
+                        | Commands::Help
+                        | Commands::Up
+                        | Commands::Down
+                        | Commands::Yes
+                        | Commands::No
+                        | Commands::Tutorial
+                        | Commands::Edit
+                        | Commands::Food
+                        | Commands::Obstacle
+                        | Commands::Spawn
+                        | Commands::Challenge
+                        | Commands::Modes
+                        | Commands::Tournament
+                        | Commands::Console
+                        | Commands::Complete
+                        | Commands::Type(_)
+                        | Commands::Suspend,
+                    )
+                    | None => {}
+                }
+                let counting = game.countdown.map(|countdown| countdown.label());
+                game.update(dt);
+                recording.push(dt, input);
+                if let Some(countdown) = game.countdown
+                    && Some(countdown.label()) != counting
+                {
+                    sounds.play(Sound::Countdown);
+                }
+                dispatch_events(
+                    game,
+                    &mut streams,
+                    &mut sounds,
+                    announcer.as_mut(),
+                    adaptive.as_mut(),
+                    &mut toasts,
+                );
+                if let Some(adaptive) = &adaptive
+                    && adaptive.notches() != game.adapted
+                {
+                    log!(Debug, "adaptive difficulty: {:+}", adaptive.notches());
+                    game.adapt(adaptive.notches());
+                }
+                if let Some(out) = &mut announcer
+                    && let Err(err) = out.update(game)
+                {
+                    log!(Warn, "stopped announcing: {err}");
+                    announcer = None;
+                }
+                if let Some(cues) = &mut cues {
+                    cues.update(game, &mut sounds);
+                }
+                streams.retain_mut(|stream| match stream.update(game) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        log!(Warn, "stopped streaming state: {err}");
+                        false
+                    }
+                });
+                if game.is_over() {
+                    if let Some(bracket) = &mut tournament
+                        && let Some(versus) = &game.versus
+                    {
+                        bracket.record(versus.winner());
+                    }
+                    screen = Screen::Dying(DeathScreen::new(
+                        game.clone(),
+                        options.death_replay,
+                        options.screen_shake && !options.reduced_motion,
+                    ));
+                } else if let Some(next) = leave {
+                    // The run carries on after the settings, and so does its
+                    // replay.
+                    if !matches!(next, Screen::Settings(_)) {
+                        replay = None;
+                    }
+                    screen = next;
+                }
+            }
+            Screen::Dying(death) => {
+                death.update(dt);
+                match command {
+                    Some(Commands::Quit) => break,
+                    Some(Commands::Confirm) => {
+                        screen = match testing.take() {
+                            Some(editor) => Screen::Editor(editor),
+                            None => game_over(&death.game, replay.as_ref()),
+                        }
+                    }
+                    Some(Commands::Rewind) if death.game.rewinds > 0 => {
+                        let mut game = death.game.clone();
+                        if game.rewind() {
+                            if let Some(replay) = &mut replay {
+                                replay.push(0., Some(Input::Rewind));
+                            }
+                            screen = Screen::Playing(game);
+                        }
+                    }
+                    _ if death.finished() => {
+                        screen = match testing.take() {
+                            Some(editor) => Screen::Editor(editor),
+                            None => game_over(&death.game, replay.as_ref()),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Screen::GameOver(game_over) => {
+                game_over.update(dt);
+                match command {
+                    Some(Commands::Export) => {
+                        game_over.notice = Some(match &replay {
+                            Some(replay) => {
+                                let path = format!("snake-{}.cast", game_over.seed);
+                                let written =
+                                    File::create(&path).map_err(|err| err.to_string()).and_then(
+                                        |mut file| replay.export_cast(renderer, 1., &mut file),
+                                    );
+                                match written {
+                                    Ok(()) => format!("saved {path}"),
+                                    Err(err) => format!("couldn't export: {err}"),
+                                }
+                            }
+                            None => "nothing to export".to_string(),
+                        });
+                    }
+                    Some(Commands::Confirm | Commands::Menu) if tournament.is_some() => {
+                        if let Some(bracket) = tournament.take() {
+                            screen = Screen::Tournament(TournamentScreen::showing(bracket));
+                        }
+                    }
+                    Some(Commands::Confirm) => {
+                        replay = None;
+                        let restart = match &challenge {
+                            Some(played) => new_game(renderer, played, played.difficulty)?,
+                            None => new_game(renderer, options, difficulty)?,
+                        };
+                        screen = Screen::Playing(restart);
+                    }
+                    Some(Commands::Menu) => screen = Screen::Title(TitleScreen::new(difficulty)),
+                    Some(Commands::Quit) => break,
+                    _ => {}
+                }
+            }
+        }
+        if console.open.is_some() && !matches!(screen, Screen::Playing(_)) {
+            console.open = None;
+        }
+        if !matches!(screen, Screen::Playing(_)) {
+            chat.open = false;
+        }
+        input::set_playing(matches!(screen, Screen::Playing(_)));
+        input::set_typing(
+            console.open.is_some()
+                || chat.open
+                || matches!(&screen, Screen::Challenge(_))
+                || matches!(&screen, Screen::Tournament(entry) if entry.entering()),
+        );
+        if let Some(Commands::Suspend) = command {
+            log!(Info, "suspended");
+            stdout.suspend().map_err(SnakeError::Output)?;
+            log!(Info, "continued");
+            // Time spent stopped isn't played.
+            clock = Clock::new();
+        }
+        if options.notifications
+            && !was_over
+            && let Screen::GameOver(game_over) = &screen
+            && let Some(best) = game_over.personal_best()
+        {
+            write!(
+                stdout,
+                "{}",
+                osc::notify("snake", &best, renderer.caps.multiplexer)
+            )
+            .map_err(SnakeError::Output)?;
+        }
+        if options.window_title && screen.window_title() != window_title {
+            window_title = screen.window_title();
+            write!(
+                stdout,
+                "{}",
+                osc::title(&window_title, renderer.caps.multiplexer)
+            )
+            .map_err(SnakeError::Output)?;
+        }
+        let simulated = work.elapsed().as_secs_f64();
+        let mut frame = screen.draw(renderer)?;
+        if let (true, Some(game)) = (show_log, screen.game()) {
+            events::draw_panel(renderer, &mut frame, HUD_ROWS, &game.log);
+        }
+        if let Screen::Playing(game) = &screen
+            && game.paused
+            && game.cursor.is_none()
+        {
+            let row = HUD_ROWS + frame.height().saturating_sub(HUD_ROWS) / 2 + 2;
+            if quitting.is_some() {
+                let prompt = format!("Quit? the run is kept in slot {} (y/n)", game.slot + 1);
+                frame.print_centered(row, &prompt, renderer.theme.colors.bonus);
+            } else {
+                pause_menu.draw(renderer, &mut frame, row);
+            }
+        }
+        toasts.update(dt);
+        toasts.draw(renderer, &mut frame, HUD_ROWS);
+        debug.draw(renderer, &mut frame, screen.game());
+        chat.update(dt);
+        if let Screen::Playing(_) = screen {
+            chat.draw(renderer, &mut frame);
+        }
+        if console.open.is_some() {
+            console.draw(renderer, &mut frame);
+        }
+        if help.is_some() {
+            help::draw(renderer, &mut frame, screen.game());
+        }
+        if let Some(Commands::Screenshot) = command {
+            let notice = match screenshot::save(&frame, renderer.caps.color, &screenshot_name()) {
+                Ok(path) => format!("saved {path}"),
+                Err(err) => format!("couldn't save: {err}"),
+            };
+            match &mut screen {
+                Screen::Playing(game) => game.notify(notice),
+                Screen::GameOver(game_over) => game_over.notice = Some(notice),
+                _ => {}
+            }
+        }
+        if screen.name() != shown {
+            log!(Info, "screen: {shown} -> {}", screen.name());
+            shown = screen.name();
+            // Dying has a sound of its own.
+            if !matches!(screen, Screen::Dying(_)) {
+                sounds.play(Sound::Menu);
+            }
+        }
+        renderer.present(&frame, stdout)?;
+        sounds.flush(stdout)?;
+        let work = work.elapsed().as_secs_f64();
+        let fps = if options.smooth { 60. } else { 30. };
+        if work > 1. / fps {
+            log!(
+                Warn,
+                "frame took {:.1} ms, over the {:.1} ms budget",
+                work * 1000.,
+                1000. / fps
+            );
+        }
+        let waiting = Instant::now();
+        dt = clock.tick(fps);
+        if options.profile.is_some() {
+            profile.push(FrameTime {
+                simulate: simulated,
+                render: work - simulated,
+                sleep: waiting.elapsed().as_secs_f64(),
+            });
+        }
+        #[cfg(feature = "music")]
+        music.update(
+            dt,
+            screen.track(),
+            screen
+                .game()
+                .map_or(1., |game| game.speed() / TICKS_PER_SECOND),
+        );
+        debug.record(dt, work, screen.game());
+    }
+    Ok(profile)
+}
+
+/// Starts a run on an arena of the size asked for, filling the terminal below
+/// the HUD in any direction that wasn't given.
+fn new_game(
+    renderer: &Renderer,
+    options: &Options,
+    difficulty: Difficulty,
+) -> Result<Game, SnakeError> {
+    let (cols, rows) = terminal_size()?;
+    let (width, height) = renderer.arena_size(cols, rows.saturating_sub(HUD_ROWS));
+    let width = options.width.unwrap_or(width);
+    let height = options.height.unwrap_or(height);
+    let seed = options.seed.unwrap_or_else(Rng::random_seed);
+    log!(
+        Info,
+        "new run: seed {seed}, arena {width}x{height}, {}",
+        difficulty.name()
+    );
+    let mut game = Game::new(width, height, seed, options.food);
+    game.achievements = Achievements::load().unwrap_or_default();
+    game.camera = Camera::new(options.camera);
+    game.resume_countdown = options.resume_countdown;
+    game.smooth = options.smooth;
+    game.lives = options.lives.max(1);
+    game.rules = Ruleset {
+        hunger: options.rules.hunger.filter(|&secs| secs > 0.),
+        closing: options.rules.closing.filter(|&secs| secs > 0.),
+        trail: false,
+        ..options.rules
+    };
+    game.add_portals(options.portals);
+    game.fog = options.fog.filter(|&radius| radius > 0.);
+    game.movement = options.movement;
+    game.practice = options.practice;
+    if options.level.is_none()
+        && !options.practice
+        && options.speed.is_none()
+        && !options.coop
+        && options.versus.is_none()
+        && options.start == Start::default()
+    {
+        let code = options.challenge(seed, width, height, difficulty).encode();
+        log!(Info, "challenge code {code}");
+        game.challenge = Some(code);
+    }
+    let mut start = options.start;
+    if let Some(level) = &options.level {
+        game.load_level(level);
+        start.spawn = start.spawn.or(Some(level.spawn));
+        start.direction = start.direction.or(Some(level.direction));
+    }
+    game.place_snake(start).map_err(SnakeError::Start)?;
+    game.players = options.players.clone();
+    if let Some(first_to) = options.versus {
+        game.start_versus(first_to);
+    } else if options.coop {
+        game.start_coop();
+    }
+    if let Some(speed) = options.speed {
+        game.base_speed = speed;
+    }
+    game.corner_grace = options.corner_grace;
+    game.slow_motion = options.slow_motion;
+    difficulty.apply(&mut game);
+    if options.rules.trail {
+        game.start_trail();
+    }
+    if options.rules.timer {
+        game.clock = Some(TIME_ATTACK);
+    }
+    if options.mission {
+        game.start_mission();
+    }
+    if let Some(table) = game.score_table() {
+        game.high_score = HighScores::load()
+            .ok()
+            .and_then(|scores| scores.table(&table).first().copied());
+    }
+    if options.speedrun {
+        let category = match options.seed {
+            Some(seed) => format!("{} seed {seed}", game.mode_name()),
+            None => format!("{} any seed", game.mode_name()),
+        };
+        game.speedrun = Some(Speedrun::new(category));
+    }
+    Ok(game)
+}
+
+/// A run of the tutorial, in an arena that fits the terminal.
+fn new_tutorial(renderer: &Renderer, options: &Options) -> Result<Game, SnakeError> {
+    let (cols, rows) = terminal_size()?;
+    let (width, height) = renderer.arena_size(cols, rows.saturating_sub(HUD_ROWS));
+    log!(Info, "tutorial: arena {width}x{height}");
+    let mut game = Game::new(width, height, Rng::random_seed(), FoodRules::default());
+    game.camera = Camera::new(options.camera);
+    game.smooth = options.smooth;
+    game.resume_countdown = options.resume_countdown;
+    game.start_tutorial();
+    Ok(game)
+}
+
+/// The run or the screen open as a line of JSON, with the `features`
+/// agreed with the client asking.
+fn state(screen: &Screen, features: Features) -> String {
+    match screen.game() {
+        Some(game) => stream::state_line(game, &[], features),
+        None => format!(r#"{{"screen": {}}}"#, json::string(screen.name())),
+    }
+}
+
+/// Records a change a control client made to the run in its replay, so the
+/// replay still plays back the same, and replies that it's done.
+fn record(replay: &mut Option<Replay>, input: Input) -> String {
+    // Before the first frame the replay hasn't started, and will start
+    // from the game with the change made.
+    if let Some(replay) = replay {
+        replay.push(0., Some(input));
+    }
+    "ok".to_string()
+}
+
+/// Lets everyone know `player` has taken their seat, or taken it `back`,
+/// replying with the seat's `token` and where things stand for the client
+/// to pick up from: the run as a line of JSON, with the `features` agreed
+/// with it, or the lobby.
+fn joined(
+    player: usize,
+    back: bool,
+    token: u64,
+    features: Features,
+    screen: &Screen,
+    options: &Options,
+    toasts: &mut Toasts,
+) -> String {
+    let name = player_name(screen, options, player);
+    let notice = if back {
+        format!("{name} is back")
+    } else {
+        format!("{name} joined")
+    };
+    log!(Info, "{notice}");
+    toasts.push(ToastKind::Network, notice);
+    let state = match screen {
+        Screen::Lobby(lobby) => lobby.to_json(),
+        screen => state(screen, features),
+    };
+    format!(r#"{{"token": "{token:016x}", "state": {state}}}"#)
+}
+
+/// What `player` goes by, in the run being played if there is one.
+fn player_name<'a>(screen: &'a Screen, options: &'a Options, player: usize) -> &'a str {
+    let players = match screen {
+        Screen::Lobby(lobby) => &lobby.players,
+        screen => screen.game().map_or(&options.players, |game| &game.players),
+    };
+    &players[player].name
+}
+
+/// Passes everything that's happened in `game` since the last call on to
+/// whatever reacts to it.
+fn dispatch_events(
+    game: &mut Game,
+    streams: &mut [StateStream],
+    sounds: &mut Sounds,
+    mut announcer: Option<&mut Announcer>,
+    mut adaptive: Option<&mut Adaptive>,
+    toasts: &mut Toasts,
+) {
+    for (kind, text) in game.drain_notices() {
+        toasts.push(kind, text);
+    }
+    for event in game.drain_events() {
+        if let Some(announcer) = &mut announcer {
+            announcer.record(&event, game);
+        }
+        if let Some(adaptive) = &mut adaptive {
+            adaptive.record(&event);
+        }
+        if let Some(sound) = Sound::for_event(&event) {
+            sounds.play(sound);
+        }
+        for stream in &mut *streams {
+            stream.record(&event);
+        }
+        if let GameEvent::Lesson(Lesson::Done) = event {
+            // Remembered so it isn't started on its own again.
+            let marked = LifetimeStats::load().and_then(|mut stats| {
+                stats.tutorial_done = true;
+                stats.save()
+            });
+            if let Err(err) = marked {
+                log!(Warn, "couldn't record the tutorial as done: {err}");
+            }
+        }
+        if let GameEvent::PlayerOut { .. } = event {
+            toasts.push(ToastKind::Notice, event.describe());
+        }
+        if let GameEvent::PowerUpStarted(_) | GameEvent::ShieldPickedUp = event {
+            toasts.push(ToastKind::PowerUp, event.describe());
+        }
+        if let GameEvent::Achievement(achievement) = event {
+            toasts.push(
+                ToastKind::Achievement,
+                format!("achievement unlocked: {}", achievement.name()),
+            );
+            if game.achievements.unsaved {
+                // Retried on the next unlock if it fails.
+                if let Err(err) = game.achievements.save() {
+                    log!(Warn, "couldn't save achievements: {err}");
+                }
+            }
+        }
+        game.log.push(game.stats.time, event);
+    }
+}
+
+/// The game-over screen for `game`, with the run entered into the high
+/// scores and lifetime stats, and its replay kept as the last run's.
+fn game_over(game: &Game, replay: Option<&Replay>) -> Screen {
+    if let Some(replay) = replay {
+        // Only needed for exporting later, so a failure isn't worth stopping
+        // for.
+        if let Err(err) = replay.save_last() {
+            log!(Warn, "couldn't save the replay: {err}");
+        }
+    }
+    log!(
+        Info,
+        "run over: score {}, {}",
+        game.stats.score,
+        game.stats.death.map_or("gave up", |cause| cause.describe())
+    );
+    let mut game_over = GameOverScreen::new(game);
+    game_over.record();
+    if let Some(run) = &game.speedrun {
+        match run.record() {
+            Ok(true) => {
+                game_over.notice = Some("new personal best splits".to_string());
+                game_over.best_splits = true;
+            }
+            Ok(false) => {}
+            Err(err) => log!(Warn, "couldn't save the splits: {err}"),
+        }
+    }
+    if let Ok(mut lifetime) = LifetimeStats::load() {
+        let stats = &game.stats;
+        let cause = stats.death.map_or("gave up", |cause| cause.describe());
+        lifetime.add(
+            stats.score,
+            stats.apples,
+            stats.time,
+            game.player.len(),
+            cause,
+        );
+        if stats.death.is_some() {
+            lifetime
+                .heatmap
+                .add(game.player.head, game.width, game.height);
+        }
+        // Stats are a nice extra; a failed save shouldn't hold up the game.
+        if let Err(err) = lifetime.save() {
+            log!(Warn, "couldn't save lifetime stats: {err}");
+        }
+    }
+    Screen::GameOver(game_over)
+}
+
+/// What can be picked from the pause menu.
+#[derive(Clone, Copy, Debug)]
+enum PauseChoice {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+/// What is currently on screen.
+enum Screen {
+    Title(TitleScreen),
+    Playing(Game),
+    Dying(DeathScreen),
+    GameOver(GameOverScreen),
+    Achievements(AchievementsScreen),
+    Stats(StatsScreen),
+    Load(LoadScreen),
+    Editor(EditorScreen),
+    Challenge(ChallengeScreen),
+    Modes(ModeScreen),
+    Rules(RulesScreen),
+    Lobby(LobbyScreen),
+    Tournament(TournamentScreen),
+    Settings(SettingsScreen),
+}
+
+impl Screen {
+    fn name(&self) -> &'static str {
+        match self {
+            Screen::Title(_) => "title",
+            Screen::Playing(_) => "playing",
+            Screen::Dying(_) => "dying",
+            Screen::GameOver(_) => "game over",
+            Screen::Achievements(_) => "achievements",
+            Screen::Stats(_) => "stats",
+            Screen::Load(_) => "load",
+            Screen::Editor(_) => "editor",
+            Screen::Challenge(_) => "challenge",
+            Screen::Modes(_) => "modes",
+            Screen::Rules(_) => "custom game",
+            Screen::Lobby(_) => "lobby",
+            Screen::Tournament(_) => "tournament",
+            Screen::Settings(_) => "settings",
+        }
+    }
+
+    /// What the terminal's window title says while it's showing.
+    fn window_title(&self) -> String {
+        let (score, state) = match self {
+            Screen::Playing(game) if game.paused => (game.stats.score, "paused"),
+            Screen::Playing(game) => (game.stats.score, game.mode_name()),
+            Screen::Dying(death) => (death.game.stats.score, "dead"),
+            Screen::GameOver(game_over) => (game_over.stats.score, "game over"),
+            screen => return format!("snake \u{2014} {}", screen.name()),
+        };
+        format!("snake \u{2014} {score} pts \u{2014} {state}")
+    }
+
+    /// The background track that goes with it.
+    #[cfg(feature = "music")]
+    fn track(&self) -> Track {
+        match self {
+            Screen::Playing(_) | Screen::Dying(_) => Track::Gameplay,
+            Screen::GameOver(_) => Track::GameOver,
+            Screen::Title(_)
+            | Screen::Achievements(_)
+            | Screen::Stats(_)
+            | Screen::Load(_)
+            | Screen::Editor(_)
+            | Screen::Challenge(_)
+            | Screen::Modes(_)
+            | Screen::Rules(_)
+            | Screen::Lobby(_)
+            | Screen::Tournament(_)
+            | Screen::Settings(_) => Track::Menu,
+        }
+    }
+
+    /// The run being played or just lost, if any.
+    fn game(&self) -> Option<&Game> {
+        match self {
+            Screen::Playing(game) => Some(game),
+            Screen::Dying(death) => Some(&death.game),
+            Screen::Settings(settings) => Some(&settings.game),
+            Screen::Title(_)
+            | Screen::GameOver(_)
+            | Screen::Achievements(_)
+            | Screen::Stats(_)
+            | Screen::Load(_)
+            | Screen::Editor(_)
+            | Screen::Challenge(_)
+            | Screen::Modes(_)
+            | Screen::Rules(_)
+            | Screen::Lobby(_)
+            | Screen::Tournament(_) => None,
+        }
+    }
+
+    fn draw(&self, renderer: &Renderer) -> Result<Frame, SnakeError> {
+        let (width, height) = terminal_size()?;
+        Ok(match self {
+            Screen::Title(title) => title.draw(renderer, width, height),
+            Screen::Playing(game) => game.draw(renderer, width, height),
+            Screen::Dying(death) => death.draw(renderer, width, height),
+            Screen::GameOver(game_over) => game_over.draw(renderer, width, height),
+            Screen::Achievements(achievements) => achievements.draw(renderer, width, height),
+            Screen::Stats(stats) => stats.draw(renderer, width, height),
+            Screen::Load(load) => load.draw(renderer, width, height),
+            Screen::Editor(editor) => editor.draw(renderer, width, height),
+            Screen::Challenge(entry) => entry.draw(renderer, width, height),
+            Screen::Modes(modes) => modes.draw(renderer, width, height),
+            Screen::Rules(custom) => custom.draw(renderer, width, height),
+            Screen::Lobby(lobby) => lobby.draw(renderer, width, height),
+            Screen::Tournament(tournament) => tournament.draw(renderer, width, height),
+            Screen::Settings(settings) => settings.draw(renderer, width, height),
+        })
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 enum Commands {
     RotatePlayer(f64),
+    /// Turning the second snake in co-op, or the only one otherwise.
+    RotatePartner(f64),
     Extend,
     Shrink,
+    Confirm,
+    Pause,
+    Rewind,
+    Menu,
+    Achievements,
+    /// Opening the chat line, in a run.
+    Chat,
+    Stats,
+    QuickSave,
+    QuickLoad,
+    /// A number key, counting from 0 for the `1` key.
+    Slot(usize),
+    Delete,
+    Export,
+    Screenshot,
+    Log,
+    Debug,
+    Help,
+    /// Up and down, through menus.
+    Up,
+    Down,
+    /// Answers to a question.
+    Yes,
+    No,
+    Tutorial,
+    /// Practice mode's editor: starting it, and what it puts down.
+    Edit,
+    Food,
+    Obstacle,
+    /// Where the snake starts, in the level editor.
+    Spawn,
+    Challenge,
+    /// The mode menu.
+    Modes,
+    Tournament,
+    /// The cheat console, in developer builds or with `--dev`.
+    Console,
+    /// Completes what's being typed, with tab.
+    Complete,
+    /// A character typed into a text box, such as a challenge code.
+    Type(char),
+    /// Stop the game and go back to the shell, as `Ctrl-Z`.
+    Suspend,
     Quit,
 }
 
+/// Every key the game knows and what it does, in the order the help lists
+/// them.
+fn keymap() -> Vec<(Key, Commands)> {
+    let right = Commands::RotatePlayer(90_f64.to_radians());
+    let left = Commands::RotatePlayer(-90_f64.to_radians());
+    let mut keymap = vec![
+        (Key::Right, right),
+        (Key::Char('l'), right),
+        (Key::Left, left),
+        (Key::Char('h'), left),
+        (Key::Char('d'), Commands::RotatePartner(90_f64.to_radians())),
+        (
+            Key::Char('a'),
+            Commands::RotatePartner(-90_f64.to_radians()),
+        ),
+        (Key::Char('\n'), Commands::Confirm),
+        (Key::Char(' '), Commands::Confirm),
+        (Key::Up, Commands::Up),
+        (Key::Char('k'), Commands::Up),
+        (Key::Down, Commands::Down),
+        (Key::Char('j'), Commands::Down),
+        (Key::Char('p'), Commands::Pause),
+        (Key::Char('z'), Commands::Rewind),
+        (Key::Char('e'), Commands::Extend),
+        (Key::Char('r'), Commands::Shrink),
+        (Key::Char('m'), Commands::Menu),
+        // Before achievements, which it takes the key from in a run.
+        (Key::Char('t'), Commands::Chat),
+        (Key::Char('t'), Commands::Achievements),
+        (Key::Char('s'), Commands::Stats),
+        (Key::F(5), Commands::QuickSave),
+        (Key::F(9), Commands::QuickLoad),
+    ];
+    for (slot, digit) in ('1'..='9').enumerate() {
+        keymap.push((Key::Char(digit), Commands::Slot(slot)));
+    }
+    keymap.extend([
+        (Key::Char('x'), Commands::Delete),
+        (Key::Delete, Commands::Delete),
+        (Key::Char('c'), Commands::Export),
+        (Key::F(12), Commands::Screenshot),
+        (Key::Char('\t'), Commands::Log),
+        (Key::F(3), Commands::Debug),
+        (Key::Char('?'), Commands::Help),
+        (Key::F(1), Commands::Help),
+        (Key::Char('y'), Commands::Yes),
+        (Key::Char('n'), Commands::No),
+        (Key::Char('u'), Commands::Tutorial),
+        (Key::Char('b'), Commands::Edit),
+        (Key::Char('f'), Commands::Food),
+        (Key::Char('o'), Commands::Obstacle),
+        (Key::Char('g'), Commands::Spawn),
+        (Key::Char('i'), Commands::Challenge),
+        (Key::Char('v'), Commands::Modes),
+        (Key::Char('w'), Commands::Tournament),
+        (Key::Char('~'), Commands::Console),
+        (Key::Ctrl('z'), Commands::Suspend),
+        (Key::Char('q'), Commands::Quit),
+    ]);
+    keymap
+}
+
 impl Commands {
-    fn from_key(key: Key) -> Option<Commands> {
+    /// What `key` does, `playing` a run or not.
+    fn from_key(key: Key, playing: bool) -> Option<Commands> {
+        keymap()
+            .into_iter()
+            .filter(|&(_, command)| playing || !matches!(command, Commands::Chat))
+            .find(|&(bound, _)| bound == key)
+            .map(|(_, command)| command)
+    }
+
+    /// What `key` does while text is being typed: characters are typed in,
+    /// and only enter, tab, backspace and escape do anything else.
+    fn typed(key: Key) -> Option<Commands> {
         match key {
-            Key::Char('q') => Some(Commands::Quit),
-            Key::Char('e') => Some(Commands::Extend),
-            Key::Char('r') => Some(Commands::Shrink),
-            Key::Right | Key::Char('d') | Key::Char('l') => {
-                Some(Commands::RotatePlayer(90_f64.to_radians()))
-            }
-            Key::Left | Key::Char('a') | Key::Char('h') => {
-                Some(Commands::RotatePlayer(-90_f64.to_radians()))
-            }
-            _ => None,
+            Key::Char('\n') => Some(Commands::Confirm),
+            Key::Char('\t') => Some(Commands::Complete),
+            Key::Char(ch) if !ch.is_control() => Some(Commands::Type(ch)),
+            Key::Backspace => Some(Commands::Delete),
+            Key::Esc => Some(Commands::Menu),
+            _ => Commands::from_key(key, false),
+        }
+    }
+
+    /// What it does, as the help puts it.
+    fn describe(self) -> &'static str {
+        match self {
+            Commands::RotatePlayer(angle) if angle > 0. => "turn right",
+            Commands::RotatePlayer(_) => "turn left",
+            Commands::RotatePartner(angle) if angle > 0. => "turn right (co-op: 2nd)",
+            Commands::RotatePartner(_) => "turn left (co-op: 2nd)",
+            Commands::Extend => "grow (editing)",
+            Commands::Shrink => "shrink (editing)",
+            Commands::Confirm => "start, confirm",
+            Commands::Pause => "pause",
+            Commands::Rewind => "rewind",
+            Commands::Menu => "back to the menu",
+            Commands::Achievements => "achievements",
+            Commands::Chat => "chat (in a run)",
+            Commands::Stats => "stats",
+            Commands::QuickSave => "quick save",
+            Commands::QuickLoad => "quick load",
+            Commands::Slot(_) => "pick a save slot",
+            Commands::Delete => "delete a save",
+            Commands::Export => "export the replay",
+            Commands::Screenshot => "screenshot",
+            Commands::Log => "event log",
+            Commands::Debug => "debug overlay",
+            Commands::Help => "this help",
+            Commands::Up => "menu up",
+            Commands::Down => "menu down",
+            Commands::Yes => "yes",
+            Commands::No => "no",
+            Commands::Tutorial => "tutorial",
+            Commands::Edit => "edit the board (practice)",
+            Commands::Food => "place food (editing)",
+            Commands::Obstacle => "place a block (editing)",
+            Commands::Spawn => "set or turn the start (level editor)",
+            Commands::Challenge => "enter a challenge code",
+            Commands::Modes => "choose a mode",
+            Commands::Tournament => "start a tournament",
+            Commands::Console => "cheat console (--dev)",
+            Commands::Complete => "complete a command (console)",
+            Commands::Type(_) => "type a character",
+            Commands::Suspend => "suspend to the shell",
+            Commands::Quit => "quit",
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 struct Vec2 {
     x: f64,
     y: f64,
@@ -177,7 +2685,11 @@ impl Vec2 {
         self
     }
     pub fn inside_rectange(&self, p1: Vec2, p2: Vec2) -> bool {
-        return self.x >= p1.x && self.y >= p1.y && self.x <= p2.x && self.y <= p2.y;
+        self.x >= p1.x && self.y >= p1.y && self.x <= p2.x && self.y <= p2.y
+    }
+
+    pub fn distance(self, other: Vec2) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
     }
 
     pub fn round(self) -> Self {
@@ -186,173 +2698,109 @@ impl Vec2 {
         Self { x, y }
     }
 
-    fn outside_rectange(&self, p1: Vec2, p2: Vec2) -> bool {
-        return self.x < p1.x && self.y < p1.y && self.x > p2.x && self.y > p2.y;
+    /// The same direction, one cell long.
+    pub fn normalized(self) -> Self {
+        let length = self.x.hypot(self.y);
+        if length == 0. {
+            self
+        } else {
+            Self::new(self.x / length, self.y / length)
+        }
     }
-}
 
-#[derive(Debug, Clone)]
-struct Clock {
-    last_tick: Instant,
-}
-
-impl Clock {
-    fn new() -> Self {
-        let last_tick = Instant::now();
-        Self { last_tick }
-    }
-    fn tick(&mut self, fps: f64) -> f64 {
-        let mut elapsed = self.last_tick.elapsed();
-        if elapsed.as_secs_f64() <= 1. / fps {
-            thread::sleep(Duration::from_secs_f64(1. / fps));
-            elapsed = self.last_tick.elapsed()
-        }
-        self.last_tick = Instant::now();
-        elapsed.as_secs_f64()
+    fn outside_rectange(&self, p1: Vec2, p2: Vec2) -> bool {
+        self.x < p1.x && self.y < p1.y && self.x > p2.x && self.y > p2.y
     }
 }
 
-#[derive(Debug, Clone)]
-struct Snake {
-    len: u32,
-    head: Vec2,
-    body: LinkedList<Vec2>,
-    forward: Vec2,
+/// Screen-relative heading, with y growing downwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
-impl Snake {
-    pub fn new() -> Self {
-        let len = 1;
-        let head = Vec2::new(0.03, 0.03);
-        let forward = Vec2::new(0.11, 0.);
-        let body = LinkedList::new();
-        Snake {
-            len,
-            head,
-            forward,
-            body,
+impl Direction {
+    fn from_name(name: &str) -> Option<Direction> {
+        match name {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            "right" => Some(Direction::Right),
+            _ => None,
         }
     }
 
-    pub fn extend(&mut self) {
-        let newhead = self.head
-            + self
-                .forward
-                .clamp(Vec2::new(-0.01, -0.01), Vec2::new(0.01, 0.01));
-        self.body.push_front(self.head);
-        self.head = newhead;
+    /// A step of one cell this way.
+    fn step(self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::new(0., -1.),
+            Direction::Down => Vec2::new(0., 1.),
+            Direction::Left => Vec2::new(-1., 0.),
+            Direction::Right => Vec2::new(1., 0.),
+        }
     }
 
-    pub fn shrink(&mut self) {
-        self.body.pop_back();
+    /// A quarter turn clockwise.
+    fn clockwise(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
     }
 
-    pub fn r#move(&mut self, dt: f64) {
-        self.body.push_front(self.head);
-        self.head += self.forward * dt;
-        self.body.pop_back();
+    /// The direction of the dominant axis of `v`.
+    fn of(v: Vec2) -> Direction {
+        if v.x.abs() >= v.y.abs() {
+            if v.x < 0. {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        } else if v.y < 0. {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
     }
 
-    pub fn rotate(&mut self, angle: f64) {
-        self.forward.rotate(angle);
+    /// The direction from cell `from` to cell `to`.
+    fn between(from: (u16, u16), to: (u16, u16)) -> Direction {
+        let delta = Vec2::new(to.0 as f64 - from.0 as f64, to.1 as f64 - from.1 as f64);
+        Direction::of(delta)
     }
 
-    fn move_back(&mut self) {
-        self.head -= self.forward;
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
-struct Game {
-    height: u16,
-    width: u16,
-    player: Snake,
-    clock: Clock,
+struct Clock {
+    last_tick: Instant,
 }
 
-impl Game {
+impl Clock {
     fn new() -> Self {
-        let (width, height) = terminal_size().unwrap();
-        let player = Snake::new();
-        let clock = Clock::new();
-        Self {
-            height,
-            width,
-            player,
-            clock,
-        }
-    }
-
-    fn update(&mut self, dt: f64) {
-        if (self.player.head + self.player.forward * dt)
-            .inside_rectange(Vec2::new(0., 0.), Vec2::new(1., 1.))
-        {
-            self.player.r#move(dt);
-        } else if self
-            .player
-            .head
-            .outside_rectange(Vec2::new(0., 0.), Vec2::new(1., 1.))
-        {
-        }
-    }
-
-    fn draw(&self, stdout: &mut termion::raw::RawTerminal<Stdout>) {
-        write!(
-            stdout,
-            "{}{}",
-            termion::clear::All,
-            termion::cursor::Goto(1, 1)
-        )
-        .unwrap();
-        writeln!(
-            stdout,
-            "snake head gamecoord: ({:0.2},{:0.2})",
-            self.player.head.x, self.player.head.y
-        )
-        .unwrap();
-        let snake_termcoord = self.term_coord(self.player.head);
-        writeln!(
-            stdout,
-            "\rsnake head termcoord: ({},{})",
-            snake_termcoord.0, snake_termcoord.1
-        )
-        .unwrap();
-        self.draw_snake(stdout);
-        stdout.flush().unwrap();
-    }
-
-    fn term_coord(&self, v: Vec2) -> (u16, u16) {
-        let x = v.x * self.width as f64;
-        let y = v.y * self.height as f64;
-        return (x as u16 + 1, y as u16 + 1);
+        let last_tick = Instant::now();
+        Self { last_tick }
     }
-
-    pub fn draw_snake(&self, stdout: &mut termion::raw::RawTerminal<Stdout>) {
-        let (mut row, mut col) = self.term_coord(self.player.head);
-        write!(
-            stdout,
-            "{}\u{2588}{}",
-            termion::cursor::Goto(row, col),
-            termion::cursor::Hide,
-        )
-        .unwrap();
-
-        for peice in self.player.body.iter() {
-            (row, col) = self.term_coord(*peice);
-            write!(
-                stdout,
-                "{}\u{2588}{}",
-                termion::cursor::Goto(row, col),
-                termion::cursor::Hide,
-            )
-            .unwrap();
+    fn tick(&mut self, fps: f64) -> f64 {
+        let mut elapsed = self.last_tick.elapsed();
+        if elapsed.as_secs_f64() <= 1. / fps {
+            thread::sleep(Duration::from_secs_f64(1. / fps));
+            elapsed = self.last_tick.elapsed()
         }
-    }
-
-    fn game_coord(&self, x: u16, y: u16) -> Vec2 {
-        let ratio = self.width as f64 / self.height as f64;
-        let x = x as f64 * ratio;
-        let y = y as f64 * ratio;
-        return Vec2 { x, y };
+        self.last_tick = Instant::now();
+        elapsed.as_secs_f64()
     }
 }
@@ -0,0 +1,76 @@
+use crate::render::{
+    Frame,
+    Renderer,
+};
+
+/// A list of choices stacked down the screen, moved through with up and down
+/// and picked with enter.
+#[derive(Clone, Debug)]
+pub struct Menu<T> {
+    items: Vec<(T, &'static str)>,
+    selected: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new(items: Vec<(T, &'static str)>) -> Self {
+        Self { items, selected: 0 }
+    }
+
+    /// Moves the selection up one, round to the bottom from the top.
+    pub fn up(&mut self) {
+        let len = self.items.len().max(1);
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len().max(1);
+    }
+
+    pub fn reset(&mut self) {
+        self.selected = 0;
+    }
+
+    /// Moves the selection onto `item`, if it's in the menu.
+    pub fn select(&mut self, item: T)
+    where
+        T: PartialEq,
+    {
+        if let Some(index) = self.items.iter().position(|&(other, _)| other == item) {
+            self.selected = index;
+        }
+    }
+
+    pub fn chosen(&self) -> Option<T> {
+        self.items.get(self.selected).map(|&(item, _)| item)
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.items
+            .get(self.selected)
+            .map_or("", |&(_, label)| label)
+    }
+
+    /// Rows the menu takes up.
+    pub fn height(&self) -> u16 {
+        self.items.len() as u16
+    }
+
+    /// Draws the items centred from `row` down, marking the selected one.
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame, row: u16) {
+        let colors = &renderer.theme.colors;
+        let width = self
+            .items
+            .iter()
+            .map(|(_, label)| label.len())
+            .max()
+            .unwrap_or(0);
+        for (index, (_, label)) in self.items.iter().enumerate() {
+            let (marker, fg) = if index == self.selected {
+                ('>', colors.head)
+            } else {
+                (' ', colors.wall)
+            };
+            frame.print_centered(row + index as u16, &format!("{marker} {label:<width$}"), fg);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    game::Snapshot,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Largest minimap, in terminal cells, including its border.
+const MAX_WIDTH: u16 = 18;
+const MAX_HEIGHT: u16 = 10;
+
+/// Draws a downsampled view of a `width` by `height` arena into the top-right
+/// corner of `frame`, just below `top`. Each minimap cell stands for a block of
+/// arena cells and shows the most important thing in it: the head, then the
+/// rest of the snake, then food.
+pub fn draw(
+    renderer: &Renderer,
+    frame: &mut Frame,
+    top: u16,
+    width: u16,
+    height: u16,
+    snapshot: &Snapshot,
+) {
+    let map_width = MAX_WIDTH.min(width + 2).min(frame.width());
+    let map_height = MAX_HEIGHT
+        .min(height + 2)
+        .min(frame.height().saturating_sub(top));
+    if map_width < 3 || map_height < 3 {
+        return;
+    }
+    let (inner_width, inner_height) = (map_width - 2, map_height - 2);
+    let left = frame.width() - map_width;
+    let glyphs = &renderer.theme.glyphs;
+    let colors = &renderer.theme.colors;
+
+    let mut map = renderer.frame(map_width, map_height);
+    for col in 0..map_width {
+        map.put(col, 0, glyphs.wall, colors.wall);
+        map.put(col, map_height - 1, glyphs.wall, colors.wall);
+    }
+    for row in 0..map_height {
+        map.put(0, row, glyphs.wall, colors.wall);
+        map.put(map_width - 1, row, glyphs.wall, colors.wall);
+    }
+    let to_map = |x: f64, y: f64| {
+        let col = (x.max(0.) * inner_width as f64 / width as f64) as u16;
+        let row = (y.max(0.) * inner_height as f64 / height as f64) as u16;
+        (1 + col.min(inner_width - 1), 1 + row.min(inner_height - 1))
+    };
+    for food in &snapshot.food {
+        let (col, row) = to_map(food.at.x, food.at.y);
+        let (glyph, fg) = renderer.food_style(food.kind);
+        map.put(col, row, glyph, fg);
+    }
+    for segment in snapshot.segments.iter().skip(1) {
+        let (col, row) = to_map(segment.x, segment.y);
+        map.put(col, row, glyphs.body_horizontal, colors.body);
+    }
+    if let Some(head) = snapshot.segments.first() {
+        let (col, row) = to_map(head.x, head.y);
+        map.put(col, row, glyphs.head[3], colors.head);
+    }
+    frame.blit(&map, left, top);
+}
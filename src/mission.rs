@@ -0,0 +1,160 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    food::APPLE_SCORE,
+    rng::Rng,
+};
+
+/// Something to aim for over a run, on top of the score.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Objective {
+    /// Eat `count` things in a row without turning left.
+    NoLeftTurns { count: u32 },
+    /// Grow to `length` within `seconds` of the start.
+    ReachLength { length: usize, seconds: f64 },
+    /// Build a combo up to `multiplier`.
+    Combo { multiplier: u32 },
+}
+
+impl Objective {
+    pub const ALL: [Objective; 3] = [
+        Objective::NoLeftTurns { count: 5 },
+        Objective::ReachLength {
+            length: 30,
+            seconds: 60.,
+        },
+        Objective::Combo { multiplier: 4 },
+    ];
+
+    /// Points for completing it.
+    pub fn reward(self) -> u32 {
+        match self {
+            Objective::NoLeftTurns { .. } => 10 * APPLE_SCORE,
+            Objective::ReachLength { .. } => 15 * APPLE_SCORE,
+            Objective::Combo { .. } => 10 * APPLE_SCORE,
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            Objective::NoLeftTurns { count } => format!("eat {count} without turning left"),
+            Objective::ReachLength { length, seconds } => {
+                format!("reach length {length} in {seconds}s")
+            }
+            Objective::Combo { multiplier } => format!("build a x{multiplier} combo"),
+        }
+    }
+}
+
+/// What a mission hears about from the game.
+#[derive(Clone, Copy, Debug)]
+pub enum MissionEvent {
+    /// The snake ate something that counts as food.
+    Ate,
+    /// The player asked for a turn, negative angles being to the left.
+    Turned(f64),
+    /// How things stand after a move.
+    Moved {
+        time: f64,
+        length: usize,
+        combo: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MissionState {
+    Active,
+    Complete,
+    Failed,
+}
+
+/// An objective and how far the run has got with it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Mission {
+    pub objective: Objective,
+    pub state: MissionState,
+    /// Progress towards the goal, in whatever the objective counts.
+    progress: u32,
+    /// Seconds into the run, as of the last move.
+    time: f64,
+}
+
+impl Mission {
+    pub fn new(objective: Objective) -> Self {
+        Self {
+            objective,
+            state: MissionState::Active,
+            progress: 0,
+            time: 0.,
+        }
+    }
+
+    pub fn random(rng: &mut Rng) -> Self {
+        let index = rng.below(Objective::ALL.len() as u64) as usize;
+        Mission::new(Objective::ALL[index])
+    }
+
+    /// Updates progress for `event`, returning true if it's what completed
+    /// the mission.
+    pub fn observe(&mut self, event: MissionEvent) -> bool {
+        if self.state != MissionState::Active {
+            return false;
+        }
+        match (self.objective, event) {
+            (Objective::NoLeftTurns { count }, MissionEvent::Ate) => {
+                self.progress += 1;
+                if self.progress >= count {
+                    self.state = MissionState::Complete;
+                }
+            }
+            (Objective::NoLeftTurns { .. }, MissionEvent::Turned(angle)) if angle < 0. => {
+                self.progress = 0;
+            }
+            (
+                Objective::ReachLength { length, seconds },
+                MissionEvent::Moved {
+                    time, length: now, ..
+                },
+            ) => {
+                self.time = time;
+                self.progress = now as u32;
+                if now >= length {
+                    self.state = MissionState::Complete;
+                } else if time > seconds {
+                    self.state = MissionState::Failed;
+                }
+            }
+            (Objective::Combo { multiplier }, MissionEvent::Moved { combo, .. }) => {
+                self.progress = self.progress.max(combo);
+                if combo >= multiplier {
+                    self.state = MissionState::Complete;
+                }
+            }
+            _ => {}
+        }
+        self.state == MissionState::Complete
+    }
+
+    /// A short line on how it's going, for the HUD.
+    pub fn status(&self) -> String {
+        match self.state {
+            MissionState::Complete => "mission complete".to_string(),
+            MissionState::Failed => "mission failed".to_string(),
+            MissionState::Active => match self.objective {
+                Objective::NoLeftTurns { count } => {
+                    format!("no lefts {}/{count}", self.progress)
+                }
+                Objective::ReachLength { length, seconds } => {
+                    let left = (seconds - self.time).max(0.).ceil();
+                    format!("length {}/{length} {left}s", self.progress)
+                }
+                Objective::Combo { multiplier } => {
+                    format!("combo x{}/x{multiplier}", self.progress.max(1))
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use crate::{
+    Direction,
+    Options,
+    food::FoodKind,
+    game::player_tint,
+    menu::Menu,
+    movement::MovementRules,
+    render::{
+        Frame,
+        Renderer,
+    },
+    rules::{
+        CLOSING,
+        HUNGER,
+        Ruleset,
+    },
+    versus,
+};
+
+/// Columns of the widest line of a description.
+const TEXT_WIDTH: usize = 36;
+
+/// Columns between the list of modes and the preview.
+const GAP: u16 = 4;
+
+/// What the last item in the mode menu, for mixing the rules by hand, says
+/// about itself.
+const CUSTOM: &str =
+    "Switch the rules on and off one at a time, and play whatever mix of them you like.";
+
+/// A way to play, as picked from the mode menu: what it's called, what it's
+/// like, a little picture of it, and the rules and any other options it
+/// plays with. A mode only has to be in `MODES` to be offered.
+#[derive(Debug)]
+pub struct ModeSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The arena in miniature: `#` is wall, `*` food, `O` a portal and `=`
+    /// trail, with the snake's head `@` and its body `o`, and any second
+    /// snake's head `&` and body `x`.
+    pub preview: &'static [&'static str],
+    /// The rules it plays by, in place of any others.
+    pub rules: Ruleset,
+    /// Sets up anything else about the mode, on top of the options the game
+    /// was started with.
+    pub apply: fn(&mut Options),
+}
+
+pub const MODES: &[ModeSpec] = &[
+    ModeSpec {
+        name: "classic",
+        description: "Eat to grow longer. Running into the walls or your own tail ends the run.",
+        preview: &[
+            "##################",
+            "#                #",
+            "#   oooo@    *   #",
+            "#   o            #",
+            "#   ooo          #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "wrap",
+        description: "Classic snake where the edges wrap round: leaving one side comes back in on the other.",
+        preview: &[
+            "                  ",
+            "                  ",
+            "oo@          *  oo",
+            "                 o",
+            "              ooo ",
+            "                  ",
+            "                  ",
+        ],
+        rules: Ruleset {
+            wraps: true,
+            ..Ruleset::CLASSIC
+        },
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "light cycle",
+        description: "No food: the snake leaves a trail that never goes away, and lasting longest is all that counts.",
+        preview: &[
+            "##################",
+            "#                #",
+            "# ========       #",
+            "#        =  @    #",
+            "#        ====    #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset {
+            trail: true,
+            ..Ruleset::CLASSIC
+        },
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "time attack",
+        description: "A race against a fixed clock, eating as much as possible before time runs out.",
+        preview: &[
+            "##################",
+            "#  *          *  #",
+            "#      oooo@     #",
+            "#      o         #",
+            "#  *   o      *  #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset {
+            timer: true,
+            ..Ruleset::CLASSIC
+        },
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "zen",
+        description: "Nothing can end the run: the walls wrap round and the snake passes over itself. Just watch it grow.",
+        preview: &[
+            "                  ",
+            "   *              ",
+            "ooooooooo@      oo",
+            "                o ",
+            "                o ",
+            "           *    o ",
+            "                  ",
+        ],
+        rules: Ruleset::ZEN,
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "mission",
+        description: "The run comes with a goal to meet, shown under the score, on top of the usual rules.",
+        preview: &[
+            "##################",
+            "#                #",
+            "#  *  oooo@      #",
+            "#        #####   #",
+            "#            *   #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.mission = true,
+    },
+    ModeSpec {
+        name: "portals",
+        description: "Two pairs of portals link far corners of the arena. Going in one comes out of its twin.",
+        preview: &[
+            "##################",
+            "# O          O   #",
+            "#     oooo@      #",
+            "#     o       *  #",
+            "#   O          O #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.portals = 2,
+    },
+    ModeSpec {
+        name: "closing walls",
+        description: "Every twenty seconds the walls close in a cell, so the arena keeps getting smaller.",
+        preview: &[
+            "##################",
+            "##################",
+            "###  oooo@   *  ##",
+            "###  o          ##",
+            "##################",
+            "##################",
+            "##################",
+        ],
+        rules: Ruleset {
+            closing: Some(CLOSING),
+            ..Ruleset::CLASSIC
+        },
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "fog",
+        description: "Only the cells around the head can be seen, and eating pushes the fog back.",
+        preview: &[
+            "                  ",
+            "                  ",
+            "      oo@ *       ",
+            "      o           ",
+            "                  ",
+            "                  ",
+            "                  ",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.fog = Some(5.),
+    },
+    ModeSpec {
+        name: "hunger",
+        description: "The snake loses a segment every five seconds it goes without eating.",
+        preview: &[
+            "##################",
+            "#                #",
+            "#   oo@          #",
+            "#          *     #",
+            "#                #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset {
+            hunger: Some(HUNGER),
+            ..Ruleset::CLASSIC
+        },
+        apply: |_| {},
+    },
+    ModeSpec {
+        name: "ice",
+        description: "Turns take a move to bite, and the snake slides on a cell after eating before it can turn.",
+        preview: &[
+            "##################",
+            "#                #",
+            "#  oooooo@  *    #",
+            "#  o             #",
+            "#  o             #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.movement = MovementRules::Ice,
+    },
+    ModeSpec {
+        name: "co-op",
+        description: "Two snakes on one keyboard, arrows and WASD, sharing a score and lives. They pass through each other, and there's food for both.",
+        preview: &[
+            "##################",
+            "#  *             #",
+            "#   oooo@  xx    #",
+            "#   o       x  * #",
+            "#  *      &xx    #",
+            "#             *  #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.coop = true,
+    },
+    ModeSpec {
+        name: "versus",
+        description: "Two snakes on one keyboard, arrows and WASD, each out to make the other crash. First to three rounds wins.",
+        preview: &[
+            "##################",
+            "#        *       #",
+            "#  oooo@   &xxx  #",
+            "#  o         x   #",
+            "#  o    *    x   #",
+            "#                #",
+            "##################",
+        ],
+        rules: Ruleset::CLASSIC,
+        apply: |options| options.versus = Some(versus::FIRST_TO),
+    },
+];
+
+impl ModeSpec {
+    /// `options` with this mode played on them.
+    pub fn options(&self, options: &Options) -> Options {
+        let mut options = options.clone();
+        options.rules = self.rules;
+        (self.apply)(&mut options);
+        options
+    }
+
+    /// Draws the preview with the theme's own glyphs.
+    fn draw_preview(&self, renderer: &Renderer) -> Frame {
+        let (glyphs, colors) = (&renderer.theme.glyphs, &renderer.theme.colors);
+        let width = self
+            .preview
+            .iter()
+            .map(|line| line.len())
+            .max()
+            .unwrap_or(0);
+        let mut frame = renderer.frame(width as u16, self.preview.len() as u16);
+        let (mut head, mut partner) = (None, None);
+        for (row, line) in self.preview.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                let at = (col as u16, row as u16);
+                match ch {
+                    '#' => frame.put(at.0, at.1, glyphs.wall, colors.wall),
+                    '=' => frame.put(at.0, at.1, glyphs.wall, colors.body),
+                    'O' => frame.put(at.0, at.1, glyphs.portal, colors.portal),
+                    '*' => {
+                        let (glyph, fg) = renderer.food_style(FoodKind::Normal);
+                        frame.put(at.0, at.1, glyph, fg);
+                    }
+                    '@' => head = Some(at),
+                    '&' => partner = Some(at),
+                    _ => {}
+                }
+            }
+        }
+        let snakes = [
+            (partner, 'x', player_tint(renderer, 1, None)),
+            (head, 'o', None),
+        ];
+        for (head, body, tint) in snakes {
+            let Some(head) = head else {
+                continue;
+            };
+            let cells = self.snake(head, body);
+            let heading = match cells.get(1) {
+                Some(&neck) => Direction::between(neck, head),
+                None => Direction::Right,
+            };
+            renderer.draw_snake_cells_in(&mut frame, heading, &cells, tint);
+        }
+        frame
+    }
+
+    /// A snake in the preview, head first, followed along its `body` cells
+    /// from `head`, round the edges as well as across.
+    fn snake(&self, head: (u16, u16), body: char) -> Vec<(u16, u16)> {
+        let width = self
+            .preview
+            .iter()
+            .map(|line| line.len())
+            .max()
+            .unwrap_or(1) as u16;
+        let height = self.preview.len().max(1) as u16;
+        let on_body = |(col, row): (u16, u16)| {
+            self.preview
+                .get(row as usize)
+                .and_then(|line| line.chars().nth(col as usize))
+                == Some(body)
+        };
+        let mut cells = vec![head];
+        let mut at = head;
+        loop {
+            let next = [
+                ((at.0 + 1) % width, at.1),
+                ((at.0 + width - 1) % width, at.1),
+                (at.0, (at.1 + 1) % height),
+                (at.0, (at.1 + height - 1) % height),
+            ]
+            .into_iter()
+            .find(|&cell| on_body(cell) && !cells.contains(&cell));
+            match next {
+                Some(cell) => {
+                    cells.push(cell);
+                    at = cell;
+                }
+                None => return cells,
+            }
+        }
+    }
+}
+
+/// Picking a mode to play, each shown with a description and a preview,
+/// or going on to mix the rules by hand.
+#[derive(Clone, Debug)]
+pub struct ModeScreen {
+    /// The modes, then `None` for a custom game.
+    pub modes: Menu<Option<&'static ModeSpec>>,
+}
+
+impl ModeScreen {
+    pub fn new() -> Self {
+        let mut items: Vec<(Option<&'static ModeSpec>, &'static str)> =
+            MODES.iter().map(|mode| (Some(mode), mode.name)).collect();
+        items.push((None, "custom"));
+        Self {
+            modes: Menu::new(items),
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let list_width = MODES.iter().map(|mode| mode.name.len()).max().unwrap_or(0) as u16 + 2;
+        let chosen = self.modes.chosen().flatten();
+        let inset = chosen.map(|mode| {
+            let preview = mode.draw_preview(renderer);
+            let mut inset = renderer.frame(preview.width() + 2, preview.height() + 2);
+            inset.blit(&preview, 1, 1);
+            inset.outline(
+                0,
+                0,
+                inset.width(),
+                inset.height(),
+                renderer.caps.unicode,
+                colors.wall,
+            );
+            inset
+        });
+        let text = wrap(chosen.map_or(CUSTOM, |mode| mode.description), TEXT_WIDTH);
+        let inset_height = inset.as_ref().map_or(0, |inset| inset.height() + 1);
+        let right_width = inset
+            .as_ref()
+            .map_or(0, Frame::width)
+            .max(TEXT_WIDTH as u16);
+        let block_width = list_width + GAP + right_width;
+        let block_height = self.modes.height().max(inset_height + text.len() as u16);
+        let col = width.saturating_sub(block_width) / 2;
+        let row = height.saturating_sub(block_height + 4) / 2;
+        frame.print_centered(row, "CHOOSE A MODE", colors.head);
+
+        let mut list = renderer.frame(list_width, self.modes.height());
+        self.modes.draw(renderer, &mut list, 0);
+        frame.blit(&list, col, row + 2);
+
+        let right = col + list_width + GAP;
+        if let Some(inset) = &inset {
+            frame.blit(inset, right, row + 2);
+        }
+        for (index, line) in text.iter().enumerate() {
+            let at = row + 2 + inset_height + index as u16;
+            frame.print_fg(right, at, line, colors.body);
+        }
+        frame.print_centered(
+            row + 3 + block_height,
+            "up/down  choose   enter  play   m  back",
+            colors.wall,
+        );
+        frame
+    }
+}
+
+/// `text` broken into lines at most `width` long, between words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
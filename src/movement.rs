@@ -0,0 +1,73 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Degrees analog steering turns by for each key press, unless set.
+pub const DEFAULT_STEER: u16 = 15;
+
+/// How the snake responds to steering. Each variant is a different kind of
+/// ground to move on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MovementRules {
+    /// Turns are taken on the very next move.
+    #[default]
+    Classic,
+    /// Turns take a move to bite, and the snake slides on a cell after eating
+    /// before it can turn again.
+    Ice,
+    /// Left and right turn the heading by this many degrees, and the snake
+    /// glides off the grid. Anything within `reach` of the head counts as
+    /// hit.
+    Analog(u16),
+}
+
+impl MovementRules {
+    pub fn from_name(name: &str) -> Option<MovementRules> {
+        match name {
+            "classic" => Some(MovementRules::Classic),
+            "ice" => Some(MovementRules::Ice),
+            "analog" => Some(MovementRules::Analog(DEFAULT_STEER)),
+            _ => None,
+        }
+    }
+
+    /// Moves a queued turn waits before the snake takes it.
+    pub fn turn_delay(self) -> u32 {
+        match self {
+            MovementRules::Classic | MovementRules::Analog(_) => 0,
+            MovementRules::Ice => 1,
+        }
+    }
+
+    /// Cells the snake carries straight on for after eating, whatever the
+    /// player does.
+    pub fn slide(self) -> u32 {
+        match self {
+            MovementRules::Classic | MovementRules::Analog(_) => 0,
+            MovementRules::Ice => 1,
+        }
+    }
+
+    /// The turn actually made for a quarter turn asked for by the player.
+    pub fn turn_angle(self, angle: f64) -> f64 {
+        match self {
+            MovementRules::Classic | MovementRules::Ice => angle,
+            MovementRules::Analog(degrees) => angle.signum() * (degrees as f64).to_radians(),
+        }
+    }
+
+    /// Whether the heading is kept to the four grid directions.
+    pub fn on_grid(self) -> bool {
+        !matches!(self, MovementRules::Analog(_))
+    }
+
+    /// How close, in cells, two positions have to be to touch. On the grid
+    /// that only happens when they're on the same cell.
+    pub fn reach(self) -> f64 {
+        match self {
+            MovementRules::Classic | MovementRules::Ice => 0.5,
+            MovementRules::Analog(_) => 0.75,
+        }
+    }
+}
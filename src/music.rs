@@ -0,0 +1,121 @@
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::logging::log;
+
+/// Seconds a crossfade between two tracks takes.
+const CROSSFADE: f64 = 1.5;
+
+/// How much faster the gameplay track plays at most, as the snake speeds up.
+const MAX_TEMPO: f64 = 1.25;
+
+/// A looping background track, one for each kind of screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Track {
+    Menu,
+    Gameplay,
+    GameOver,
+}
+
+impl Track {
+    const ALL: [Track; 3] = [Track::Menu, Track::Gameplay, Track::GameOver];
+
+    /// The name its file goes by in the music directory, before the
+    /// extension.
+    fn stem(self) -> &'static str {
+        match self {
+            Track::Menu => "menu",
+            Track::Gameplay => "game",
+            Track::GameOver => "gameover",
+        }
+    }
+}
+
+/// Background music from tracks the player provides, crossfading whenever
+/// the screen calls for a different one. With no audio library to hand this
+/// keeps track of what would be playing, at what volume and tempo, and logs
+/// it, for a backend to pick up.
+#[derive(Clone, Debug, Default)]
+pub struct Music {
+    files: Vec<(Track, PathBuf)>,
+    playing: Option<Track>,
+    /// The track fading out, with the seconds it has left.
+    fading: Option<(Track, f64)>,
+    /// How fast the current track plays, 1 being as recorded.
+    tempo: f64,
+}
+
+impl Music {
+    /// Finds the tracks in `dir`: `menu`, `game` and `gameover`, with any
+    /// extension. A track without a file is silence.
+    pub fn load(dir: &Path) -> Result<Music, String> {
+        let entries = fs::read_dir(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+        let mut files = Vec::new();
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let stem = path.file_stem().and_then(|stem| stem.to_str());
+            if let Some(&track) = Track::ALL.iter().find(|track| Some(track.stem()) == stem) {
+                files.push((track, path));
+            }
+        }
+        Ok(Music {
+            files,
+            tempo: 1.,
+            ..Music::default()
+        })
+    }
+
+    fn file(&self, track: Track) -> Option<&Path> {
+        self.files
+            .iter()
+            .find(|(found, _)| *found == track)
+            .map(|(_, path)| path.as_path())
+    }
+
+    /// Moves on by `dt` seconds with `track` wanted, starting a crossfade if
+    /// it's not the one playing. `speed` is how many times its starting
+    /// speed the snake is going, which the tempo follows.
+    pub fn update(&mut self, dt: f64, track: Track, speed: f64) {
+        if self.files.is_empty() {
+            return;
+        }
+        if self.playing != Some(track) {
+            if let Some(file) = self.file(track) {
+                log!(Debug, "music: fading to {}", file.display());
+            }
+            self.fading = self.playing.map(|playing| (playing, CROSSFADE));
+            self.playing = Some(track);
+        }
+        if let Some((_, left)) = &mut self.fading {
+            *left -= dt;
+            if *left <= 0. {
+                self.fading = None;
+            }
+        }
+        self.tempo = match track {
+            Track::Gameplay => (1. + (speed - 1.) / 4.).clamp(1., MAX_TEMPO),
+            Track::Menu | Track::GameOver => 1.,
+        };
+    }
+
+    /// The tracks sounding right now with their volumes from 0 to 1, the
+    /// one fading out first.
+    pub fn levels(&self) -> Vec<(&Path, f64)> {
+        let fade = self.fading.map_or(1., |(_, left)| 1. - left / CROSSFADE);
+        let outgoing = self
+            .fading
+            .and_then(|(track, _)| Some((self.file(track)?, 1. - fade)));
+        let current = self
+            .playing
+            .and_then(|track| Some((self.file(track)?, fade)));
+        outgoing.into_iter().chain(current).collect()
+    }
+
+    pub fn tempo(&self) -> f64 {
+        self.tempo
+    }
+}
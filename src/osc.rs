@@ -0,0 +1,64 @@
+use crate::caps::Multiplexer;
+
+/// Saves the window title on the terminal's title stack, to put back with
+/// `pop_title`. Multiplexers keep their own titles, so there's nothing to
+/// save inside one.
+pub fn push_title(multiplexer: Option<Multiplexer>) -> &'static str {
+    match multiplexer {
+        None => "\x1b[22;0t",
+        Some(_) => "",
+    }
+}
+
+pub fn pop_title(multiplexer: Option<Multiplexer>) -> &'static str {
+    match multiplexer {
+        None => "\x1b[23;0t",
+        Some(_) => "",
+    }
+}
+
+/// Sets the window title to `text`. Like the rest of these, terminals that
+/// don't know it ignore it. tmux takes the usual sequence as the pane's
+/// title, and screen has one of its own for the window's.
+pub fn title(text: &str, multiplexer: Option<Multiplexer>) -> String {
+    match multiplexer {
+        Some(Multiplexer::Screen) => format!("\x1bk{}\x1b\\", clean(text)),
+        Some(Multiplexer::Tmux) | None => format!("\x1b]2;{}\x07", clean(text)),
+    }
+}
+
+/// A desktop notification, sent both as OSC 9, which iTerm2, Windows
+/// Terminal and others show, and OSC 777, which urxvt, foot and VTE
+/// terminals do. Multiplexers would swallow them, so they're passed through
+/// to the terminal outside.
+pub fn notify(title: &str, body: &str, multiplexer: Option<Multiplexer>) -> String {
+    let (title, body) = (clean(title), clean(body));
+    [
+        format!("\x1b]9;{title}: {body}\x07"),
+        format!("\x1b]777;notify;{title};{body}\x07"),
+    ]
+    .iter()
+    .map(|sequence| passthrough(sequence, multiplexer))
+    .collect()
+}
+
+/// `sequence` wrapped to go straight through `multiplexer` to the terminal.
+/// tmux only does so with `allow-passthrough` on, and wants the escapes
+/// inside doubled.
+fn passthrough(sequence: &str, multiplexer: Option<Multiplexer>) -> String {
+    match multiplexer {
+        None => sequence.to_string(),
+        Some(Multiplexer::Tmux) => {
+            format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+        }
+        Some(Multiplexer::Screen) => format!("\x1bP{sequence}\x1b\\"),
+    }
+}
+
+/// `text` without anything that could end the sequence early.
+fn clean(text: &str) -> String {
+    text.chars()
+        .filter(|ch| !ch.is_control())
+        .map(|ch| if ch == ';' { ',' } else { ch })
+        .collect()
+}
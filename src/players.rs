@@ -0,0 +1,65 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::theme::Color;
+
+/// Who's playing a game for two: the name each player goes by and the
+/// colour their snake's drawn in, if not the usual. The first player is on
+/// the arrows, the second on WASD.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Player {
+    pub name: String,
+    /// A colour as themes write them, such as `bright-green` or `#rrggbb`.
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl Player {
+    /// The usual player `index`, counting from 0, named by their number.
+    pub fn numbered(index: usize) -> Self {
+        Self {
+            name: format!("player {}", index + 1),
+            color: None,
+        }
+    }
+
+    /// Reads a player from `name` or `name:color`, as given on the command
+    /// line, checking the colour.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (name, color) = match value.split_once(':') {
+            Some((name, color)) => (name, Some(color.to_string())),
+            None => (value, None),
+        };
+        let player = Self {
+            name: name.trim().to_string(),
+            color,
+        };
+        player.check()?;
+        Ok(player)
+    }
+
+    /// Fails if the name's blank or the colour isn't one.
+    pub fn check(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("a player's name can't be blank".to_string());
+        }
+        if let Some(color) = &self.color
+            && Color::parse(color).is_none()
+        {
+            return Err(format!("{}: unknown colour `{color}`", self.name));
+        }
+        Ok(())
+    }
+
+    /// The colour their snake's drawn in, if they picked one.
+    pub fn tint(&self) -> Option<Color> {
+        self.color.as_deref().and_then(Color::parse)
+    }
+}
+
+/// Both players, numbered unless set.
+pub fn default_players() -> [Player; 2] {
+    [Player::numbered(0), Player::numbered(1)]
+}
@@ -0,0 +1,153 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::rng::Rng;
+
+/// Longest a power-up can be stacked up to, in seconds.
+const MAX_DURATION: f64 = 20.;
+
+/// How much faster the snake goes with a speed boost.
+const SPEED_FACTOR: f64 = 1.5;
+
+/// How much slower the snake goes in slow motion.
+const SLOW_FACTOR: f64 = 0.6;
+
+/// Something a pickup does to the snake for a while.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PowerUp {
+    /// Moves faster.
+    Speed,
+    /// Moves slower.
+    Slow,
+    /// Passes through its own body.
+    Ghost,
+    /// Doubles the points for anything eaten.
+    Double,
+    /// Pulls the nearest food towards the head.
+    Magnet,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 5] = [
+        PowerUp::Speed,
+        PowerUp::Slow,
+        PowerUp::Ghost,
+        PowerUp::Double,
+        PowerUp::Magnet,
+    ];
+
+    pub fn random(rng: &mut Rng) -> PowerUp {
+        PowerUp::ALL[rng.below(PowerUp::ALL.len() as u64) as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUp::Speed => "speed",
+            PowerUp::Slow => "slow motion",
+            PowerUp::Ghost => "ghost",
+            PowerUp::Double => "double points",
+            PowerUp::Magnet => "magnet",
+        }
+    }
+
+    /// Seconds one pickup lasts.
+    pub fn duration(self) -> f64 {
+        match self {
+            PowerUp::Speed | PowerUp::Slow => 6.,
+            PowerUp::Ghost => 5.,
+            PowerUp::Double => 10.,
+            PowerUp::Magnet => 8.,
+        }
+    }
+
+    /// The one that can't be active at the same time, if any.
+    fn opposite(self) -> Option<PowerUp> {
+        match self {
+            PowerUp::Speed => Some(PowerUp::Slow),
+            PowerUp::Slow => Some(PowerUp::Speed),
+            PowerUp::Ghost | PowerUp::Double | PowerUp::Magnet => None,
+        }
+    }
+
+    /// The character it's shown as, on the board and in the HUD.
+    pub fn icon(self, unicode: bool) -> char {
+        match (self, unicode) {
+            (PowerUp::Speed, true) => '»',
+            (PowerUp::Slow, true) => '«',
+            (PowerUp::Ghost, true) => '◌',
+            (PowerUp::Double, true) => '×',
+            (PowerUp::Magnet, true) => '∩',
+            (PowerUp::Speed, false) => '>',
+            (PowerUp::Slow, false) => '<',
+            (PowerUp::Ghost, false) => 'g',
+            (PowerUp::Double, false) => '2',
+            (PowerUp::Magnet, false) => 'm',
+        }
+    }
+}
+
+/// The power-ups in effect, each with the seconds it has left.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PowerUps {
+    active: Vec<(PowerUp, f64)>,
+}
+
+impl PowerUps {
+    /// Starts `power`. Picking up one that's already running adds to its time
+    /// rather than running two, and picking up the opposite of a running one
+    /// cancels that instead. Returns the one cancelled, if any.
+    pub fn grant(&mut self, power: PowerUp) -> Option<PowerUp> {
+        let cancelled = power.opposite().filter(|&opposite| self.has(opposite));
+        if let Some(opposite) = cancelled {
+            self.active.retain(|&(active, _)| active != opposite);
+        }
+        match self.active.iter_mut().find(|(active, _)| *active == power) {
+            Some((_, left)) => *left = (*left + power.duration()).min(MAX_DURATION),
+            None => self.active.push((power, power.duration())),
+        }
+        cancelled
+    }
+
+    /// Counts the power-ups down, returning those that have just run out.
+    pub fn update(&mut self, dt: f64) -> Vec<PowerUp> {
+        for (_, left) in &mut self.active {
+            *left -= dt;
+        }
+        let ended = self
+            .active
+            .iter()
+            .filter(|&&(_, left)| left <= 0.)
+            .map(|&(power, _)| power)
+            .collect();
+        self.active.retain(|&(_, left)| left > 0.);
+        ended
+    }
+
+    pub fn has(&self, power: PowerUp) -> bool {
+        self.active.iter().any(|&(active, _)| active == power)
+    }
+
+    /// What the snake's speed is multiplied by.
+    pub fn speed_factor(&self) -> f64 {
+        if self.has(PowerUp::Speed) {
+            SPEED_FACTOR
+        } else if self.has(PowerUp::Slow) {
+            SLOW_FACTOR
+        } else {
+            1.
+        }
+    }
+
+    /// What points are multiplied by.
+    pub fn score_factor(&self) -> u32 {
+        if self.has(PowerUp::Double) { 2 } else { 1 }
+    }
+
+    /// The running power-ups in the order they were picked up, with seconds
+    /// left.
+    pub fn iter(&self) -> impl Iterator<Item = (PowerUp, f64)> + '_ {
+        self.active.iter().copied()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    Commands,
+    Vec2,
+    game::Game,
+    replay::Input,
+};
+
+/// Cells ahead of the head the cursor starts.
+const CURSOR_START: f64 = 3.;
+
+/// What the keys do while editing, for the line under the board.
+pub const HINT: &str = "arrows move  f food  o block  x clear  e/r length  enter play";
+
+/// Stops the clock and puts the cursor on the board for placing things,
+/// returning the pause for the run's replay if it wasn't already paused.
+pub fn start(game: &mut Game) -> Option<Input> {
+    let ahead = game.player.head + (game.player.heading() * CURSOR_START).round();
+    game.cursor = Some(clamp(game, ahead));
+    if game.paused {
+        None
+    } else {
+        game.toggle_pause();
+        Some(Input::Pause)
+    }
+}
+
+/// Carries out `command` while editing, returning any change it made to
+/// the run for its replay. Enter, or the key that started editing, goes
+/// back to playing.
+pub fn edit(game: &mut Game, command: Commands) -> Option<Input> {
+    let cursor = game.cursor?;
+    let step = match command {
+        Commands::RotatePlayer(angle) | Commands::RotatePartner(angle) => {
+            Some(Vec2::new(angle.signum(), 0.))
+        }
+        Commands::Up => Some(Vec2::new(0., -1.)),
+        Commands::Down => Some(Vec2::new(0., 1.)),
+        _ => None,
+    };
+    if let Some(step) = step {
+        game.cursor = Some(clamp(game, cursor + step));
+        return None;
+    }
+    let changed = match command {
+        Commands::Food => game.place_food(cursor).map(|()| Input::PlaceFood(cursor)),
+        Commands::Obstacle => game
+            .place_obstacle(cursor)
+            .map(|()| Input::PlaceObstacle(cursor)),
+        Commands::Delete => game.clear_cell(cursor).map(|()| Input::ClearCell(cursor)),
+        Commands::Extend => {
+            game.player.lengthen(1);
+            Ok(Input::Lengthen)
+        }
+        Commands::Shrink => {
+            game.player.shrink();
+            Ok(Input::Shrink)
+        }
+        Commands::Edit | Commands::Confirm | Commands::Pause => {
+            game.cursor = None;
+            game.toggle_pause();
+            Ok(Input::Pause)
+        }
+        _ => return None,
+    };
+    changed.map_err(|err| game.notify(err)).ok()
+}
+
+/// `cell` moved onto the nearest cell of the arena.
+fn clamp(game: &Game, cell: Vec2) -> Vec2 {
+    Vec2::new(
+        cell.x.clamp(0., game.width.saturating_sub(1) as f64),
+        cell.y.clamp(0., game.height.saturating_sub(1) as f64),
+    )
+}
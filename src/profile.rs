@@ -0,0 +1,79 @@
+use std::{
+    fmt::Write as _,
+    fs,
+};
+
+/// Where one frame of the loop spent its time, in seconds.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameTime {
+    /// Taking input and moving the game on.
+    pub simulate: f64,
+    /// Drawing the frame and writing it out.
+    pub render: f64,
+    /// Waiting for the next frame.
+    pub sleep: f64,
+}
+
+/// Every frame's times from a session, kept to write out when it ends, for
+/// tracking down stutter.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    frames: Vec<FrameTime>,
+}
+
+impl Profile {
+    pub fn push(&mut self, frame: FrameTime) {
+        self.frames.push(frame);
+    }
+
+    /// Writes every frame to `path` as CSV, in milliseconds.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut csv = String::from("frame,simulate_ms,render_ms,sleep_ms\n");
+        for (index, frame) in self.frames.iter().enumerate() {
+            let _ = writeln!(
+                csv,
+                "{index},{:.3},{:.3},{:.3}",
+                frame.simulate * 1000.,
+                frame.render * 1000.,
+                frame.sleep * 1000.
+            );
+        }
+        fs::write(path, csv).map_err(|err| format!("{path}: {err}"))
+    }
+
+    /// The 50th, 95th and 99th percentiles of each time, a line each.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} frames profiled\n", self.frames.len());
+        let columns = [
+            ("simulate", self.sorted(|frame| frame.simulate)),
+            ("render", self.sorted(|frame| frame.render)),
+            ("sleep", self.sorted(|frame| frame.sleep)),
+        ];
+        for (name, times) in columns {
+            let _ = writeln!(
+                summary,
+                "{name:<9} p50 {:.2}  p95 {:.2}  p99 {:.2} ms",
+                percentile(&times, 0.5),
+                percentile(&times, 0.95),
+                percentile(&times, 0.99),
+            );
+        }
+        summary
+    }
+
+    /// One of the times from every frame, smallest first.
+    fn sorted(&self, time: impl Fn(&FrameTime) -> f64) -> Vec<f64> {
+        let mut times: Vec<f64> = self.frames.iter().map(time).collect();
+        times.sort_by(f64::total_cmp);
+        times
+    }
+}
+
+/// The value at percentile `p` of sorted `times`, in milliseconds.
+pub fn percentile(times: &[f64], p: f64) -> f64 {
+    if times.is_empty() {
+        return 0.;
+    }
+    let index = ((times.len() - 1) as f64 * p).round() as usize;
+    times[index] * 1000.
+}
@@ -0,0 +1,765 @@
+use std::io::{
+    self,
+    Write,
+};
+
+use crate::{
+    Direction,
+    Vec2,
+    caps::{
+        Capabilities,
+        ColorDepth,
+    },
+    food::FoodKind,
+    theme::{
+        Color,
+        Theme,
+    },
+};
+
+/// How game space is rasterised onto the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    /// One full block character per terminal cell.
+    Block,
+    /// Braille patterns, giving a 2x4 dot grid inside every terminal cell.
+    Braille,
+}
+
+impl RenderMode {
+    pub fn from_name(name: &str) -> Option<RenderMode> {
+        match name {
+            "block" => Some(RenderMode::Block),
+            "braille" => Some(RenderMode::Braille),
+            _ => None,
+        }
+    }
+
+    /// How many arena cells fit across and down one terminal cell.
+    pub fn cell_size(self) -> (u16, u16) {
+        match self {
+            RenderMode::Block => (1, 1),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+}
+
+/// One character on screen along with its colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    const BLANK: Cell = Cell {
+        ch: ' ',
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+}
+
+/// Off-screen copy of the terminal that everything is drawn into before it is
+/// written out in one go.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        let cells = vec![Cell::BLANK; width as usize * height as usize];
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn fill(&mut self, cell: Cell) {
+        self.cells.fill(cell);
+    }
+
+    pub fn get(&self, col: u16, row: u16) -> Option<Cell> {
+        if col < self.width && row < self.height {
+            Some(self.cells[row as usize * self.width as usize + col as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the character and foreground of a cell, keeping its background.
+    pub fn put(&mut self, col: u16, row: u16, ch: char, fg: Color) {
+        if col < self.width && row < self.height {
+            let cell = &mut self.cells[row as usize * self.width as usize + col as usize];
+            cell.ch = ch;
+            cell.fg = fg;
+        }
+    }
+
+    /// Sets the background of a cell, keeping what's drawn on it.
+    pub fn highlight(&mut self, col: u16, row: u16, bg: Color) {
+        if col < self.width && row < self.height {
+            self.cells[row as usize * self.width as usize + col as usize].bg = bg;
+        }
+    }
+
+    pub fn print(&mut self, col: u16, row: u16, text: &str) {
+        self.print_fg(col, row, text, Color::Default);
+    }
+
+    /// Draws a box `width` by `height` cells, edges included, with its top
+    /// left at `col`, `row`. Line-drawing characters need `unicode`.
+    pub fn outline(
+        &mut self,
+        col: u16,
+        row: u16,
+        width: u16,
+        height: u16,
+        unicode: bool,
+        fg: Color,
+    ) {
+        if width < 2 || height < 2 {
+            return;
+        }
+        let (horizontal, vertical, corners) = if unicode {
+            ('─', '│', ['┌', '┐', '└', '┘'])
+        } else {
+            ('-', '|', ['+'; 4])
+        };
+        let (right, bottom) = (col + width - 1, row + height - 1);
+        for x in col + 1..right {
+            self.put(x, row, horizontal, fg);
+            self.put(x, bottom, horizontal, fg);
+        }
+        for y in row + 1..bottom {
+            self.put(col, y, vertical, fg);
+            self.put(right, y, vertical, fg);
+        }
+        self.put(col, row, corners[0], fg);
+        self.put(right, row, corners[1], fg);
+        self.put(col, bottom, corners[2], fg);
+        self.put(right, bottom, corners[3], fg);
+    }
+
+    /// Prints `text` horizontally centred on `row`.
+    pub fn print_centered(&mut self, row: u16, text: &str, fg: Color) {
+        let col = self.width.saturating_sub(text.chars().count() as u16) / 2;
+        self.print_fg(col, row, text, fg);
+    }
+
+    pub fn print_fg(&mut self, col: u16, row: u16, text: &str, fg: Color) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(col + i as u16, row, ch, fg);
+        }
+    }
+
+    pub fn present(&self, out: &mut impl Write, depth: ColorDepth) -> io::Result<()> {
+        for (row, line) in self.cells.chunks(self.width.max(1) as usize).enumerate() {
+            write!(out, "{}", termion::cursor::Goto(1, row as u16 + 1))?;
+            let mut current = None;
+            for cell in line {
+                if current != Some((cell.fg, cell.bg)) {
+                    cell.fg.write_fg(out, depth)?;
+                    cell.bg.write_bg(out, depth)?;
+                    current = Some((cell.fg, cell.bg));
+                }
+                write!(out, "{}", cell.ch)?;
+            }
+        }
+        Color::Default.write_fg(out, depth)?;
+        Color::Default.write_bg(out, depth)?;
+        write!(out, "{}", termion::cursor::Hide)?;
+        out.flush()
+    }
+
+    /// The characters on screen, one line per row with trailing spaces cut.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for line in self.cells.chunks(self.width.max(1) as usize) {
+            let line: String = line.iter().map(|cell| cell.ch).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Like `to_text`, but coloured with escape codes. Colours are reset at
+    /// the end of every line so each can be pasted on its own.
+    pub fn to_ansi(&self, depth: ColorDepth) -> String {
+        let mut out = String::new();
+        for line in self.cells.chunks(self.width.max(1) as usize) {
+            let mut current = None;
+            for cell in line {
+                if current != Some((cell.fg, cell.bg)) {
+                    out.push_str(&cell.fg.fg_escape(depth));
+                    out.push_str(&cell.bg.bg_escape(depth));
+                    current = Some((cell.fg, cell.bg));
+                }
+                out.push(cell.ch);
+            }
+            out.push_str(&Color::Default.fg_escape(depth));
+            out.push_str(&Color::Default.bg_escape(depth));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Copies `other` onto this frame with its top-left corner at `col, row`.
+    pub fn blit(&mut self, other: &Frame, col: u16, row: u16) {
+        self.blit_offset(other, col as i32, row as i32);
+    }
+
+    /// The `width` by `height` part of this frame starting at `col, row`.
+    pub fn crop(&self, col: u16, row: u16, width: u16, height: u16) -> Frame {
+        let mut cropped = Frame::new(width, height);
+        cropped.blit_offset(self, -(col as i32), -(row as i32));
+        cropped
+    }
+
+    /// The frame blown up `factor` times each way, every cell repeated
+    /// across and down.
+    pub fn scaled(&self, factor: u16) -> Frame {
+        if factor <= 1 {
+            return self.clone();
+        }
+        let mut scaled = Frame::new(self.width * factor, self.height * factor);
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let col = (i % self.width as usize) as u16 * factor;
+            let row = (i / self.width as usize) as u16 * factor;
+            for y in row..row + factor {
+                for x in col..col + factor {
+                    scaled.cells[y as usize * scaled.width as usize + x as usize] = cell;
+                }
+            }
+        }
+        scaled
+    }
+
+    /// Like `blit`, but `other` may hang off any edge.
+    pub fn blit_offset(&mut self, other: &Frame, col: i32, row: i32) {
+        for (i, &cell) in other.cells.iter().enumerate() {
+            let x = col + (i % other.width as usize) as i32;
+            let y = row + (i / other.width as usize) as i32;
+            if (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+                self.cells[y as usize * self.width as usize + x as usize] = cell;
+            }
+        }
+    }
+
+    /// Plots coloured arena cells using the requested mode. Later points win
+    /// when several land in the same terminal cell.
+    pub fn plot(&mut self, mode: RenderMode, points: impl Iterator<Item = (Vec2, Color)>) {
+        match mode {
+            RenderMode::Block => {
+                for (p, fg) in points {
+                    let (col, row) = cell_of(p);
+                    self.put(col, row, '\u{2588}', fg);
+                }
+            }
+            RenderMode::Braille => {
+                let mut canvas = BrailleCanvas::new(self.width, self.height);
+                for (p, fg) in points {
+                    let (x, y) = cell_of(p);
+                    canvas.set(x, y, fg);
+                }
+                canvas.blit(self);
+            }
+        }
+    }
+}
+
+/// The arena cell a position falls in. Anything left of or above the arena
+/// lands on its edge, which only matters for a snake that just died there.
+fn cell_of(p: Vec2) -> (u16, u16) {
+    (p.x.max(0.) as u16, p.y.max(0.) as u16)
+}
+
+/// Stands in for the terminal, keeping everything presented to it as one
+/// string per frame, so output can be checked without a terminal to show
+/// it. A frame ends where it's flushed, as `present` does last thing.
+#[derive(Clone, Debug, Default)]
+pub struct Capture {
+    pub frames: Vec<String>,
+    pending: Vec<u8>,
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let frame = String::from_utf8_lossy(&self.pending).into_owned();
+        self.frames.push(frame);
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Draws game objects into frames according to the chosen mode and theme.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    pub mode: RenderMode,
+    pub theme: Theme,
+    pub caps: Capabilities,
+    /// Whether block mode gives each arena cell two terminal columns, so the
+    /// roughly 1:2 terminal cells add up to squares.
+    pub square: bool,
+    /// Leave out blinking, flashes, effects and gradients.
+    pub reduced_motion: bool,
+    /// How many times over the arena is blown up each way, for seeing it
+    /// from across the room.
+    pub scale: u16,
+}
+
+impl Renderer {
+    /// Settles on the closest mode and glyphs to those asked for that the
+    /// terminal can show.
+    pub fn new(mode: RenderMode, theme: Theme, caps: Capabilities, square: bool) -> Self {
+        let mode = match mode {
+            RenderMode::Braille if !caps.braille => RenderMode::Block,
+            mode => mode,
+        };
+        let theme = if caps.unicode { theme } else { theme.ascii() };
+        Self {
+            mode,
+            theme,
+            caps,
+            square,
+            reduced_motion: false,
+            scale: 1,
+        }
+    }
+
+    /// Terminal columns each arena cell is drawn across. Braille dots are
+    /// already about square, so only block mode is ever widened.
+    pub fn cell_columns(&self) -> u16 {
+        if self.square && self.mode == RenderMode::Block {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// A frame of the given size filled with the theme's background.
+    pub fn frame(&self, width: u16, height: u16) -> Frame {
+        let mut frame = Frame::new(width, height);
+        frame.fill(Cell {
+            ch: self.theme.glyphs.background,
+            fg: Color::Default,
+            bg: self.theme.colors.background,
+        });
+        frame
+    }
+
+    /// How many arena cells fit in `cols` by `rows` terminal cells.
+    pub fn arena_size(&self, cols: u16, rows: u16) -> (u16, u16) {
+        let (x, y) = self.mode.cell_size();
+        let (cols, rows) = (cols / self.scale.max(1), rows / self.scale.max(1));
+        (cols / self.cell_columns() * x, rows * y)
+    }
+
+    /// A background-filled frame just big enough for an arena of the given
+    /// size in cells.
+    pub fn arena_frame(&self, width: u16, height: u16) -> Frame {
+        let (x, y) = self.mode.cell_size();
+        self.frame(width.div_ceil(x) * self.cell_columns(), height.div_ceil(y))
+    }
+
+    /// The terminal cell an arena cell is drawn in, or the leftmost of them
+    /// when it's widened.
+    pub fn screen_cell(&self, p: Vec2) -> (u16, u16) {
+        let (x, y) = self.mode.cell_size();
+        let (col, row) = cell_of(p);
+        (col / x * self.cell_columns(), row / y)
+    }
+
+    /// A HUD gauge `width` characters wide, `fill` of the way full.
+    pub fn bar(&self, fill: f64, width: usize) -> String {
+        let (full, empty) = if self.caps.unicode {
+            ('█', '░')
+        } else {
+            ('=', '-')
+        };
+        let filled = (fill.clamp(0., 1.) * width as f64).ceil() as usize;
+        (0..width)
+            .map(|i| if i < filled { full } else { empty })
+            .collect()
+    }
+
+    /// Sets the background of every terminal cell arena cell `p` is drawn in.
+    pub fn highlight_cell(&self, frame: &mut Frame, p: Vec2, bg: Color) {
+        let (col, row) = self.screen_cell(p);
+        for i in 0..self.cell_columns() {
+            frame.highlight(col + i, row, bg);
+        }
+    }
+
+    pub fn present(&self, frame: &Frame, out: &mut impl Write) -> io::Result<()> {
+        frame.present(out, self.caps.color)
+    }
+
+    /// The colour of segment `index` of a snake `len` segments long, in
+    /// `tint`'s head and body colours if given rather than the theme's. Body
+    /// gradients need at least 256 colours; with fewer the plain body colour
+    /// reads better than a banded approximation.
+    fn segment_color(&self, index: usize, len: usize, tint: Option<(Color, Color)>) -> Color {
+        let colors = &self.theme.colors;
+        match tint {
+            Some((head, _)) if index == 0 => return head,
+            Some((_, body)) => return body,
+            None => {}
+        }
+        match colors.gradient {
+            Some((start, end))
+                if self.caps.color >= ColorDepth::Ansi256 && !self.reduced_motion =>
+            {
+                let t = if len > 1 {
+                    index as f64 / (len - 1) as f64
+                } else {
+                    0.
+                };
+                start.lerp(end, t)
+            }
+            _ if index == 0 => colors.head,
+            _ => colors.body,
+        }
+    }
+
+    /// Draws a snake whose arena cells are given head first. Block mode picks a
+    /// glyph per cell from the neighbouring segments so turns read as corners,
+    /// and joins up widened cells across the column between them.
+    pub fn draw_snake(&self, frame: &mut Frame, heading: Vec2, segments: &[Vec2]) {
+        self.draw_snake_in(frame, heading, segments, None);
+    }
+
+    /// Draws a snake as `draw_snake` does, but in `tint`'s head and body
+    /// colours, if given, to tell it apart from another.
+    pub fn draw_snake_in(
+        &self,
+        frame: &mut Frame,
+        heading: Vec2,
+        segments: &[Vec2],
+        tint: Option<(Color, Color)>,
+    ) {
+        if self.mode == RenderMode::Braille {
+            let len = segments.len();
+            let points = segments
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, &p)| (p, self.segment_color(i, len, tint)));
+            frame.plot(self.mode, points);
+            return;
+        }
+        let cells: Vec<(u16, u16)> = segments.iter().map(|&p| self.screen_cell(p)).collect();
+        self.draw_snake_cells_in(frame, Direction::of(heading), &cells, tint);
+        if self.cell_columns() > 1 {
+            for (i, pair) in cells.windows(2).enumerate() {
+                let ((a, row), (b, other_row)) = (pair[0], pair[1]);
+                if row == other_row && a.abs_diff(b) == 2 {
+                    let fg = self.segment_color(i + 1, cells.len(), tint);
+                    frame.put(a.min(b) + 1, row, self.theme.glyphs.body_horizontal, fg);
+                }
+            }
+        }
+    }
+
+    /// Redraws the ends of a block-mode snake as partly filled cells: the head
+    /// `progress` of the way in from the cell behind it, and the tail
+    /// `1 - progress` of the way out of the cell it's leaving, in `tint`'s
+    /// colours if given.
+    pub fn draw_motion(
+        &self,
+        frame: &mut Frame,
+        segments: &[Vec2],
+        vacated: Option<Vec2>,
+        progress: f64,
+        tint: Option<(Color, Color)>,
+    ) {
+        // Partly filled cells blown up leave gaps down the middle.
+        if self.mode != RenderMode::Block || !self.caps.unicode || self.scale > 1 {
+            return;
+        }
+        let len = segments.len();
+        let heads = segments.first().zip(segments.get(1));
+        let heads = heads
+            .filter(|&(&head, &neck)| self.linked(self.screen_cell(head), self.screen_cell(neck)));
+        if let Some((&head, &neck)) = heads {
+            let side = Direction::between(cell_of(head), cell_of(neck));
+            self.draw_partial(
+                frame,
+                head,
+                side,
+                progress,
+                self.segment_color(0, len, tint),
+            );
+        }
+        if let (Some(vacated), Some(&tail)) = (vacated, segments.last()) {
+            let side = Direction::between(cell_of(vacated), cell_of(tail));
+            let fg = self.segment_color(len - 1, len, tint);
+            self.draw_partial(frame, vacated, side, 1. - progress, fg);
+        }
+    }
+
+    /// Fills `fill` of an arena cell starting from its `side` edge. A widened
+    /// cell fills its columns one after the other going across, and together
+    /// going up or down.
+    fn draw_partial(&self, frame: &mut Frame, p: Vec2, side: Direction, fill: f64, fg: Color) {
+        let (col, row) = self.screen_cell(p);
+        let columns = self.cell_columns();
+        for i in 0..columns {
+            let from_side = match side {
+                Direction::Left => i,
+                Direction::Right => columns - 1 - i,
+                Direction::Up | Direction::Down => {
+                    self.draw_partial_cell(frame, col + i, row, side, fill, fg);
+                    continue;
+                }
+            };
+            let fill = fill * columns as f64 - from_side as f64;
+            self.draw_partial_cell(frame, col + i, row, side, fill, fg);
+        }
+    }
+
+    /// Fills `fill` of a terminal cell in eighths, starting from its `side`
+    /// edge. Block characters only grow from the left and bottom, so the other
+    /// two sides are drawn inverted on a solid background, or as the nearest
+    /// of the few blocks that exist when there's no background colour to
+    /// invert to.
+    fn draw_partial_cell(
+        &self,
+        frame: &mut Frame,
+        col: u16,
+        row: u16,
+        side: Direction,
+        fill: f64,
+        fg: Color,
+    ) {
+        let eighths = (fill.clamp(0., 1.) * 8.).round() as u32;
+        let background = self.theme.colors.background;
+        let left = |n: u32| char::from_u32(0x2590 - n).unwrap();
+        let lower = |n: u32| char::from_u32(0x2580 + n).unwrap();
+        let (ch, fg, bg) = match (side, eighths) {
+            (_, 0) => (self.theme.glyphs.background, Color::Default, background),
+            (_, 8) => ('\u{2588}', fg, background),
+            (Direction::Left, n) => (left(n), fg, background),
+            (Direction::Down, n) => (lower(n), fg, background),
+            (Direction::Right, n) if background != Color::Default => (left(8 - n), background, fg),
+            (Direction::Up, n) if background != Color::Default => (lower(8 - n), background, fg),
+            (Direction::Right, n) => (if n < 3 { '\u{2595}' } else { '\u{2590}' }, fg, background),
+            (Direction::Up, n) => (if n < 3 { '\u{2594}' } else { '\u{2580}' }, fg, background),
+        };
+        frame.put(col, row, ch, fg);
+        frame.highlight(col, row, bg);
+    }
+
+    /// The glyph and colour food of `kind` is drawn with.
+    pub fn food_style(&self, kind: FoodKind) -> (char, Color) {
+        let (glyphs, colors) = (&self.theme.glyphs, &self.theme.colors);
+        match kind {
+            FoodKind::Normal => (glyphs.food, colors.food),
+            FoodKind::Bonus => (glyphs.bonus, colors.bonus),
+            FoodKind::Mega => (glyphs.mega, colors.mega),
+            FoodKind::Mouse => (glyphs.mouse, colors.mouse),
+            FoodKind::Fruit => (glyphs.fruit, colors.fruit),
+            FoodKind::Poison => (glyphs.poison, colors.poison),
+            FoodKind::Power(power) => (power.icon(self.caps.unicode), colors.power),
+            FoodKind::Shield => (glyphs.shield, colors.shield),
+        }
+    }
+
+    pub fn draw_food(&self, frame: &mut Frame, food: Vec2, kind: FoodKind) {
+        let (glyph, fg) = self.food_style(kind);
+        match self.mode {
+            RenderMode::Braille if !self.theme.shapes => {
+                frame.plot(self.mode, [(food, fg)].into_iter())
+            }
+            RenderMode::Block | RenderMode::Braille => {
+                let (col, row) = self.screen_cell(food);
+                frame.put(col, row, glyph, fg);
+            }
+        }
+    }
+
+    /// Blanks out every terminal cell whose middle is more than `radius`
+    /// arena cells from `center`.
+    pub fn fog(&self, frame: &mut Frame, center: Vec2, radius: f64) {
+        let (x, y) = self.mode.cell_size();
+        let columns = self.cell_columns();
+        for row in 0..frame.height() {
+            for col in 0..frame.width() {
+                let middle = Vec2::new(
+                    ((col / columns) * x) as f64 + (x as f64 - 1.) / 2.,
+                    (row * y) as f64 + (y as f64 - 1.) / 2.,
+                );
+                let offset = middle - center;
+                if offset.x.hypot(offset.y) > radius {
+                    frame.put(col, row, self.theme.glyphs.background, Color::Default);
+                    frame.highlight(col, row, self.theme.colors.background);
+                }
+            }
+        }
+    }
+
+    /// Fills the terminal cells arena cell `at` is drawn in with wall.
+    pub fn draw_wall(&self, frame: &mut Frame, at: Vec2) {
+        let (col, row) = self.screen_cell(at);
+        for i in 0..self.cell_columns() {
+            frame.put(col + i, row, self.theme.glyphs.wall, self.theme.colors.wall);
+        }
+    }
+
+    pub fn draw_portal(&self, frame: &mut Frame, at: Vec2) {
+        let colors = &self.theme.colors;
+        match self.mode {
+            RenderMode::Block => {
+                let (col, row) = self.screen_cell(at);
+                frame.put(col, row, self.theme.glyphs.portal, colors.portal);
+            }
+            RenderMode::Braille => frame.plot(self.mode, [(at, colors.portal)].into_iter()),
+        }
+    }
+
+    /// Whether terminal cells `a` and `b` hold neighbouring arena cells. They
+    /// don't when the snake has gone through a portal between them.
+    fn linked(&self, a: (u16, u16), b: (u16, u16)) -> bool {
+        let (dx, dy) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        (dy == 0 && dx <= self.cell_columns()) || (dx == 0 && dy <= 1)
+    }
+
+    /// Draws a snake already laid out on terminal cells, head first. Where
+    /// neighbouring segments aren't next to each other the body is drawn as
+    /// carrying straight on across the gap.
+    pub fn draw_snake_cells(&self, frame: &mut Frame, heading: Direction, cells: &[(u16, u16)]) {
+        self.draw_snake_cells_in(frame, heading, cells, None);
+    }
+
+    /// Draws a snake laid out on terminal cells as `draw_snake_cells` does,
+    /// but in `tint`'s head and body colours, if given.
+    pub fn draw_snake_cells_in(
+        &self,
+        frame: &mut Frame,
+        heading: Direction,
+        cells: &[(u16, u16)],
+        tint: Option<(Color, Color)>,
+    ) {
+        for (i, &(col, row)) in cells.iter().enumerate().rev() {
+            let fg = self.segment_color(i, cells.len(), tint);
+            if i == 0 {
+                frame.put(col, row, self.head_glyph(heading), fg);
+            } else {
+                let towards_tail = cells
+                    .get(i + 1)
+                    .filter(|&&next| self.linked(cells[i], next))
+                    .map(|&next| Direction::between(cells[i], next));
+                let towards_head = if self.linked(cells[i], cells[i - 1]) {
+                    Direction::between(cells[i], cells[i - 1])
+                } else if let Some(towards_tail) = towards_tail {
+                    towards_tail.opposite()
+                } else {
+                    heading
+                };
+                frame.put(col, row, self.body_glyph(towards_head, towards_tail), fg);
+            }
+        }
+    }
+
+    fn head_glyph(&self, direction: Direction) -> char {
+        let [up, down, left, right] = self.theme.glyphs.head;
+        match direction {
+            Direction::Up => up,
+            Direction::Down => down,
+            Direction::Left => left,
+            Direction::Right => right,
+        }
+    }
+
+    /// Picks the segment glyph joining the neighbours in the given directions.
+    /// Blown-up cells are filled in solid, since lines and corners repeated
+    /// side by side no longer join up.
+    fn body_glyph(&self, towards_head: Direction, towards_tail: Option<Direction>) -> char {
+        use Direction::*;
+        if self.scale > 1 && self.caps.unicode {
+            return '█';
+        }
+        let glyphs = &self.theme.glyphs;
+        let [down_right, down_left, up_right, up_left] = glyphs.corners;
+        match (
+            towards_head,
+            towards_tail.unwrap_or(towards_head.opposite()),
+        ) {
+            (Up | Down, Up | Down) => glyphs.body_vertical,
+            (Left | Right, Left | Right) => glyphs.body_horizontal,
+            (Down, Right) | (Right, Down) => down_right,
+            (Down, Left) | (Left, Down) => down_left,
+            (Up, Right) | (Right, Up) => up_right,
+            (Up, Left) | (Left, Up) => up_left,
+        }
+    }
+}
+
+/// Dot bits of a braille cell, indexed by `[row][col]`.
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// A grid of braille dots, two columns and four rows per terminal cell. Each
+/// cell takes the colour of the last dot set in it.
+#[derive(Debug, Clone)]
+struct BrailleCanvas {
+    width: u16,
+    height: u16,
+    cells: Vec<(u8, Color)>,
+}
+
+impl BrailleCanvas {
+    fn new(width: u16, height: u16) -> Self {
+        let cells = vec![(0, Color::Default); width as usize * height as usize];
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    fn set(&mut self, x: u16, y: u16, fg: Color) {
+        let (col, row) = (x / 2, y / 4);
+        if col < self.width && row < self.height {
+            let cell = &mut self.cells[row as usize * self.width as usize + col as usize];
+            cell.0 |= BRAILLE_DOTS[(y % 4) as usize][(x % 2) as usize];
+            cell.1 = fg;
+        }
+    }
+
+    /// Writes every non-empty braille cell over the frame, merging its dots
+    /// with any braille already drawn there.
+    fn blit(&self, frame: &mut Frame) {
+        for (i, &(mut bits, fg)) in self.cells.iter().enumerate() {
+            if bits == 0 {
+                continue;
+            }
+            let col = (i % self.width as usize) as u16;
+            let row = (i / self.width as usize) as u16;
+            if let Some(cell) = frame.get(col, row)
+                && let Some(existing) = (cell.ch as u32).checked_sub(0x2800)
+                && existing <= 0xff
+            {
+                bits |= existing as u8;
+            }
+            let ch = char::from_u32(0x2800 + bits as u32).unwrap();
+            frame.put(col, row, ch, fg);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    io::Write,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Vec2,
+    config,
+    console::Cheat,
+    game::{
+        Game,
+        HUD_ROWS,
+    },
+    json,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Where the last finished run is kept, in the data directory.
+pub const LAST_REPLAY: &str = "last-replay.toml";
+
+/// Something the player did that changes how a run plays out.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum Input {
+    Steer(f64),
+    /// Turning the second snake in co-op.
+    SteerPartner(f64),
+    /// Growing by a segment, from when that had a key of its own. Kept so
+    /// runs from then still play back.
+    Extend,
+    Shrink,
+    Pause,
+    Rewind,
+    /// Speed set through the control socket, as a multiple of the usual.
+    Speed(f64),
+    /// Food placed through the control socket or in practice mode.
+    PlaceFood(Vec2),
+    /// Practice mode edits.
+    PlaceObstacle(Vec2),
+    ClearCell(Vec2),
+    Lengthen,
+    /// A command typed into the console.
+    Cheat(Cheat),
+}
+
+impl Input {
+    /// Does to `game` what the input did when it was recorded. Edits that
+    /// weren't possible then aren't now either, so their errors are dropped.
+    pub fn apply(self, game: &mut Game) {
+        match self {
+            Input::Steer(angle) => game.steer(angle),
+            Input::SteerPartner(angle) => game.steer_partner(angle),
+            Input::Extend | Input::Lengthen => game.player.lengthen(1),
+            Input::Shrink => game.player.shrink(),
+            Input::Pause => game.toggle_pause(),
+            Input::Rewind => {
+                game.rewind();
+            }
+            Input::Speed(factor) => game.set_speed(factor),
+            Input::PlaceFood(cell) => {
+                let _ = game.place_food(cell);
+            }
+            Input::PlaceObstacle(cell) => {
+                let _ = game.place_obstacle(cell);
+            }
+            Input::ClearCell(cell) => {
+                let _ = game.clear_cell(cell);
+            }
+            Input::Cheat(cheat) => {
+                let _ = cheat.apply(game);
+            }
+        }
+    }
+}
+
+/// One pass of the game loop: the input taken, if any, and the seconds the
+/// game was then advanced by.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct ReplayFrame {
+    pub dt: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Input>,
+}
+
+/// A whole run: the game as it started and everything that happened to it
+/// after. The game is deterministic, so playing the frames back against the
+/// start reproduces the run exactly.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Replay {
+    /// Terminal size the run was played at.
+    pub cols: u16,
+    pub rows: u16,
+    pub start: Game,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn new(start: &Game, cols: u16, rows: u16) -> Self {
+        Self {
+            cols,
+            rows,
+            start: start.clone(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, dt: f64, input: Option<Input>) {
+        self.frames.push(ReplayFrame { dt, input });
+    }
+
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        toml::from_str(&text).map_err(|err| format!("{path}: {err}"))
+    }
+
+    /// Keeps the replay as the last run's, for exporting later.
+    pub fn save_last(&self) -> Result<(), String> {
+        config::save_data(LAST_REPLAY, self)
+    }
+
+    /// Plays the run back, passing `each` the seconds since the start and the
+    /// game after every frame, until it returns false.
+    fn play(&self, renderer: &Renderer, mut each: impl FnMut(f64, &Game) -> bool) {
+        let mut game = self.start.clone();
+        let (width, height) = renderer.arena_size(self.cols, self.rows.saturating_sub(HUD_ROWS));
+        let mut time = 0.;
+        for frame in &self.frames {
+            if let Some(input) = frame.input {
+                input.apply(&mut game);
+            }
+            game.track(width, height);
+            game.update(frame.dt);
+            game.drain_events();
+            time += frame.dt;
+            if !each(time, &game) {
+                return;
+            }
+        }
+    }
+
+    /// Frame `index` of the run as it was on screen, counting the first as
+    /// one, or the start itself for zero.
+    pub fn frame(&self, renderer: &Renderer, index: usize) -> Result<Frame, String> {
+        if index == 0 {
+            return Ok(self.start.draw(renderer, self.cols, self.rows));
+        }
+        if index > self.frames.len() {
+            return Err(format!("the replay only has {} frames", self.frames.len()));
+        }
+        let mut seen = 0;
+        let mut found = None;
+        self.play(renderer, |_, game| {
+            seen += 1;
+            if seen == index {
+                found = Some(game.draw(renderer, self.cols, self.rows));
+            }
+            seen < index
+        });
+        found.ok_or_else(|| format!("no frame {index}"))
+    }
+
+    /// Plays the run back, drawing each frame with `renderer`, and writes it
+    /// out as an asciinema v2 cast running `playback` times as fast as the
+    /// run was played. Frames that look the same as the one before are left
+    /// out.
+    pub fn export_cast(
+        &self,
+        renderer: &Renderer,
+        playback: f64,
+        out: &mut impl Write,
+    ) -> Result<(), String> {
+        let (cols, rows) = (self.cols, self.rows);
+        writeln!(
+            out,
+            r#"{{"version": 2, "width": {cols}, "height": {rows}, "title": "snake, seed {}"}}"#,
+            self.start.seed
+        )
+        .map_err(|err| err.to_string())?;
+        let mut last = Vec::new();
+        let mut result = Ok(());
+        self.play(renderer, |time, game| {
+            let mut bytes = Vec::new();
+            if last.is_empty() {
+                bytes.extend(termion::clear::All.to_string().into_bytes());
+            }
+            if let Err(err) = renderer.present(&game.draw(renderer, cols, rows), &mut bytes) {
+                result = Err(err.to_string());
+                return false;
+            }
+            if bytes != last {
+                let text = String::from_utf8_lossy(&bytes);
+                let time = time / playback;
+                result = writeln!(out, "[{time:.6}, \"o\", {}]", json::string(&text))
+                    .map_err(|err| err.to_string());
+                last = bytes;
+            }
+            result.is_ok()
+        });
+        result
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    menu::Menu,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Seconds between segments lost to hunger when it's switched on from the
+/// custom game menu.
+pub const HUNGER: f64 = 5.;
+
+/// Seconds between the walls closing in when they're switched on from the
+/// custom game menu.
+pub const CLOSING: f64 = 20.;
+
+/// The rules a run is played by, each on or off by itself. Named modes are
+/// just some of them switched on; a custom game can have any mix.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Ruleset {
+    /// The walls wrap round rather than ending the run.
+    #[serde(default)]
+    pub wraps: bool,
+    /// Seconds between the snake losing a segment for going without food,
+    /// if it does.
+    pub hunger: Option<f64>,
+    /// Whether poison turns up at all.
+    #[serde(default = "poison_default")]
+    pub poison: bool,
+    /// Seconds between the walls closing in a cell, if they do.
+    pub closing: Option<f64>,
+    /// Light-cycle rules: the snake never loses its tail, so its whole trail
+    /// is a wall, and there's no food. Points come from surviving.
+    pub trail: bool,
+    /// Score what can be scored against a fixed clock.
+    #[serde(default)]
+    pub timer: bool,
+    /// The snake passes over itself, the other snake, obstacles and the
+    /// level's walls. Only the edge of the arena stops it.
+    #[serde(default)]
+    pub pass_through: bool,
+}
+
+fn poison_default() -> bool {
+    true
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::CLASSIC
+    }
+}
+
+/// One of the rules, as switched on and off in the custom game menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    Wraps,
+    Hunger,
+    Poison,
+    Closing,
+    Trail,
+    Timer,
+    PassThrough,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::Wraps,
+        Rule::Hunger,
+        Rule::Poison,
+        Rule::Closing,
+        Rule::Trail,
+        Rule::Timer,
+        Rule::PassThrough,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::Wraps => "wrap walls",
+            Rule::Hunger => "hunger",
+            Rule::Poison => "poison",
+            Rule::Closing => "shrinking arena",
+            Rule::Trail => "light-cycle trail",
+            Rule::Timer => "timer",
+            Rule::PassThrough => "pass through",
+        }
+    }
+}
+
+impl Ruleset {
+    /// Plain snake: solid walls, some poison and nothing else.
+    pub const CLASSIC: Ruleset = Ruleset {
+        wraps: false,
+        hunger: None,
+        poison: true,
+        closing: None,
+        trail: false,
+        timer: false,
+        pass_through: false,
+    };
+
+    /// Zen: classic snake that nothing can end, where the walls wrap round
+    /// and the snake passes over itself.
+    pub const ZEN: Ruleset = Ruleset::CLASSIC.zen();
+
+    /// These rules with everything that could end the run taken away.
+    pub const fn zen(self) -> Ruleset {
+        Ruleset {
+            wraps: true,
+            pass_through: true,
+            hunger: None,
+            poison: false,
+            closing: None,
+            ..self
+        }
+    }
+
+    /// Whether nothing in these rules can end the run.
+    pub fn is_zen(&self) -> bool {
+        *self == self.zen()
+    }
+
+    pub fn has(&self, rule: Rule) -> bool {
+        match rule {
+            Rule::Wraps => self.wraps,
+            Rule::Hunger => self.hunger.is_some(),
+            Rule::Poison => self.poison,
+            Rule::Closing => self.closing.is_some(),
+            Rule::Trail => self.trail,
+            Rule::Timer => self.timer,
+            Rule::PassThrough => self.pass_through,
+        }
+    }
+
+    pub fn toggle(&mut self, rule: Rule) {
+        match rule {
+            Rule::Wraps => self.wraps = !self.wraps,
+            Rule::Hunger => self.hunger = self.hunger.xor(Some(HUNGER)),
+            Rule::Poison => self.poison = !self.poison,
+            Rule::Closing => self.closing = self.closing.xor(Some(CLOSING)),
+            Rule::Trail => self.trail = !self.trail,
+            Rule::Timer => self.timer = !self.timer,
+            Rule::PassThrough => self.pass_through = !self.pass_through,
+        }
+    }
+}
+
+/// The custom game menu: every rule with whether it's on, and a last item
+/// to play with them.
+#[derive(Clone, Debug)]
+pub struct RulesScreen {
+    pub rules: Ruleset,
+    /// The rules, then `None` for playing.
+    pub menu: Menu<Option<Rule>>,
+}
+
+impl RulesScreen {
+    pub fn new(rules: Ruleset) -> Self {
+        let mut items: Vec<(Option<Rule>, &'static str)> = Rule::ALL
+            .into_iter()
+            .map(|rule| (Some(rule), rule.name()))
+            .collect();
+        items.push((None, "play"));
+        Self {
+            rules,
+            menu: Menu::new(items),
+        }
+    }
+
+    /// Switches the selected rule, if it's a rule that's selected.
+    pub fn toggle(&mut self) {
+        if let Some(Some(rule)) = self.menu.chosen() {
+            self.rules.toggle(rule);
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let names = Rule::ALL.map(Rule::name);
+        let name_width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+        let mut row = height.saturating_sub(Rule::ALL.len() as u16 + 6) / 2;
+        frame.print_centered(row, "CUSTOM GAME", colors.head);
+        row += 2;
+        for rule in Rule::ALL {
+            let selected = self.menu.chosen() == Some(Some(rule));
+            let marker = if selected { '>' } else { ' ' };
+            let (state, fg) = match (self.rules.has(rule), selected) {
+                (true, _) => ("on ", colors.bonus),
+                (false, true) => ("off", colors.head),
+                (false, false) => ("off", colors.wall),
+            };
+            let line = format!("{marker} {:<name_width$}  {state}", rule.name());
+            frame.print_centered(row, &line, fg);
+            row += 1;
+        }
+        row += 1;
+        let play = self.menu.chosen() == Some(None);
+        let (line, fg) = if play {
+            ("> play", colors.head)
+        } else {
+            ("  play", colors.wall)
+        };
+        frame.print_centered(row, &format!("{line:<w$}", w = name_width + 7), fg);
+        frame.print_centered(
+            row + 2,
+            "up/down  choose   enter  switch, play   m  back",
+            colors.wall,
+        );
+        frame
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    io,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+use crate::{
+    achievements::Achievements,
+    config,
+    game::{
+        Countdown,
+        Game,
+    },
+    render::{
+        Frame,
+        Renderer,
+    },
+    theme::Color,
+};
+
+/// How many save slots there are.
+pub const SLOTS: usize = 5;
+
+/// Width of the strip of arena drawn under each slot on the load screen.
+const THUMBNAIL_WIDTH: usize = 40;
+
+const PROMPT: &str = "1-5  pick    enter  load    x  delete    m  back";
+
+fn path(slot: usize) -> Option<PathBuf> {
+    Some(config::data_dir()?.join(format!("save-{}.toml", slot + 1)))
+}
+
+/// Writes `game` to `slot`, replacing whatever was saved there before.
+pub fn save(game: &Game, slot: usize) -> Result<(), String> {
+    config::save_data(&format!("save-{}.toml", slot + 1), game)
+}
+
+/// Reads back the game saved in `slot`, if there is one. It picks up with a
+/// countdown so the player has a moment to get their bearings.
+pub fn load(slot: usize) -> Result<Option<Game>, String> {
+    let Some(path) = path(slot) else {
+        return Ok(None);
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+    let mut game: Game =
+        toml::from_str(&text).map_err(|err| format!("{}: {err}", path.display()))?;
+    game.achievements = Achievements::load().unwrap_or_default();
+    game.slot = slot;
+    game.paused = false;
+    game.countdown = Some(Countdown::default());
+    Ok(Some(game))
+}
+
+/// Empties `slot`. An already empty slot is left as it is.
+pub fn delete(slot: usize) -> Result<(), String> {
+    let Some(path) = path(slot) else {
+        return Ok(());
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("{}: {err}", path.display())),
+    }
+}
+
+/// Seconds since `slot` was last saved to, if it has been.
+fn age(slot: usize) -> Option<u64> {
+    let modified = fs::metadata(path(slot)?).ok()?.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    Some(age.as_secs())
+}
+
+/// Roughly how long ago something `secs` old happened.
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// What's in one save slot.
+#[derive(Debug, Clone)]
+struct Slot {
+    game: Game,
+    age: Option<u64>,
+}
+
+/// Lists the save slots with what's in each, to pick one to carry on from
+/// or clear out.
+#[derive(Debug, Clone)]
+pub struct LoadScreen {
+    slots: Vec<Option<Slot>>,
+    pub selected: usize,
+}
+
+impl LoadScreen {
+    pub fn new() -> Self {
+        let mut screen = Self {
+            slots: Vec::new(),
+            selected: 0,
+        };
+        screen.refresh();
+        screen
+    }
+
+    fn refresh(&mut self) {
+        self.slots = (0..SLOTS)
+            .map(|slot| {
+                let game = load(slot).ok().flatten()?;
+                Some(Slot {
+                    game,
+                    age: age(slot),
+                })
+            })
+            .collect();
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < SLOTS {
+            self.selected = slot;
+        }
+    }
+
+    /// The game in the selected slot, if there is one.
+    pub fn chosen(&self) -> Option<Game> {
+        let slot = self.slots.get(self.selected)?.as_ref()?;
+        Some(slot.game.clone())
+    }
+
+    pub fn delete_selected(&mut self) {
+        // A slot that couldn't be deleted just stays listed.
+        let _ = delete(self.selected);
+        self.refresh();
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let block_height = 2 * SLOTS as u16 + 4;
+        let mut row = height.saturating_sub(block_height) / 2;
+        frame.print_centered(row, "LOAD GAME", colors.head);
+        row += 2;
+        let col = width.saturating_sub(PROMPT.len() as u16) / 2;
+        for (index, slot) in self.slots.iter().enumerate() {
+            let marker = if index == self.selected { '>' } else { ' ' };
+            let Some(slot) = slot else {
+                frame.print_fg(
+                    col,
+                    row,
+                    &format!("{marker} {}  empty", index + 1),
+                    colors.wall,
+                );
+                row += 2;
+                continue;
+            };
+            let game = &slot.game;
+            let line = format!(
+                "{marker} {}  {:<11} score {:<5} length {:<4} {}",
+                index + 1,
+                game.mode_name(),
+                game.stats.score,
+                game.player.len(),
+                slot.age.map_or(String::new(), format_age),
+            );
+            frame.print(col, row, &line);
+            for (thumb_col, (ch, fg)) in (col + 4..).zip(thumbnail(renderer, game)) {
+                frame.put(thumb_col, row + 1, ch, fg);
+            }
+            row += 2;
+        }
+        row += 1;
+        frame.print_centered(row, PROMPT, colors.wall);
+        frame
+    }
+}
+
+/// A one-row sketch of the arena: each character stands for a band of
+/// columns, showing the snake if it's anywhere in the band, or else food.
+fn thumbnail(renderer: &Renderer, game: &Game) -> Vec<(char, Color)> {
+    let glyphs = &renderer.theme.glyphs;
+    let colors = &renderer.theme.colors;
+    let empty = if renderer.caps.unicode { '·' } else { '.' };
+    let band = |x: f64| (x / game.width.max(1) as f64 * THUMBNAIL_WIDTH as f64) as usize;
+    let mut strip = vec![(empty, colors.wall); THUMBNAIL_WIDTH];
+    for food in &game.food {
+        if let Some(cell) = strip.get_mut(band(food.at.x)) {
+            *cell = renderer.food_style(food.kind);
+        }
+    }
+    for segment in game.player.segments() {
+        if let Some(cell) = strip.get_mut(band(segment.x)) {
+            *cell = (glyphs.body_horizontal, colors.body);
+        }
+    }
+    if let Some(cell) = strip.get_mut(band(game.player.head.x)) {
+        *cell = (glyphs.head[3], colors.head);
+    }
+    strip
+}
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::config;
+
+const FILE: &str = "scores.toml";
+
+/// Scores kept in each table.
+const TABLE_SIZE: usize = 10;
+
+/// The best scores from past runs, in a table for each kind of run, highest
+/// first. Kept in `$XDG_DATA_HOME/snake/scores.toml`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct HighScores {
+    tables: BTreeMap<String, Vec<u32>>,
+}
+
+impl HighScores {
+    /// Loads the saved scores, treating a missing file as no scores yet.
+    pub fn load() -> Result<HighScores, String> {
+        config::load_data(FILE)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        config::save_data(FILE, self)
+    }
+
+    /// The scores in `table`, highest first.
+    pub fn table(&self, table: &str) -> &[u32] {
+        self.tables.get(table).map_or(&[], Vec::as_slice)
+    }
+
+    /// Enters `score` into `table`, returning where it placed if it made the
+    /// table at all. Nothing is entered for scoring nothing.
+    pub fn add(&mut self, table: &str, score: u32) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+        let scores = self.tables.entry(table.to_string()).or_default();
+        let place = scores.iter().position(|&other| score > other);
+        let place = place.unwrap_or(scores.len());
+        if place >= TABLE_SIZE {
+            return None;
+        }
+        scores.insert(place, score);
+        scores.truncate(TABLE_SIZE);
+        Some(place)
+    }
+}
@@ -0,0 +1,16 @@
+use std::fs;
+
+use crate::{
+    caps::ColorDepth,
+    render::Frame,
+};
+
+/// Writes `frame` out twice: as plain text to `<base>.txt` and with its
+/// colours to `<base>.ans`. Returns the plain text file's name.
+pub fn save(frame: &Frame, depth: ColorDepth, base: &str) -> Result<String, String> {
+    let text = format!("{base}.txt");
+    fs::write(&text, frame.to_text()).map_err(|err| format!("{text}: {err}"))?;
+    let ansi = format!("{base}.ans");
+    fs::write(&ansi, frame.to_ansi(depth)).map_err(|err| format!("{ansi}: {err}"))?;
+    Ok(text)
+}
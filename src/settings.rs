@@ -0,0 +1,225 @@
+use crate::{
+    Options,
+    config::Config,
+    game::Game,
+    menu::Menu,
+    render::{
+        Frame,
+        Renderer,
+    },
+    theme::{
+        BUILTIN_THEMES,
+        Theme,
+    },
+};
+
+/// The theme high contrast swaps the palette for.
+const HIGH_CONTRAST: &str = "high-contrast";
+
+/// How much each press of left or right turns the volume up or down.
+const VOLUME_STEP: f64 = 0.1;
+
+/// Something that can be changed from the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    /// Shaking the arena on crashes.
+    Shake,
+    /// Which of the built-in themes to draw with.
+    Palette,
+    HighContrast,
+    /// Leaving out blinking, flashes, effects and gradients.
+    ReducedMotion,
+    Volume,
+    Mute,
+}
+
+/// Settings that can be changed mid-run, reached from the pause menu. Each
+/// change takes effect straight away and is written to the config file.
+#[derive(Clone, Debug)]
+pub struct SettingsScreen {
+    /// The paused run, to go back to.
+    pub game: Game,
+    shake: bool,
+    /// The name of the theme, which may be one of the user's own, other
+    /// than high contrast.
+    theme: String,
+    /// The user's own theme the run started with, if it did, to go back to.
+    own_theme: Option<Box<Theme>>,
+    high_contrast: bool,
+    reduced_motion: bool,
+    volume: f64,
+    mute: bool,
+    pub menu: Menu<Setting>,
+    /// Why the last change couldn't be saved, if it couldn't.
+    pub notice: Option<String>,
+}
+
+impl SettingsScreen {
+    pub fn new(game: Game, options: &Options) -> Self {
+        let high_contrast = options.theme.name == HIGH_CONTRAST;
+        let theme = if high_contrast {
+            BUILTIN_THEMES[0].to_string()
+        } else {
+            options.theme.name.clone()
+        };
+        Self {
+            game,
+            shake: options.screen_shake,
+            theme,
+            own_theme: Theme::builtin(&options.theme.name)
+                .is_none()
+                .then(|| Box::new(options.theme.clone())),
+            high_contrast,
+            reduced_motion: options.reduced_motion,
+            volume: options.volume,
+            mute: options.mute,
+            menu: Menu::new(vec![
+                (Setting::Shake, "screen shake"),
+                (Setting::Palette, "palette"),
+                (Setting::HighContrast, "high contrast"),
+                (Setting::ReducedMotion, "reduced motion"),
+                (Setting::Volume, "volume"),
+                (Setting::Mute, "mute"),
+            ]),
+            notice: None,
+        }
+    }
+
+    /// Changes the selected setting a step `forward` or back, saving it to
+    /// the config file.
+    pub fn change(&mut self, forward: bool) {
+        let Some(setting) = self.menu.chosen() else {
+            return;
+        };
+        let saved = match setting {
+            Setting::Shake => {
+                self.shake = !self.shake;
+                Config::set("screen_shake", self.shake)
+            }
+            // Picking a palette takes high contrast off, to show it.
+            Setting::Palette => {
+                let palettes: Vec<&str> = BUILTIN_THEMES
+                    .into_iter()
+                    .filter(|&name| name != HIGH_CONTRAST)
+                    .collect();
+                let len = palettes.len();
+                // A theme of the user's own goes on to the first built-in.
+                let next = match palettes.iter().position(|&name| name == self.theme) {
+                    Some(index) if forward => (index + 1) % len,
+                    Some(index) => (index + len - 1) % len,
+                    None => 0,
+                };
+                self.theme = palettes[next].to_string();
+                self.high_contrast = false;
+                Config::set("theme", self.theme.as_str())
+            }
+            // Kept as the theme, so turning it off goes back to the palette.
+            Setting::HighContrast => {
+                self.high_contrast = !self.high_contrast;
+                Config::set("theme", self.theme_name())
+            }
+            Setting::ReducedMotion => {
+                self.reduced_motion = !self.reduced_motion;
+                Config::set("reduced_motion", self.reduced_motion)
+            }
+            Setting::Volume => {
+                let step = if forward { VOLUME_STEP } else { -VOLUME_STEP };
+                // Rounded so the steps don't drift off tenths.
+                self.volume = ((self.volume + step).clamp(0., 1.) * 10.).round() / 10.;
+                Config::set("volume", self.volume)
+            }
+            Setting::Mute => {
+                self.mute = !self.mute;
+                Config::set("mute", self.mute)
+            }
+        };
+        self.notice = saved
+            .err()
+            .map(|err| format!("couldn't save the setting: {err}"));
+    }
+
+    /// `options` with everything changed here.
+    pub fn options(&self, options: &Options) -> Options {
+        Options {
+            screen_shake: self.shake,
+            theme: Theme::builtin(self.theme_name())
+                .or_else(|| self.own_theme.as_deref().cloned())
+                .unwrap_or_else(|| options.theme.clone()),
+            reduced_motion: self.reduced_motion,
+            volume: self.volume,
+            mute: self.mute,
+            ..options.clone()
+        }
+    }
+
+    /// The theme to draw with: high contrast if it's on, or else the
+    /// palette.
+    fn theme_name(&self) -> &str {
+        if self.high_contrast {
+            HIGH_CONTRAST
+        } else {
+            &self.theme
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let rows = self.menu.height() + 5;
+        let mut row = height.saturating_sub(rows) / 2;
+        frame.print_centered(row, "SETTINGS", colors.head);
+        row += 2;
+        let settings = [
+            (
+                Setting::Shake,
+                "screen shake",
+                on_off(self.shake).to_string(),
+            ),
+            (Setting::Palette, "palette", self.theme.clone()),
+            (
+                Setting::HighContrast,
+                "high contrast",
+                on_off(self.high_contrast).to_string(),
+            ),
+            (
+                Setting::ReducedMotion,
+                "reduced motion",
+                on_off(self.reduced_motion).to_string(),
+            ),
+            (
+                Setting::Volume,
+                "volume",
+                format!("{:.0}%", self.volume * 100.),
+            ),
+            (Setting::Mute, "mute", on_off(self.mute).to_string()),
+        ];
+        let name_width = settings
+            .iter()
+            .map(|(_, name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (setting, name, value) in settings {
+            let (marker, fg) = if self.menu.chosen() == Some(setting) {
+                ('>', colors.head)
+            } else {
+                (' ', colors.wall)
+            };
+            let line = format!("{marker} {name:<name_width$}  {value:<13}");
+            frame.print_centered(row, &line, fg);
+            row += 1;
+        }
+        frame.print_centered(
+            row + 1,
+            "up/down  choose   left/right  change   m  back",
+            colors.wall,
+        );
+        if let Some(notice) = &self.notice {
+            frame.print_centered(row + 2, notice, colors.bonus);
+        }
+        frame
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
@@ -0,0 +1,110 @@
+use std::io::{
+    self,
+    Write,
+};
+
+use crate::{
+    events::GameEvent,
+    food::FoodKind,
+    game::Game,
+    logging::log,
+};
+
+/// A short sound played for something that happened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    /// Eating, with a sound for each kind of food.
+    Eat(FoodKind),
+    PowerUp,
+    Death,
+    /// Moving between screens.
+    Menu,
+    /// Each step of the countdown before a run.
+    Countdown,
+    /// The head got closer to food, pitched from 0 far away up to 1 right
+    /// next to it.
+    Closer(f64),
+    /// Carrying straight on would crash.
+    Warning,
+}
+
+impl Sound {
+    /// The sound for `event`, if it has one.
+    pub fn for_event(event: &GameEvent) -> Option<Sound> {
+        match event {
+            GameEvent::FoodEaten { kind, .. } => Some(Sound::Eat(*kind)),
+            GameEvent::Poisoned => Some(Sound::Eat(FoodKind::Poison)),
+            GameEvent::PowerUpStarted(_) | GameEvent::ShieldPickedUp => Some(Sound::PowerUp),
+            GameEvent::LifeLost(_) | GameEvent::Died { .. } | GameEvent::PlayerOut { .. } => {
+                Some(Sound::Death)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Sounds that describe the board as the snake moves, so it can be played
+/// without seeing it well.
+#[derive(Clone, Debug, Default)]
+pub struct Cues {
+    last_tick: Option<u64>,
+    /// Cells to the nearest food after the last move.
+    distance: Option<f64>,
+}
+
+impl Cues {
+    /// Plays the cues for `game` if the snake has moved since the last call.
+    pub fn update(&mut self, game: &Game, sounds: &mut Sounds) {
+        if self.last_tick == Some(game.ticks) {
+            return;
+        }
+        self.last_tick = Some(game.ticks);
+        let distance = game.nearest_food().map(|(_, distance)| distance);
+        if game.danger_ahead().is_some() {
+            sounds.play(Sound::Warning);
+        } else if let (Some(now), Some(before)) = (distance, self.distance)
+            && now < before
+        {
+            let across = (game.width + game.height) as f64;
+            sounds.play(Sound::Closer(1. - now / across));
+        }
+        self.distance = distance;
+    }
+}
+
+/// Sound effects. With no audio library to hand they all come out as the
+/// terminal bell, at most once a frame, and the volume can only turn it off.
+#[derive(Clone, Debug)]
+pub struct Sounds {
+    /// From 0 for silence to 1.
+    volume: f64,
+    /// Whether anything's been played since the last flush.
+    pending: bool,
+}
+
+impl Sounds {
+    pub fn new(volume: f64, muted: bool) -> Self {
+        let volume = if muted { 0. } else { volume.clamp(0., 1.) };
+        Self {
+            volume,
+            pending: false,
+        }
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        log!(Trace, "sound: {sound:?}");
+        if self.volume > 0. {
+            self.pending = true;
+        }
+    }
+
+    /// Sends anything played since the last call to the terminal.
+    pub fn flush(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if !self.pending {
+            return Ok(());
+        }
+        self.pending = false;
+        out.write_all(b"\x07")?;
+        out.flush()
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::Vec2;
+
+/// Things on the board bucketed by the cells they take up, so finding
+/// what's on or near a cell doesn't mean going through everything on the
+/// board. Each is referred to by where it came in the iterator the hash was
+/// built from.
+#[derive(Clone, Debug, Default)]
+pub struct SpatialHash {
+    buckets: HashMap<(i64, i64), Vec<(usize, Vec2)>>,
+}
+
+/// The bucket a position falls in.
+fn key(at: Vec2) -> (i64, i64) {
+    (at.x.round() as i64, at.y.round() as i64)
+}
+
+impl SpatialHash {
+    /// Buckets the cells of each thing `cells` yields.
+    pub fn new(cells: impl Iterator<Item = Vec<Vec2>>) -> Self {
+        let mut hash = SpatialHash::default();
+        for (index, cells) in cells.enumerate() {
+            hash.insert(index, cells);
+        }
+        hash
+    }
+
+    /// Adds thing `index`, on `cells`.
+    pub fn insert(&mut self, index: usize, cells: Vec<Vec2>) {
+        for at in cells {
+            self.buckets.entry(key(at)).or_default().push((index, at));
+        }
+    }
+
+    /// The entities with a cell within `radius` of `at`, which may repeat
+    /// for an entity with several cells that close.
+    pub fn near(&self, at: Vec2, radius: f64) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = key(at);
+        let reach = radius.ceil() as i64;
+        (y - reach..=y + reach)
+            .flat_map(move |y| (x - reach..=x + reach).map(move |x| (x, y)))
+            .filter_map(|key| self.buckets.get(&key))
+            .flatten()
+            .filter(move |(_, cell)| cell.distance(at) < radius)
+            .map(|&(index, _)| index)
+    }
+
+    /// The entities touching `at`, taking anything closer than `reach` as
+    /// touching.
+    pub fn entities_at(&self, at: Vec2, reach: f64) -> impl Iterator<Item = usize> + '_ {
+        self.near(at, reach)
+    }
+
+    pub fn occupied(&self, at: Vec2, reach: f64) -> bool {
+        self.entities_at(at, reach).next().is_some()
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::config;
+
+const FILE: &str = "splits.toml";
+
+/// Apples eaten at each split.
+pub const MILESTONES: [u32; 3] = [10, 25, 50];
+
+/// The personal-best splits for each category of run, kept in
+/// `$XDG_DATA_HOME/snake/splits.toml`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+struct BestSplits {
+    categories: BTreeMap<String, Vec<f64>>,
+}
+
+/// A run being timed against the clock, split at each milestone.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Speedrun {
+    /// Which runs it's compared with: the mode, and the seed if it's fixed.
+    pub category: String,
+    /// Seconds into the run each milestone was reached, so far.
+    pub splits: Vec<f64>,
+    /// The personal best's splits, to compare against.
+    pub best: Vec<f64>,
+}
+
+impl Speedrun {
+    /// A new attempt in `category`, against its personal best if there's one
+    /// saved.
+    pub fn new(category: String) -> Self {
+        let best = config::load_data::<BestSplits>(FILE)
+            .ok()
+            .and_then(|mut best| best.categories.remove(&category))
+            .unwrap_or_default();
+        Self {
+            category,
+            splits: Vec::new(),
+            best,
+        }
+    }
+
+    /// Splits if `apples` has just reached the next milestone, `time` seconds
+    /// in, returning the split's number.
+    pub fn update(&mut self, apples: u32, time: f64) -> Option<usize> {
+        let next = *MILESTONES.get(self.splits.len())?;
+        if apples < next {
+            return None;
+        }
+        self.splits.push(time);
+        Some(self.splits.len() - 1)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.splits.len() == MILESTONES.len()
+    }
+
+    /// Seconds the split numbered `index` was ahead of the personal best,
+    /// negative, or behind it.
+    pub fn delta(&self, index: usize) -> Option<f64> {
+        Some(self.splits.get(index)? - self.best.get(index)?)
+    }
+
+    /// Whether the attempt beats the personal best: getting further, or as
+    /// far in less time.
+    fn beats_best(&self) -> bool {
+        match self.splits.len().cmp(&self.best.len()) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => match (self.splits.last(), self.best.last()) {
+                (Some(now), Some(best)) => now < best,
+                _ => false,
+            },
+        }
+    }
+
+    /// Saves the attempt as the category's personal best if it is one,
+    /// returning whether it was.
+    pub fn record(&self) -> Result<bool, String> {
+        if !self.beats_best() {
+            return Ok(false);
+        }
+        let mut best: BestSplits = config::load_data(FILE)?;
+        best.categories
+            .insert(self.category.clone(), self.splits.clone());
+        config::save_data(FILE, &best)?;
+        Ok(true)
+    }
+}
+
+/// Formats seconds as `m:ss.mmm`.
+pub fn format_split(seconds: f64) -> String {
+    let millis = (seconds.max(0.) * 1000.).round() as u64;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// A split's difference from the personal best, such as `+1.250`.
+pub fn format_delta(delta: f64) -> String {
+    format!("{delta:+.3}")
+}
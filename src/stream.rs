@@ -0,0 +1,133 @@
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufWriter,
+        Write,
+    },
+    os::fd::FromRawFd,
+};
+
+use crate::{
+    control::Features,
+    events::GameEvent,
+    game::Game,
+    json,
+};
+
+/// The live game written out for other programs, one line of JSON per move
+/// of the snake, to a file, a FIFO or an inherited file descriptor.
+pub struct StateStream {
+    out: BufWriter<Box<dyn Write>>,
+    /// Events since the last line.
+    events: Vec<GameEvent>,
+    last_tick: Option<u64>,
+}
+
+impl StateStream {
+    /// Opens `target`: a file descriptor the game was started with if it's a
+    /// number, otherwise a path. Opening a FIFO waits for something to read
+    /// from it.
+    pub fn open(target: &str) -> Result<StateStream, String> {
+        Ok(StateStream::new(open_target(target)?))
+    }
+
+    pub fn new(out: impl Write + 'static) -> StateStream {
+        StateStream {
+            out: BufWriter::new(Box::new(out)),
+            events: Vec::new(),
+            last_tick: None,
+        }
+    }
+
+    /// Keeps `event` for the next line.
+    pub fn record(&mut self, event: &GameEvent) {
+        self.events.push(event.clone());
+    }
+
+    /// Writes out a line for `game` if the snake has moved since the last
+    /// one.
+    pub fn update(&mut self, game: &Game) -> io::Result<()> {
+        if self.last_tick == Some(game.ticks) {
+            return Ok(());
+        }
+        self.last_tick = Some(game.ticks);
+        let line = state_line(game, &self.events, Features::ALL);
+        self.events.clear();
+        writeln!(self.out, "{line}")?;
+        self.out.flush()
+    }
+}
+
+/// Opens `target` to write to: a file descriptor the game was started with
+/// if it's a number, otherwise a path.
+pub fn open_target(target: &str) -> Result<File, String> {
+    match target.parse::<i32>() {
+        // SAFETY: the descriptor is one the user handed the game to write to,
+        // and nothing else takes ownership of it.
+        Ok(fd) if fd > 2 => Ok(unsafe { File::from_raw_fd(fd) }),
+        Ok(fd) => Err(format!("can't write to file descriptor {fd}")),
+        Err(_) => File::create(target).map_err(|err| format!("{target}: {err}")),
+    }
+}
+
+/// `game` as a line of JSON, with `events` as what's happened since the last
+/// line. The power-ups running are listed too if `features` has them.
+pub fn state_line(game: &Game, events: &[GameEvent], features: Features) -> String {
+    let snake: Vec<String> = game
+        .player
+        .segments()
+        .map(|cell| format!("[{}, {}]", cell.x, cell.y))
+        .collect();
+    let food: Vec<String> = game
+        .food
+        .iter()
+        .map(|food| {
+            format!(
+                r#"{{"kind": {}, "at": [{}, {}]}}"#,
+                json::string(food.kind.name()),
+                food.at.x,
+                food.at.y
+            )
+        })
+        .collect();
+    let events: Vec<String> = events
+        .iter()
+        .map(|event| {
+            format!(
+                r#"{{"type": {}, "text": {}}}"#,
+                json::string(event.name()),
+                json::string(&event.describe())
+            )
+        })
+        .collect();
+    let powers = if features.has(Features::POWER_UPS) {
+        let powers: Vec<String> = game
+            .powers
+            .iter()
+            .map(|(power, left)| {
+                format!(
+                    r#"{{"kind": {}, "left": {left:.3}}}"#,
+                    json::string(power.name())
+                )
+            })
+            .collect();
+        format!(r#", "powers": [{}]"#, powers.join(", "))
+    } else {
+        String::new()
+    };
+    format!(
+        r#"{{"tick": {}, "time": {:.3}, "arena": [{}, {}], "score": {}, "length": {}, "lives": {}, "over": {}, "snake": [{}], "food": [{}], "events": [{}]{powers}}}"#,
+        game.ticks,
+        game.stats.time,
+        game.width,
+        game.height,
+        game.stats.score,
+        game.player.len(),
+        game.lives,
+        game.is_over(),
+        snake.join(", "),
+        food.join(", "),
+        events.join(", "),
+    )
+}
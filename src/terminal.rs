@@ -0,0 +1,88 @@
+use std::{
+    io::{
+        self,
+        Stdout,
+        Write,
+    },
+    process::{
+        self,
+        Command,
+    },
+};
+
+use termion::{
+    cursor,
+    raw::{
+        IntoRawMode,
+        RawTerminal,
+    },
+    screen::{
+        ToAlternateScreen,
+        ToMainScreen,
+    },
+};
+
+/// The terminal the game is drawn on: in raw mode, and on the alternate
+/// screen if it has one. It's put back how it was when dropped, and can be
+/// handed back for a while to suspend the game.
+pub struct Terminal {
+    raw: RawTerminal<Stdout>,
+    alt_screen: bool,
+}
+
+impl Terminal {
+    pub fn new(alt_screen: bool) -> io::Result<Terminal> {
+        let mut terminal = Terminal {
+            raw: io::stdout().into_raw_mode()?,
+            alt_screen,
+        };
+        if alt_screen {
+            write!(terminal.raw, "{ToAlternateScreen}")?;
+            terminal.raw.flush()?;
+        }
+        Ok(terminal)
+    }
+
+    /// Puts the terminal back for the shell, stops the game as `Ctrl-Z`
+    /// would outside raw mode, and takes the terminal over again once the
+    /// game's continued.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        if self.alt_screen {
+            write!(self.raw, "{ToMainScreen}")?;
+        }
+        write!(self.raw, "{}", cursor::Show)?;
+        self.raw.flush()?;
+        self.raw.suspend_raw_mode()?;
+        // Raw mode turns off the terminal sending the signal itself, so
+        // it's sent by hand. This returns once the shell continues us.
+        let stopped = Command::new("kill")
+            .args(["-TSTP", &process::id().to_string()])
+            .status();
+        self.raw.activate_raw_mode()?;
+        if self.alt_screen {
+            write!(self.raw, "{ToAlternateScreen}")?;
+        }
+        write!(self.raw, "{}", cursor::Hide)?;
+        self.raw.flush()?;
+        stopped.map(|_| ())
+    }
+}
+
+impl Write for Terminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.raw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.raw.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.alt_screen {
+            let _ = write!(self.raw, "{ToMainScreen}");
+            let _ = self.raw.flush();
+        }
+    }
+}
@@ -0,0 +1,647 @@
+use std::{
+    collections::HashMap,
+    io::{
+        self,
+        Write,
+    },
+};
+
+use serde::Deserialize;
+
+use crate::caps::ColorDepth;
+
+/// A terminal colour as written in themes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// Whatever the terminal uses by default.
+    Default,
+    /// One of the 16 standard palette entries.
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Approximate RGB values of the 16 standard palette entries.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// Parses `default`, a standard colour name such as `bright-green`, or
+    /// `#rrggbb`.
+    pub fn parse(value: &str) -> Option<Color> {
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        let (bright, name) = match value.strip_prefix("bright-") {
+            Some(name) => (8, name),
+            None => (0, value),
+        };
+        let index = match name {
+            "default" if bright == 0 => return Some(Color::Default),
+            "black" => 0,
+            "red" => 1,
+            "green" => 2,
+            "yellow" => 3,
+            "blue" => 4,
+            "magenta" => 5,
+            "cyan" => 6,
+            "white" => 7,
+            _ => return None,
+        };
+        Some(Color::Ansi(bright + index))
+    }
+
+    pub fn rgb(self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::Default => None,
+            Color::Ansi(n) => Some(ANSI_RGB[n as usize % 16]),
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+
+    /// The colour `t` of the way from `self` to `other`. Colours without an
+    /// RGB value don't blend, so the nearer end wins.
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        let (Some(a), Some(b)) = (self.rgb(), other.rgb()) else {
+            return if t < 0.5 { self } else { other };
+        };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color::Rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+    }
+
+    /// The closest colour the terminal can actually show.
+    pub fn downgrade(self, depth: ColorDepth) -> Color {
+        let Color::Rgb(r, g, b) = self else {
+            return self;
+        };
+        match depth {
+            ColorDepth::Monochrome => Color::Default,
+            ColorDepth::TrueColor => self,
+            ColorDepth::Ansi256 => {
+                let level = |c: u8| ((c as u16 * 5 + 127) / 255) as u8;
+                let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+                let gray = (r as u16 + g as u16 + b as u16) / 3;
+                let gray_index = 232 + (gray.saturating_sub(3) / 10).min(23) as u8;
+                let gray_value = 8 + 10 * (gray_index - 232) as i32;
+                let cube_value = |c: u8| if c == 0 { 0 } else { 55 + 40 * c as i32 };
+                let cube_error = [(r, level(r)), (g, level(g)), (b, level(b))]
+                    .iter()
+                    .map(|&(c, l)| (c as i32 - cube_value(l)).pow(2))
+                    .sum::<i32>();
+                let gray_error = [r, g, b]
+                    .iter()
+                    .map(|&c| (c as i32 - gray_value).pow(2))
+                    .sum::<i32>();
+                Color::Ansi(if gray_error < cube_error {
+                    gray_index
+                } else {
+                    cube
+                })
+            }
+            ColorDepth::Ansi16 => {
+                let distance = |&(_, &(pr, pg, pb)): &(usize, &(u8, u8, u8))| {
+                    (r as i32 - pr as i32).pow(2)
+                        + (g as i32 - pg as i32).pow(2)
+                        + (b as i32 - pb as i32).pow(2)
+                };
+                let (index, _) = ANSI_RGB.iter().enumerate().min_by_key(distance).unwrap();
+                Color::Ansi(index as u8)
+            }
+        }
+    }
+
+    /// The escape code that sets this as the foreground, or nothing at all
+    /// on a monochrome terminal.
+    pub fn fg_escape(self, depth: ColorDepth) -> String {
+        use termion::color::{
+            AnsiValue,
+            Fg,
+            Reset,
+            Rgb,
+        };
+        if depth == ColorDepth::Monochrome {
+            return String::new();
+        }
+        match self.downgrade(depth) {
+            Color::Default => Fg(Reset).to_string(),
+            Color::Ansi(n) if depth == ColorDepth::Ansi16 => basic_escape(n, 30),
+            Color::Ansi(n) => Fg(AnsiValue(n)).to_string(),
+            Color::Rgb(r, g, b) => Fg(Rgb(r, g, b)).to_string(),
+        }
+    }
+
+    /// Like `fg_escape`, for the background.
+    pub fn bg_escape(self, depth: ColorDepth) -> String {
+        use termion::color::{
+            AnsiValue,
+            Bg,
+            Reset,
+            Rgb,
+        };
+        if depth == ColorDepth::Monochrome {
+            return String::new();
+        }
+        match self.downgrade(depth) {
+            Color::Default => Bg(Reset).to_string(),
+            Color::Ansi(n) if depth == ColorDepth::Ansi16 => basic_escape(n, 40),
+            Color::Ansi(n) => Bg(AnsiValue(n)).to_string(),
+            Color::Rgb(r, g, b) => Bg(Rgb(r, g, b)).to_string(),
+        }
+    }
+
+    pub fn write_fg(self, out: &mut impl Write, depth: ColorDepth) -> io::Result<()> {
+        out.write_all(self.fg_escape(depth).as_bytes())
+    }
+
+    pub fn write_bg(self, out: &mut impl Write, depth: ColorDepth) -> io::Result<()> {
+        out.write_all(self.bg_escape(depth).as_bytes())
+    }
+}
+
+/// The escape code for one of the 16 standard colours, counting the
+/// foreground's codes from 30 or the background's from 40. A 16-colour
+/// terminal may not know the 256-colour form for even these.
+fn basic_escape(n: u8, first: u8) -> String {
+    let code = if n < 8 { first + n } else { first + 60 + n % 8 };
+    format!("\x1b[{code}m")
+}
+
+/// Characters used for every kind of thing drawn in the arena.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Head facing up, down, left and right.
+    pub head: [char; 4],
+    pub body_vertical: char,
+    pub body_horizontal: char,
+    /// Turns joining down-right, down-left, up-right and up-left.
+    pub corners: [char; 4],
+    pub food: char,
+    pub bonus: char,
+    pub mega: char,
+    pub mouse: char,
+    /// The special fruit that turns up every so often.
+    pub fruit: char,
+    pub poison: char,
+    pub shield: char,
+    pub portal: char,
+    pub wall: char,
+    pub background: char,
+}
+
+impl Glyphs {
+    fn box_drawing() -> Self {
+        Self {
+            head: ['▲', '▼', '◀', '▶'],
+            body_vertical: '│',
+            body_horizontal: '─',
+            corners: ['╭', '╮', '╰', '╯'],
+            food: '●',
+            bonus: '◆',
+            mega: '★',
+            mouse: '◐',
+            fruit: '◉',
+            poison: '✖',
+            shield: '◈',
+            portal: '◎',
+            wall: '█',
+            background: ' ',
+        }
+    }
+
+    /// Glyphs that any terminal and font can show.
+    pub fn ascii() -> Self {
+        Self {
+            head: ['o'; 4],
+            body_vertical: '|',
+            body_horizontal: '-',
+            corners: ['+'; 4],
+            food: '*',
+            bonus: '$',
+            mega: '@',
+            mouse: '~',
+            fruit: '%',
+            poison: 'x',
+            shield: '&',
+            portal: 'O',
+            wall: '#',
+            background: ' ',
+        }
+    }
+
+    /// Box-drawing glyphs with every kind of item a clearly different
+    /// shape, so nothing has to be told apart by colour alone.
+    fn distinct() -> Self {
+        Self {
+            bonus: '■',
+            fruit: '♣',
+            shield: '♥',
+            ..Self::box_drawing()
+        }
+    }
+
+    /// Heavy lines and filled shapes, for the high-contrast theme.
+    fn bold() -> Self {
+        Self {
+            body_vertical: '┃',
+            body_horizontal: '━',
+            corners: ['┏', '┓', '┗', '┛'],
+            ..Self::distinct()
+        }
+    }
+
+    fn solid() -> Self {
+        Self {
+            body_vertical: '█',
+            body_horizontal: '█',
+            corners: ['█'; 4],
+            ..Self::box_drawing()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub head: Color,
+    pub body: Color,
+    pub food: Color,
+    pub bonus: Color,
+    pub mega: Color,
+    pub mouse: Color,
+    pub fruit: Color,
+    /// Something to stay away from.
+    pub poison: Color,
+    /// Power-up pickups and their HUD timers.
+    pub power: Color,
+    pub shield: Color,
+    pub portal: Color,
+    pub wall: Color,
+    pub background: Color,
+    /// Head and tail colours of a body gradient, if the theme has one.
+    pub gradient: Option<(Color, Color)>,
+}
+
+/// Everything the renderer needs to know about how the game should look.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    pub glyphs: Glyphs,
+    pub colors: Palette,
+    /// Draw items as their glyphs even in braille mode, where they'd
+    /// otherwise be a dot told apart only by colour.
+    pub shapes: bool,
+}
+
+pub const BUILTIN_THEMES: [&str; 8] = [
+    "classic",
+    "neon",
+    "retro-green",
+    "monochrome",
+    "deuteranopia",
+    "protanopia",
+    "tritanopia",
+    "high-contrast",
+];
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let (glyphs, colors) = match name {
+            "classic" => (
+                Glyphs::box_drawing(),
+                Palette {
+                    head: Color::Ansi(10),
+                    body: Color::Ansi(2),
+                    food: Color::Ansi(9),
+                    bonus: Color::Ansi(11),
+                    mega: Color::Ansi(13),
+                    mouse: Color::Ansi(3),
+                    fruit: Color::Ansi(14),
+                    poison: Color::Ansi(5),
+                    power: Color::Ansi(12),
+                    shield: Color::Ansi(15),
+                    portal: Color::Ansi(6),
+                    wall: Color::Ansi(7),
+                    background: Color::Default,
+                    gradient: None,
+                },
+            ),
+            "neon" => (
+                Glyphs {
+                    food: '◆',
+                    bonus: '◇',
+                    ..Glyphs::box_drawing()
+                },
+                Palette {
+                    head: Color::Rgb(255, 60, 220),
+                    body: Color::Rgb(0, 240, 255),
+                    food: Color::Rgb(255, 230, 0),
+                    bonus: Color::Rgb(0, 255, 120),
+                    mega: Color::Rgb(255, 255, 255),
+                    mouse: Color::Rgb(200, 200, 200),
+                    fruit: Color::Rgb(255, 130, 0),
+                    poison: Color::Rgb(180, 255, 0),
+                    power: Color::Rgb(80, 140, 255),
+                    shield: Color::Rgb(200, 200, 255),
+                    portal: Color::Rgb(255, 0, 140),
+                    wall: Color::Rgb(140, 60, 255),
+                    background: Color::Rgb(12, 0, 24),
+                    gradient: Some((Color::Rgb(255, 60, 220), Color::Rgb(0, 240, 255))),
+                },
+            ),
+            "retro-green" => (
+                Glyphs {
+                    wall: '▓',
+                    ..Glyphs::solid()
+                },
+                Palette {
+                    head: Color::Rgb(170, 255, 170),
+                    body: Color::Rgb(50, 220, 80),
+                    food: Color::Rgb(120, 255, 120),
+                    bonus: Color::Rgb(210, 255, 210),
+                    mega: Color::Rgb(255, 255, 255),
+                    mouse: Color::Rgb(150, 230, 150),
+                    fruit: Color::Rgb(190, 255, 90),
+                    poison: Color::Rgb(255, 80, 40),
+                    power: Color::Rgb(140, 255, 200),
+                    shield: Color::Rgb(230, 255, 230),
+                    portal: Color::Rgb(90, 200, 255),
+                    wall: Color::Rgb(20, 110, 40),
+                    background: Color::Rgb(0, 16, 0),
+                    gradient: Some((Color::Rgb(170, 255, 170), Color::Rgb(10, 90, 30))),
+                },
+            ),
+            "monochrome" => (
+                Glyphs::box_drawing(),
+                Palette {
+                    head: Color::Default,
+                    body: Color::Default,
+                    food: Color::Default,
+                    bonus: Color::Default,
+                    mega: Color::Default,
+                    mouse: Color::Default,
+                    fruit: Color::Default,
+                    poison: Color::Default,
+                    power: Color::Default,
+                    shield: Color::Default,
+                    portal: Color::Default,
+                    wall: Color::Default,
+                    background: Color::Default,
+                    gradient: None,
+                },
+            ),
+            // The colour-blind palettes are built from the Okabe-Ito colours,
+            // keeping to pairs each kind of colour blindness can still tell
+            // apart.
+            "deuteranopia" => (
+                Glyphs::distinct(),
+                Palette {
+                    head: Color::Rgb(86, 180, 233),
+                    body: Color::Rgb(0, 114, 178),
+                    food: Color::Rgb(230, 159, 0),
+                    bonus: Color::Rgb(240, 228, 66),
+                    mega: Color::Rgb(255, 255, 255),
+                    mouse: Color::Rgb(170, 170, 170),
+                    fruit: Color::Rgb(213, 94, 0),
+                    poison: Color::Rgb(204, 121, 167),
+                    power: Color::Rgb(86, 180, 233),
+                    shield: Color::Rgb(255, 255, 255),
+                    portal: Color::Rgb(204, 121, 167),
+                    wall: Color::Rgb(150, 150, 150),
+                    background: Color::Default,
+                    gradient: None,
+                },
+            ),
+            // Reds look dark without red cones, so nothing important is red.
+            "protanopia" => (
+                Glyphs::distinct(),
+                Palette {
+                    head: Color::Rgb(86, 180, 233),
+                    body: Color::Rgb(0, 114, 178),
+                    food: Color::Rgb(240, 228, 66),
+                    bonus: Color::Rgb(230, 159, 0),
+                    mega: Color::Rgb(255, 255, 255),
+                    mouse: Color::Rgb(170, 170, 170),
+                    fruit: Color::Rgb(230, 159, 0),
+                    poison: Color::Rgb(204, 121, 167),
+                    power: Color::Rgb(86, 180, 233),
+                    shield: Color::Rgb(255, 255, 255),
+                    portal: Color::Rgb(204, 121, 167),
+                    wall: Color::Rgb(150, 150, 150),
+                    background: Color::Default,
+                    gradient: None,
+                },
+            ),
+            "tritanopia" => (
+                Glyphs::distinct(),
+                Palette {
+                    head: Color::Rgb(0, 158, 115),
+                    body: Color::Rgb(0, 110, 80),
+                    food: Color::Rgb(213, 94, 0),
+                    bonus: Color::Rgb(255, 255, 255),
+                    mega: Color::Rgb(204, 121, 167),
+                    mouse: Color::Rgb(170, 170, 170),
+                    fruit: Color::Rgb(255, 140, 140),
+                    poison: Color::Rgb(130, 20, 60),
+                    power: Color::Rgb(86, 180, 233),
+                    shield: Color::Rgb(255, 255, 255),
+                    portal: Color::Rgb(204, 121, 167),
+                    wall: Color::Rgb(150, 150, 150),
+                    background: Color::Default,
+                    gradient: None,
+                },
+            ),
+            "high-contrast" => (
+                Glyphs::bold(),
+                Palette {
+                    head: Color::Rgb(255, 255, 255),
+                    body: Color::Rgb(255, 255, 255),
+                    food: Color::Rgb(255, 255, 255),
+                    bonus: Color::Rgb(255, 255, 255),
+                    mega: Color::Rgb(255, 255, 255),
+                    mouse: Color::Rgb(255, 255, 255),
+                    fruit: Color::Rgb(255, 255, 255),
+                    poison: Color::Rgb(255, 255, 255),
+                    power: Color::Rgb(255, 255, 255),
+                    shield: Color::Rgb(255, 255, 255),
+                    portal: Color::Rgb(255, 255, 255),
+                    wall: Color::Rgb(255, 255, 255),
+                    background: Color::Rgb(0, 0, 0),
+                    gradient: None,
+                },
+            ),
+            _ => return None,
+        };
+        let shapes = matches!(
+            name,
+            "deuteranopia" | "protanopia" | "tritanopia" | "high-contrast"
+        );
+        let name = name.to_string();
+        Some(Theme {
+            name,
+            glyphs,
+            colors,
+            shapes,
+        })
+    }
+
+    /// The same theme with every glyph swapped for its ASCII counterpart.
+    pub fn ascii(self) -> Theme {
+        let glyphs = Glyphs::ascii();
+        Theme { glyphs, ..self }
+    }
+
+    /// Looks a theme up among the user's themes first, then the built-ins.
+    pub fn find(name: &str, user_themes: &HashMap<String, ThemeSpec>) -> Result<Theme, String> {
+        match user_themes.get(name) {
+            Some(spec) => spec.build(name, user_themes, 0),
+            None => Theme::builtin(name).ok_or_else(|| format!("unknown theme `{name}`")),
+        }
+    }
+}
+
+/// A user theme from the config file. Anything left out is taken from `base`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeSpec {
+    base: Option<String>,
+    /// One character for every direction, or four for up, down, left, right.
+    head: Option<String>,
+    /// One character for every segment, or six for vertical, horizontal and
+    /// the four corners.
+    body: Option<String>,
+    food: Option<char>,
+    bonus: Option<char>,
+    mega: Option<char>,
+    mouse: Option<char>,
+    fruit: Option<char>,
+    poison: Option<char>,
+    shield: Option<char>,
+    portal: Option<char>,
+    wall: Option<char>,
+    background: Option<char>,
+    /// Draw items as their glyphs in braille mode too.
+    shapes: Option<bool>,
+    head_color: Option<String>,
+    body_color: Option<String>,
+    food_color: Option<String>,
+    bonus_color: Option<String>,
+    mega_color: Option<String>,
+    mouse_color: Option<String>,
+    fruit_color: Option<String>,
+    poison_color: Option<String>,
+    power_color: Option<String>,
+    shield_color: Option<String>,
+    portal_color: Option<String>,
+    wall_color: Option<String>,
+    background_color: Option<String>,
+    /// Body gradient from the head to the tail. Setting only one end blends
+    /// towards the head or body colour.
+    gradient_start: Option<String>,
+    gradient_end: Option<String>,
+}
+
+impl ThemeSpec {
+    fn build(
+        &self,
+        name: &str,
+        user_themes: &HashMap<String, ThemeSpec>,
+        depth: usize,
+    ) -> Result<Theme, String> {
+        let base = self.base.as_deref().unwrap_or("classic");
+        let mut theme = match user_themes.get(base) {
+            Some(_) if depth > user_themes.len() => {
+                return Err(format!("theme `{name}` has a cyclic base"));
+            }
+            Some(spec) if base != name => spec.build(base, user_themes, depth + 1)?,
+            _ => Theme::builtin(base)
+                .ok_or_else(|| format!("theme `{name}` has unknown base `{base}`"))?,
+        };
+        theme.name = name.to_string();
+
+        let glyphs = &mut theme.glyphs;
+        if let Some(head) = &self.head {
+            glyphs.head = match head.chars().collect::<Vec<_>>()[..] {
+                [c] => [c; 4],
+                [up, down, left, right] => [up, down, left, right],
+                _ => return Err(format!("theme `{name}`: head needs 1 or 4 characters")),
+            };
+        }
+        if let Some(body) = &self.body {
+            let (vertical, horizontal, corners) = match body.chars().collect::<Vec<_>>()[..] {
+                [c] => (c, c, [c; 4]),
+                [v, h, dr, dl, ur, ul] => (v, h, [dr, dl, ur, ul]),
+                _ => return Err(format!("theme `{name}`: body needs 1 or 6 characters")),
+            };
+            glyphs.body_vertical = vertical;
+            glyphs.body_horizontal = horizontal;
+            glyphs.corners = corners;
+        }
+        glyphs.food = self.food.unwrap_or(glyphs.food);
+        glyphs.bonus = self.bonus.unwrap_or(glyphs.bonus);
+        glyphs.mega = self.mega.unwrap_or(glyphs.mega);
+        glyphs.mouse = self.mouse.unwrap_or(glyphs.mouse);
+        glyphs.fruit = self.fruit.unwrap_or(glyphs.fruit);
+        glyphs.poison = self.poison.unwrap_or(glyphs.poison);
+        glyphs.shield = self.shield.unwrap_or(glyphs.shield);
+        glyphs.portal = self.portal.unwrap_or(glyphs.portal);
+        glyphs.wall = self.wall.unwrap_or(glyphs.wall);
+        glyphs.background = self.background.unwrap_or(glyphs.background);
+        theme.shapes = self.shapes.unwrap_or(theme.shapes);
+
+        let colors = &mut theme.colors;
+        for (value, slot) in [
+            (&self.head_color, &mut colors.head),
+            (&self.body_color, &mut colors.body),
+            (&self.food_color, &mut colors.food),
+            (&self.bonus_color, &mut colors.bonus),
+            (&self.mega_color, &mut colors.mega),
+            (&self.mouse_color, &mut colors.mouse),
+            (&self.fruit_color, &mut colors.fruit),
+            (&self.poison_color, &mut colors.poison),
+            (&self.power_color, &mut colors.power),
+            (&self.shield_color, &mut colors.shield),
+            (&self.portal_color, &mut colors.portal),
+            (&self.wall_color, &mut colors.wall),
+            (&self.background_color, &mut colors.background),
+        ] {
+            if let Some(value) = value {
+                *slot = parse_color(name, value)?;
+            }
+        }
+        if self.gradient_start.is_some() || self.gradient_end.is_some() {
+            let (start, end) = colors.gradient.unwrap_or((colors.head, colors.body));
+            let start = match &self.gradient_start {
+                Some(value) => parse_color(name, value)?,
+                None => start,
+            };
+            let end = match &self.gradient_end {
+                Some(value) => parse_color(name, value)?,
+                None => end,
+            };
+            colors.gradient = Some((start, end));
+        }
+        Ok(theme)
+    }
+}
+
+fn parse_color(theme: &str, value: &str) -> Result<Color, String> {
+    Color::parse(value).ok_or_else(|| format!("theme `{theme}`: unknown colour `{value}`"))
+}
@@ -0,0 +1,116 @@
+use crate::{
+    Direction,
+    anim::Ticker,
+    difficulty::Difficulty,
+    menu::Menu,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+const LOGO: [&str; 5] = [
+    r" ____  _   _    _    _  _______ ",
+    r"/ ___|| \ | |  / \  | |/ / ____|",
+    r"\___ \|  \| | / _ \ | ' /|  _|  ",
+    r" ___) | |\  |/ ___ \| . \| |___ ",
+    r"|____/|_| \_/_/   \_\_|\_\_____|",
+];
+
+const HINTS: [&str; 13] = [
+    "enter  play",
+    "up/down  difficulty",
+    "a/d  turn",
+    "p  pause",
+    "t  achievements",
+    "s  stats",
+    "F9  load",
+    "u  tutorial",
+    "v  modes",
+    "i  challenge code",
+    "w  tournament",
+    "?  help",
+    "q  quit",
+];
+
+/// How many segments long the snake circling the title is.
+const BORDER_SNAKE_LEN: usize = 12;
+
+/// The screen shown on start-up, with a snake slithering around its edge.
+#[derive(Debug, Clone)]
+pub struct TitleScreen {
+    ticker: Ticker,
+    /// The difficulty the next run is played at.
+    pub difficulty: Menu<Difficulty>,
+}
+
+impl TitleScreen {
+    pub fn new(difficulty: Difficulty) -> Self {
+        let ticker = Ticker::new(15.);
+        let mut menu = Menu::new(
+            Difficulty::ALL
+                .into_iter()
+                .map(|difficulty| (difficulty, difficulty.name()))
+                .collect(),
+        );
+        menu.select(difficulty);
+        Self {
+            ticker,
+            difficulty: menu,
+        }
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        self.draw_border_snake(renderer, &mut frame, width, height);
+
+        let colors = &renderer.theme.colors;
+        let block_height = LOGO.len() as u16 + 4 + HINTS.len() as u16 + 1;
+        let mut row = height.saturating_sub(block_height) / 2;
+        for line in LOGO {
+            frame.print_centered(row, line, colors.head);
+            row += 1;
+        }
+        let version = concat!("v", env!("CARGO_PKG_VERSION"));
+        frame.print_centered(row, version, colors.body);
+        row += 2;
+        let difficulty = format!("< {} >", self.difficulty.label());
+        frame.print_centered(row, &difficulty, colors.bonus);
+        row += 2;
+        for hint in HINTS {
+            frame.print_centered(row, hint, colors.wall);
+            row += 1;
+        }
+        frame
+    }
+
+    fn draw_border_snake(&self, renderer: &Renderer, frame: &mut Frame, width: u16, height: u16) {
+        let path = border_path(width, height);
+        if path.len() < 2 {
+            return;
+        }
+        let len = BORDER_SNAKE_LEN.min(path.len() - 1);
+        let head = self.ticker.step() as usize % path.len();
+        let cells: Vec<(u16, u16)> = (0..len)
+            .map(|i| path[(head + path.len() - i) % path.len()])
+            .collect();
+        let heading = Direction::between(cells[1], cells[0]);
+        renderer.draw_snake_cells(frame, heading, &cells);
+    }
+}
+
+/// Every cell on the edge of the screen, clockwise from the top-left corner.
+fn border_path(width: u16, height: u16) -> Vec<(u16, u16)> {
+    if width < 2 || height < 2 {
+        return Vec::new();
+    }
+    let (right, bottom) = (width - 1, height - 1);
+    let top = (0..right).map(|col| (col, 0));
+    let right_side = (0..bottom).map(|row| (right, row));
+    let bottom_side = (1..=right).rev().map(|col| (col, bottom));
+    let left_side = (1..=bottom).rev().map(|row| (0, row));
+    top.chain(right_side)
+        .chain(bottom_side)
+        .chain(left_side)
+        .collect()
+}
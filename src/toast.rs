@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+use crate::{
+    render::{
+        Frame,
+        Renderer,
+    },
+    theme::Color,
+};
+
+/// Seconds each toast stays up, fade included.
+const TOAST_TIME: f64 = 3.;
+
+/// Seconds at the end of a toast's time it's drawn faded.
+const FADE_TIME: f64 = 0.6;
+
+/// Toasts up at once. Any more wait their turn.
+const MAX_SHOWN: usize = 3;
+
+/// What a toast is about, which picks its colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastKind {
+    Notice,
+    Achievement,
+    /// A new high score.
+    Record,
+    PowerUp,
+    /// Something from outside the game, such as a control client.
+    Network,
+}
+
+impl ToastKind {
+    fn color(self, renderer: &Renderer) -> Color {
+        let colors = &renderer.theme.colors;
+        match self {
+            ToastKind::Notice => colors.bonus,
+            ToastKind::Achievement => colors.head,
+            ToastKind::Record => colors.food,
+            ToastKind::PowerUp => colors.power,
+            ToastKind::Network => colors.portal,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Toast {
+    kind: ToastKind,
+    text: String,
+    /// Seconds it has left up.
+    left: f64,
+}
+
+/// Short notices stacked down the top right of the screen, below the HUD,
+/// each going after a few seconds. Only a few are up at once; the rest
+/// queue up behind them.
+#[derive(Clone, Debug, Default)]
+pub struct Toasts {
+    shown: Vec<Toast>,
+    waiting: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, kind: ToastKind, text: String) {
+        let toast = Toast {
+            kind,
+            text,
+            left: TOAST_TIME,
+        };
+        if self.shown.len() < MAX_SHOWN {
+            self.shown.push(toast);
+        } else {
+            self.waiting.push_back(toast);
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        for toast in &mut self.shown {
+            toast.left -= dt;
+        }
+        self.shown.retain(|toast| toast.left > 0.);
+        while self.shown.len() < MAX_SHOWN {
+            let Some(toast) = self.waiting.pop_front() else {
+                break;
+            };
+            self.shown.push(toast);
+        }
+    }
+
+    /// Draws the stack from `row` down, against the right edge.
+    pub fn draw(&self, renderer: &Renderer, frame: &mut Frame, row: u16) {
+        for (index, toast) in self.shown.iter().enumerate() {
+            let text = format!(" {} ", toast.text);
+            let col = frame
+                .width()
+                .saturating_sub(text.chars().count() as u16 + 1);
+            let fg = if toast.left < FADE_TIME && !renderer.reduced_motion {
+                renderer.theme.colors.wall
+            } else {
+                toast.kind.color(renderer)
+            };
+            frame.print_fg(col, row + index as u16, &text, fg);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{
+    players::Player,
+    render::{
+        Frame,
+        Renderer,
+    },
+    rng::Rng,
+};
+
+/// Fewest and most players a tournament can have.
+pub const MIN_PLAYERS: usize = 4;
+pub const MAX_PLAYERS: usize = 8;
+
+/// Characters a player's name can run to.
+const MAX_NAME: usize = 12;
+
+/// A single-elimination bracket: every player's drawn against another, and
+/// whoever wins each match goes on to play the winner of the one beside it
+/// until there's one left. With fewer players than a full bracket, some
+/// are drawn against no one and go straight through.
+#[derive(Clone, Debug)]
+pub struct Bracket {
+    pub players: Vec<String>,
+    /// Who's in each place in each round, from the first, by index into
+    /// `players`. In the first round `None` is a bye; after it, a place still
+    /// to be decided.
+    rounds: Vec<Vec<Option<usize>>>,
+    /// Whether the last match was drawn, so the next is the same one again.
+    replaying: bool,
+}
+
+impl Bracket {
+    /// Draws `players` into a bracket in an order picked by `seed`.
+    pub fn new(players: Vec<String>, seed: u64) -> Self {
+        let mut order: Vec<usize> = (0..players.len()).collect();
+        let mut rng = Rng::new(seed);
+        for index in (1..order.len()).rev() {
+            order.swap(index, rng.below(index as u64 + 1) as usize);
+        }
+        let size = players.len().next_power_of_two().max(2);
+        // Everyone in the top half of the draw gets a place to themselves,
+        // so a bye is never drawn against a bye.
+        let mut first = vec![None; size];
+        for (index, &player) in order.iter().enumerate() {
+            let place = if index < size / 2 {
+                index * 2
+            } else {
+                (index - size / 2) * 2 + 1
+            };
+            first[place] = Some(player);
+        }
+        let mut rounds = vec![first];
+        while rounds.last().is_some_and(|round| round.len() > 1) {
+            let len = rounds.last().map_or(0, Vec::len) / 2;
+            rounds.push(vec![None; len]);
+        }
+        for (place, pair) in rounds[0].clone().chunks(2).enumerate() {
+            if let [Some(player), None] | [None, Some(player)] = *pair {
+                rounds[1][place] = Some(player);
+            }
+        }
+        Self {
+            players,
+            rounds,
+            replaying: false,
+        }
+    }
+
+    /// The next match to play: its round and where in it, if any are left.
+    fn next(&self) -> Option<(usize, usize)> {
+        (0..self.rounds.len() - 1).find_map(|round| {
+            (0..self.rounds[round + 1].len())
+                .find(|&place| {
+                    self.rounds[round + 1][place].is_none()
+                        && self.rounds[round][place * 2].is_some()
+                        && self.rounds[round][place * 2 + 1].is_some()
+                })
+                .map(|place| (round, place))
+        })
+    }
+
+    /// Who plays the next match, first player first.
+    pub fn next_match(&self) -> Option<[Player; 2]> {
+        let (round, place) = self.next()?;
+        let player = |side: usize| {
+            self.rounds[round][place * 2 + side].map(|index| Player {
+                name: self.players[index].clone(),
+                color: None,
+            })
+        };
+        Some([player(0)?, player(1)?])
+    }
+
+    /// Puts the `winner` of the next match through, 0 for its first player
+    /// and 1 for its second. With no winner it was drawn, and is played again.
+    pub fn record(&mut self, winner: Option<usize>) {
+        self.replaying = winner.is_none();
+        if let Some(winner) = winner
+            && let Some((round, place)) = self.next()
+        {
+            self.rounds[round + 1][place] = self.rounds[round][place * 2 + winner];
+        }
+    }
+
+    /// Whoever's won it all, once someone has.
+    pub fn champion(&self) -> Option<&str> {
+        let winner = self.rounds.last()?.first().copied().flatten()?;
+        Some(&self.players[winner])
+    }
+
+    /// Draws every round side by side, the next match picked out, and who
+    /// plays it or who's won.
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let rounds = &self.rounds;
+        let column = MAX_NAME as u16 + 3;
+        let rows = rounds[0].len() as u16 * 2 - 1;
+        let left = width.saturating_sub(column * rounds.len() as u16) / 2;
+        let top = height.saturating_sub(rows + 6) / 2;
+        frame.print_centered(top, "TOURNAMENT", colors.head);
+        let next = self.next();
+        // Each place sits level with the middle of the two it's decided by.
+        let mut at: Vec<u16> = (0..rounds[0].len() as u16).map(|place| place * 2).collect();
+        for (round, places) in rounds.iter().enumerate() {
+            if round > 0 {
+                at = at.chunks(2).map(|pair| (pair[0] + pair[1]) / 2).collect();
+            }
+            for (place, &player) in places.iter().enumerate() {
+                let playing = next.is_some_and(|(next, pair)| next == round && place / 2 == pair);
+                let won = round == rounds.len() - 1;
+                let (name, fg) = match player {
+                    Some(index) if won => (self.players[index].as_str(), colors.bonus),
+                    Some(index) if playing => (self.players[index].as_str(), colors.head),
+                    Some(index) => (self.players[index].as_str(), colors.body),
+                    None if round == 0 => ("bye", colors.wall),
+                    None => ("-", colors.wall),
+                };
+                frame.print_fg(left + column * round as u16, top + 2 + at[place], name, fg);
+            }
+        }
+        let row = top + 3 + rows;
+        match (self.champion(), self.next_match()) {
+            (Some(champion), _) => {
+                frame.print_centered(
+                    row,
+                    &format!("{champion} wins the tournament!"),
+                    colors.bonus,
+                );
+                frame.print_centered(row + 2, "enter  done", colors.wall);
+            }
+            (None, Some([first, second])) => {
+                frame.print_centered(
+                    row,
+                    &format!("next: {} vs {}", first.name, second.name),
+                    colors.head,
+                );
+                if self.replaying {
+                    let dash = if renderer.caps.unicode {
+                        '\u{2014}'
+                    } else {
+                        '-'
+                    };
+                    frame.print_centered(row + 1, &format!("draw {dash} replaying"), colors.bonus);
+                }
+                frame.print_centered(
+                    row + 2,
+                    "enter  play   m  leave the tournament",
+                    colors.wall,
+                );
+            }
+            (None, None) => {}
+        }
+        frame
+    }
+}
+
+/// Entering the players' names for a tournament, then the bracket between
+/// its matches, with who's playing next or who's won.
+#[derive(Clone, Debug, Default)]
+pub struct TournamentScreen {
+    /// Names entered so far.
+    names: Vec<String>,
+    text: String,
+    /// Why the last name couldn't be added, or the bracket drawn up.
+    error: Option<String>,
+    /// The bracket, once the names are in.
+    pub bracket: Option<Bracket>,
+}
+
+impl TournamentScreen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The screen between matches of `bracket`.
+    pub fn showing(bracket: Bracket) -> Self {
+        Self {
+            bracket: Some(bracket),
+            ..Self::default()
+        }
+    }
+
+    /// Whether names are still being typed.
+    pub fn entering(&self) -> bool {
+        self.bracket.is_none()
+    }
+
+    pub fn push(&mut self, ch: char) {
+        if self.text.chars().count() < MAX_NAME {
+            self.text.push(ch);
+            self.error = None;
+        }
+    }
+
+    pub fn erase(&mut self) {
+        self.text.pop();
+        self.error = None;
+    }
+
+    /// Adds the name typed, or with nothing typed draws up the bracket with
+    /// `seed` if there are players enough.
+    pub fn submit(&mut self, seed: u64) {
+        let name = self.text.trim().to_string();
+        self.error = if name.is_empty() {
+            if self.names.len() < MIN_PLAYERS {
+                Some(format!("a tournament needs at least {MIN_PLAYERS} players"))
+            } else {
+                self.bracket = Some(Bracket::new(std::mem::take(&mut self.names), seed));
+                None
+            }
+        } else if self.names.contains(&name) {
+            Some(format!("{name} is already in"))
+        } else if self.names.len() >= MAX_PLAYERS {
+            Some(format!(
+                "a tournament can have at most {MAX_PLAYERS} players"
+            ))
+        } else {
+            self.names.push(name);
+            self.text.clear();
+            None
+        };
+    }
+
+    pub fn draw(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        match &self.bracket {
+            Some(bracket) => bracket.draw(renderer, width, height),
+            None => self.draw_entry(renderer, width, height),
+        }
+    }
+
+    fn draw_entry(&self, renderer: &Renderer, width: u16, height: u16) -> Frame {
+        let mut frame = renderer.frame(width, height);
+        let colors = &renderer.theme.colors;
+        let top = height.saturating_sub(MAX_PLAYERS as u16 + 8) / 2;
+        frame.print_centered(top, "TOURNAMENT", colors.head);
+        for (index, name) in self.names.iter().enumerate() {
+            let line = format!("{}. {name:<MAX_NAME$}", index + 1);
+            frame.print_centered(top + 2 + index as u16, &line, colors.body);
+        }
+        if self.names.len() < MAX_PLAYERS {
+            let typed = format!("{}_", self.text);
+            let line = format!("{}. {typed:<MAX_NAME$}", self.names.len() + 1);
+            frame.print_centered(top + 2 + self.names.len() as u16, &line, colors.bonus);
+        }
+        let row = top + 3 + MAX_PLAYERS as u16;
+        if let Some(error) = &self.error {
+            frame.print_centered(row, error, colors.food);
+        }
+        frame.print_centered(
+            row + 2,
+            &format!("type a name and enter for each of {MIN_PLAYERS} to {MAX_PLAYERS} players"),
+            colors.wall,
+        );
+        frame.print_centered(
+            row + 3,
+            "enter with no name  draw the bracket   esc  back",
+            colors.wall,
+        );
+        frame
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::mission::MissionEvent;
+
+/// Apples to eat in the lesson on keeping clear of the tail.
+const TAIL_APPLES: u32 = 3;
+
+/// A step of the tutorial, each waiting on the player to do what it asks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Lesson {
+    TurnRight,
+    TurnLeft,
+    /// An apple is put in front of the snake to eat.
+    Eat,
+    /// The snake is grown long, with apples to collect around its tail.
+    AvoidTail,
+    Done,
+}
+
+impl Lesson {
+    /// What the player is asked to do.
+    pub fn prompt(self) -> String {
+        match self {
+            Lesson::TurnRight => "press right or d to turn right".to_string(),
+            Lesson::TurnLeft => "now left or a to turn left".to_string(),
+            Lesson::Eat => "eat the apple to grow".to_string(),
+            Lesson::AvoidTail => {
+                format!("eat {TAIL_APPLES} more without running into your tail")
+            }
+            Lesson::Done => "that's it - q to leave, or play on".to_string(),
+        }
+    }
+
+    fn next(self) -> Lesson {
+        match self {
+            Lesson::TurnRight => Lesson::TurnLeft,
+            Lesson::TurnLeft => Lesson::Eat,
+            Lesson::Eat => Lesson::AvoidTail,
+            Lesson::AvoidTail | Lesson::Done => Lesson::Done,
+        }
+    }
+}
+
+/// Walks a first-time player through the basics one lesson at a time,
+/// moving on once they've shown they can do what it asks.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Tutorial {
+    pub lesson: Lesson,
+    /// Apples eaten towards the current lesson.
+    progress: u32,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            lesson: Lesson::TurnRight,
+            progress: 0,
+        }
+    }
+
+    /// Checks `event` against the current lesson, returning true if it
+    /// completed it and the next one has started.
+    pub fn observe(&mut self, event: MissionEvent) -> bool {
+        let done = match (self.lesson, event) {
+            (Lesson::TurnRight, MissionEvent::Turned(angle)) => angle > 0.,
+            (Lesson::TurnLeft, MissionEvent::Turned(angle)) => angle < 0.,
+            (Lesson::Eat, MissionEvent::Ate) => true,
+            (Lesson::AvoidTail, MissionEvent::Ate) => {
+                self.progress += 1;
+                self.progress >= TAIL_APPLES
+            }
+            _ => false,
+        };
+        if done {
+            self.lesson = self.lesson.next();
+            self.progress = 0;
+        }
+        done
+    }
+
+    /// Starts the current lesson over, after a crash.
+    pub fn retry(&mut self) {
+        self.progress = 0;
+    }
+
+    /// Apples left to eat in the current lesson.
+    pub fn apples_left(&self) -> u32 {
+        match self.lesson {
+            Lesson::Eat => 1,
+            Lesson::AvoidTail => TAIL_APPLES - self.progress,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    game::{
+        DeathCause,
+        Snake,
+    },
+    players::Player,
+    render::{
+        Frame,
+        Renderer,
+    },
+};
+
+/// Rounds a player has to win to take a versus match, unless set.
+pub const FIRST_TO: u32 = 3;
+
+/// Seconds the scoreboard stays up between rounds, before the countdown
+/// into the next.
+pub const ROUND_BREAK: f64 = 2.5;
+
+/// A versus match between two snakes on one keyboard, played over rounds.
+/// A round ends when a snake crashes, the other taking it, and the first to
+/// win `first_to` rounds takes the match. Players are numbered from 0 for
+/// the first, on the arrows, and 1 for the second, as in a game's
+/// `players`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Match {
+    pub first_to: u32,
+    /// Rounds each player has won.
+    pub wins: [u32; 2],
+    /// The round being played, counting from 1.
+    pub round: u32,
+    /// Who took the last round once there's been one, `None` inside for a
+    /// round both lost at once.
+    pub last: Option<Option<usize>>,
+    /// Seconds left of the scoreboard between rounds.
+    pub intermission: f64,
+    /// Where each player started the first round. They swap every round.
+    spawns: [Snake; 2],
+    /// Players out of the round on this move, to be settled once every
+    /// snake has moved, and what put the last of them out.
+    fallen: Vec<usize>,
+    cause: Option<DeathCause>,
+}
+
+impl Match {
+    pub fn new(first_to: u32, spawns: [Snake; 2]) -> Self {
+        Self {
+            first_to: first_to.max(1),
+            wins: [0; 2],
+            round: 1,
+            last: None,
+            intermission: 0.,
+            spawns,
+            fallen: Vec::new(),
+            cause: None,
+        }
+    }
+
+    /// Where each player starts this round: where they started the first,
+    /// or each other's on every other round.
+    pub fn spawns(&self) -> [Snake; 2] {
+        let [first, second] = self.spawns.clone();
+        if self.round.is_multiple_of(2) {
+            [second, first]
+        } else {
+            [first, second]
+        }
+    }
+
+    /// Puts `player` out of the round for `cause`.
+    pub fn fall(&mut self, player: usize, cause: DeathCause) {
+        if !self.fallen.contains(&player) {
+            self.fallen.push(player);
+        }
+        self.cause = Some(cause);
+    }
+
+    /// Ends the round if anyone's out of it, giving it to whoever's left.
+    /// Returns what put the loser out if it did, or `None` while the round
+    /// goes on.
+    pub fn settle(&mut self) -> Option<DeathCause> {
+        if self.fallen.is_empty() {
+            return None;
+        }
+        let winner = match self.fallen.as_slice() {
+            [loser] => Some(1 - loser),
+            _ => None,
+        };
+        if let Some(winner) = winner {
+            self.wins[winner] += 1;
+        }
+        self.last = Some(winner);
+        self.fallen.clear();
+        self.cause.take()
+    }
+
+    /// Whoever's won the match, once someone has.
+    pub fn winner(&self) -> Option<usize> {
+        (0..2).find(|&player| self.wins[player] >= self.first_to)
+    }
+
+    /// Moves on to the next round, starting with the scoreboard.
+    pub fn next_round(&mut self) {
+        self.round += 1;
+        self.intermission = ROUND_BREAK;
+    }
+
+    /// The match score, first player first, as `2 - 1`.
+    pub fn scoreline(&self) -> String {
+        format!("{} - {}", self.wins[0], self.wins[1])
+    }
+
+    /// How the match went between `players`, once it's over, as the game
+    /// over screen heads it.
+    pub fn verdict(&self, players: &[Player; 2]) -> Option<String> {
+        let winner = self.winner()?;
+        Some(format!(
+            "{} takes the match {}",
+            players[winner].name,
+            self.scoreline()
+        ))
+    }
+
+    /// Draws the scoreboard shown between rounds in a box over the middle
+    /// of `frame`: which of `players` took the last round, the score, and
+    /// the round coming.
+    pub fn draw_scoreboard(&self, renderer: &Renderer, frame: &mut Frame, players: &[Player; 2]) {
+        let colors = &renderer.theme.colors;
+        let took = match self.last {
+            Some(Some(winner)) => {
+                format!("{} takes round {}", players[winner].name, self.round - 1)
+            }
+            _ => format!("round {} is a draw", self.round - 1),
+        };
+        let lines = [
+            took,
+            format!(
+                "{}  {}  {}",
+                players[0].name,
+                self.scoreline(),
+                players[1].name
+            ),
+            format!("first to {}", self.first_to),
+        ];
+        let inner = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let (width, height) = (inner + 4, lines.len() as u16 + 2);
+        let col = frame.width().saturating_sub(width) / 2;
+        let row = frame.height().saturating_sub(height) / 2;
+        let blank = " ".repeat(width as usize);
+        for y in row..row + height {
+            frame.print(col, y, &blank);
+        }
+        frame.outline(col, row, width, height, renderer.caps.unicode, colors.wall);
+        for (index, line) in lines.iter().enumerate() {
+            let fg = if index == 0 {
+                colors.head
+            } else {
+                colors.bonus
+            };
+            frame.print_centered(row + 1 + index as u16, line, fg);
+        }
+    }
+}
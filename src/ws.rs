@@ -0,0 +1,296 @@
+use std::{
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        Write,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    sync::mpsc::{
+        self,
+        Receiver,
+        Sender,
+    },
+    thread,
+};
+
+use crate::logging::log;
+
+/// The page served at `/`, which connects back for the feed and draws it.
+const VIEWER: &str = include_str!("viewer.html");
+
+/// What every WebSocket handshake's key is hashed with, from RFC 6455.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Something for the thread writing to spectators to do.
+enum Message {
+    /// Sends a line of state to every spectator.
+    Line(String),
+    /// Starts sending to a new spectator, beginning with the last line.
+    Join(TcpStream),
+}
+
+/// The state feed written out to browsers over WebSockets, for watching a
+/// game live. It listens on a port on this machine, answering a plain
+/// request for `/` with a page that connects back and draws what it's
+/// sent. It's written to like any other stream, one line of state at a
+/// time.
+pub struct Spectators {
+    messages: Sender<Message>,
+    /// What's been written since the last whole line.
+    partial: Vec<u8>,
+}
+
+impl Spectators {
+    /// Listens on `port`, on this machine only.
+    pub fn listen(port: u16) -> Result<Spectators, String> {
+        let listener =
+            TcpListener::bind(("127.0.0.1", port)).map_err(|err| format!("port {port}: {err}"))?;
+        log!(Info, "spectators can watch at http://127.0.0.1:{port}/");
+        let (messages, receiver) = mpsc::channel();
+        let joins = messages.clone();
+        thread::spawn(move || {
+            for client in listener.incoming().flatten() {
+                let joins = joins.clone();
+                thread::spawn(move || {
+                    if let Err(err) = greet(client, &joins) {
+                        log!(Debug, "spectator: {err}");
+                    }
+                });
+            }
+        });
+        thread::spawn(move || broadcast(receiver));
+        Ok(Spectators {
+            messages,
+            partial: Vec::new(),
+        })
+    }
+}
+
+impl Write for Spectators {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(bytes);
+        while let Some(end) = self.partial.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line[..end]).into_owned();
+            if self.messages.send(Message::Line(line)).is_err() {
+                return Err(io::Error::other("spectators stopped"));
+            }
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the request `client` opens with, serving the viewer or, for a
+/// WebSocket, shaking hands and passing it on to be sent the feed.
+fn greet(client: TcpStream, joins: &Sender<Message>) -> Result<(), String> {
+    let mut out = client.try_clone().map_err(|err| err.to_string())?;
+    let mut lines = BufReader::new(client).lines();
+    let request = lines
+        .next()
+        .ok_or("hung up")?
+        .map_err(|err| err.to_string())?;
+    let mut key = None;
+    for line in lines {
+        let line = line.map_err(|err| err.to_string())?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("sec-websocket-key")
+        {
+            key = Some(value.trim().to_string());
+        }
+    }
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    let write = |out: &mut TcpStream, response: String| {
+        out.write_all(response.as_bytes())
+            .map_err(|err| err.to_string())
+    };
+    match (path, key) {
+        (_, Some(key)) => {
+            let mut hashed = key.into_bytes();
+            hashed.extend_from_slice(HANDSHAKE_GUID.as_bytes());
+            let accept = base64(&sha1(&hashed));
+            write(
+                &mut out,
+                format!(
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+                ),
+            )?;
+            log!(Info, "spectator connected");
+            joins
+                .send(Message::Join(out))
+                .map_err(|_| "the game stopped".to_string())
+        }
+        ("/", None) => write(
+            &mut out,
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{VIEWER}",
+                VIEWER.len()
+            ),
+        ),
+        (_, None) => write(
+            &mut out,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        ),
+    }
+}
+
+/// Sends every line to every spectator, until the game stops, dropping any
+/// that can't be written to.
+fn broadcast(messages: Receiver<Message>) {
+    let mut spectators: Vec<TcpStream> = Vec::new();
+    let mut last: Option<Vec<u8>> = None;
+    for message in messages {
+        match message {
+            Message::Line(line) => {
+                let frame = frame(&line);
+                spectators.retain_mut(|spectator| spectator.write_all(&frame).is_ok());
+                last = Some(frame);
+            }
+            Message::Join(mut spectator) => {
+                if last
+                    .as_ref()
+                    .is_none_or(|frame| spectator.write_all(frame).is_ok())
+                {
+                    spectators.push(spectator);
+                }
+            }
+        }
+    }
+}
+
+/// `text` as a single unmasked WebSocket text frame.
+fn frame(text: &str) -> Vec<u8> {
+    let len = text.len();
+    let mut frame = vec![0x81];
+    match len {
+        0..=125 => frame.push(len as u8),
+        126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(text.as_bytes());
+    frame
+}
+
+/// The SHA-1 digest of `data`, which the handshake needs and nothing else.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, &word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = next;
+        }
+        for (total, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *total = total.wrapping_add(add);
+        }
+    }
+    let mut digest = [0; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// `bytes` in standard, padded base64.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let padded = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, padded[0], padded[1], padded[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(ALPHABET[(bits >> (18 - 6 * index)) as usize & 63] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn accept_key_matches_the_rfc() {
+        let key = b"dGhlIHNhbXBsZSBub25jZQ==258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+        assert_eq!(base64(&sha1(key)), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn sha1_pads_across_block_boundaries() {
+        // 55 bytes leave just room for the length in the one block, 56 push
+        // it into a second, and 64 fill a block exactly.
+        for (len, digest) in [
+            (0, "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            (55, "c1c8bbdc22796e28c0e15163d20899b65621d65a"),
+            (56, "c2db330f6083854c99d4b5bfb6e8f29f201be699"),
+            (64, "0098ba824b5c16427bd7a1122a5a442a25ec644d"),
+        ] {
+            assert_eq!(hex(&sha1(&vec![b'a'; len])), digest, "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn base64_pads_the_last_group() {
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+    }
+}